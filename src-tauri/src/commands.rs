@@ -24,51 +24,7 @@ pub fn list_profiles() -> Result<Vec<ProfileInfo>, String> {
         .map_err(|e| IpcError::from(e).to_string_result())?;
 
     // Convert to GUI types
-    let gui_profiles: Vec<ProfileInfo> = core_profiles
-        .into_iter()
-        .map(|p| {
-            // Load full metadata to get created timestamp and plugin count
-            let manifest = zprof::core::manifest::load_and_validate(&p.name);
-
-            let (created_at, plugin_count, prompt_mode, prompt_engine, framework_theme) = match manifest {
-                Ok(m) => {
-                    let (prompt_mode_str, engine, theme) = match &m.profile.prompt_mode {
-                        zprof::core::manifest::PromptMode::PromptEngine { engine } => {
-                            ("prompt_engine", Some(engine.clone()), None)
-                        },
-                        zprof::core::manifest::PromptMode::FrameworkTheme { theme } => {
-                            ("framework_theme", None, Some(theme.clone()))
-                        },
-                    };
-                    (
-                        m.profile.created.to_rfc3339(),
-                        m.plugins.enabled.len(),
-                        prompt_mode_str.to_string(),
-                        engine,
-                        theme,
-                    )
-                },
-                Err(_) => (
-                    chrono::Utc::now().to_rfc3339(),
-                    0,
-                    "framework_theme".to_string(),
-                    None,
-                    None,
-                ),
-            };
-
-            ProfileInfo {
-                name: p.name,
-                framework: p.framework,
-                prompt_mode,
-                prompt_engine,
-                framework_theme,
-                active: p.is_active,
-                created_at,
-                plugin_count,
-            }
-        })
-        .collect();
+    let gui_profiles: Vec<ProfileInfo> = core_profiles.into_iter().map(ProfileInfo::load).collect();
 
     Ok(gui_profiles)
 }
@@ -79,6 +35,17 @@ pub fn get_profile(name: String) -> Result<ProfileDetails, String> {
     // Load and validate manifest
     let manifest = zprof::core::manifest::load_and_validate(&name)
         .map_err(|_e| {
+            let manifest_path = zprof::core::manifest::get_manifest_path(&name);
+            let diagnostics = zprof::core::manifest::diagnose(&manifest_path).unwrap_or_default();
+            if diagnostics.iter().any(|d| d.is_error()) {
+                return IpcError::new(
+                    ErrorCode::ManifestError,
+                    format!("Profile '{}' has an invalid manifest", name)
+                )
+                .with_suggestion("Fix the highlighted fields in profile.toml")
+                .with_diagnostics(diagnostics)
+                .to_string_result();
+            }
             IpcError::new(
                 ErrorCode::ProfileNotFound,
                 format!("Profile '{}' not found", name)
@@ -87,25 +54,7 @@ pub fn get_profile(name: String) -> Result<ProfileDetails, String> {
             .to_string_result()
         })?;
 
-    // Convert to GUI types
-    let prompt_mode = match manifest.profile.prompt_mode {
-        zprof::core::manifest::PromptMode::PromptEngine { engine } => {
-            PromptModeInfo::PromptEngine { engine }
-        }
-        zprof::core::manifest::PromptMode::FrameworkTheme { theme } => {
-            PromptModeInfo::FrameworkTheme { theme }
-        }
-    };
-
-    Ok(ProfileDetails {
-        name: manifest.profile.name,
-        framework: manifest.profile.framework,
-        prompt_mode,
-        plugins: manifest.plugins.enabled,
-        env_vars: manifest.env,
-        created_at: manifest.profile.created.to_rfc3339(),
-        modified_at: manifest.profile.modified.to_rfc3339(),
-    })
+    Ok(ProfileDetails::from(manifest))
 }
 
 /// Get the currently active profile name
@@ -142,7 +91,7 @@ pub fn create_profile(config: ProfileConfig) -> Result<String, String> {
     }
 
     // Create manifest from config
-    use zprof::core::manifest::{Manifest, ProfileSection, PluginsSection, PromptMode};
+    use zprof::core::manifest::{self, Manifest, ProfileSection, PluginsSection, PromptMode};
     use chrono::Utc;
 
     let prompt_mode = match config.prompt_mode.as_str() {
@@ -178,16 +127,31 @@ pub fn create_profile(config: ProfileConfig) -> Result<String, String> {
             prompt_mode,
             created: now,
             modified: now,
+            extends: None,
         },
         plugins: PluginsSection {
             enabled: config.plugins,
+            entry: Vec::new(),
         },
-        env: config.env_vars,
+        env: config
+            .env_vars
+            .into_iter()
+            .map(|(key, value)| (key, value.into()))
+            .collect(),
+        aliases: Default::default(),
+        options: Default::default(),
+        path: Default::default(),
+        schema_version: manifest::CURRENT_SCHEMA_VERSION,
+        hooks: Default::default(),
+        keys: Default::default(),
+        completion: Default::default(),
+        when: Default::default(),
+        snippets: Vec::new(),
     };
 
     // Validate manifest
     manifest.validate()
-        .map_err(|e| IpcError::from(e).to_string_result())?;
+        .map_err(|e| IpcError::from(e).with_diagnostics(manifest.check()).to_string_result())?;
 
     // Create profile directory
     std::fs::create_dir_all(&profile_path)
@@ -203,6 +167,9 @@ pub fn create_profile(config: ProfileConfig) -> Result<String, String> {
     let manifest_path = profile_path.join("profile.toml");
     manifest.write_to_file(&manifest_path)
         .map_err(|e| IpcError::from(e).to_string_result())?;
+    if let Err(e) = zprof::core::schema::write_schema_file() {
+        log::warn!("Could not write profile.toml JSON Schema: {e:#}");
+    }
 
     // Generate shell configs
     use zprof::shell::generator::generate_zshrc_from_manifest;
@@ -225,6 +192,130 @@ pub fn create_profile(config: ProfileConfig) -> Result<String, String> {
     Ok(config.name)
 }
 
+/// Update an existing profile from configuration
+///
+/// Only the profile's own profile.toml is edited, and only the values that
+/// changed: comments and layout in the file are kept. Environment variables
+/// shown by source (secrets) stay as they are unless the value was changed.
+#[tauri::command]
+pub fn update_profile(config: ProfileConfig) -> Result<String, String> {
+    config.validate()
+        .map_err(|e| {
+            IpcError::new(ErrorCode::InvalidInput, e)
+                .to_string_result()
+        })?;
+
+    use zprof::core::manifest::{self, PromptMode};
+
+    let manifest_path = manifest::get_manifest_path(&config.name);
+    if !manifest_path.exists() {
+        return Err(IpcError::new(
+            ErrorCode::ProfileNotFound,
+            format!("Profile '{}' not found", config.name)
+        )
+        .with_suggestion("Run list_profiles to see available profiles")
+        .to_string_result());
+    }
+
+    let mut manifest = manifest::load_unresolved(&config.name)
+        .map_err(|e| {
+            let diagnostics = manifest::diagnose(&manifest_path).unwrap_or_default();
+            IpcError::from(e).with_diagnostics(diagnostics).to_string_result()
+        })?;
+
+    manifest.profile.framework = config.framework.clone();
+    manifest.profile.prompt_mode = match config.prompt_mode.as_str() {
+        "prompt_engine" => {
+            let engine = config.prompt_engine.clone().ok_or_else(|| {
+                IpcError::new(
+                    ErrorCode::InvalidInput,
+                    "prompt_engine is required when prompt_mode is 'prompt_engine'"
+                )
+                .to_string_result()
+            })?;
+            PromptMode::PromptEngine { engine }
+        }
+        "framework_theme" => {
+            PromptMode::FrameworkTheme {
+                theme: config.framework_theme.clone().unwrap_or_default(),
+            }
+        }
+        _ => {
+            return Err(IpcError::new(
+                ErrorCode::InvalidInput,
+                "prompt_mode must be 'prompt_engine' or 'framework_theme'"
+            )
+            .to_string_result());
+        }
+    };
+
+    // Plugins configured by a [[plugins.entry]] keep their entry; the rest
+    // go in `enabled`
+    let plugins = &mut manifest.plugins;
+    plugins.entry.retain(|entry| config.plugins.contains(&entry.name));
+    let enabled = std::mem::take(&mut plugins.enabled);
+    plugins.enabled = config
+        .plugins
+        .iter()
+        .filter(|name| enabled.contains(*name) || !plugins.entry.iter().any(|e| &e.name == *name))
+        .cloned()
+        .collect();
+
+    let mut env = std::mem::take(&mut manifest.env);
+    manifest.env = config
+        .env_vars
+        .into_iter()
+        .map(|(key, value)| match env.remove(&key) {
+            Some(existing) if existing.to_string() == value => (key, existing),
+            _ => (key, value.into()),
+        })
+        .collect();
+    manifest.profile.modified = chrono::Utc::now();
+
+    manifest.validate()
+        .map_err(|e| IpcError::from(e).with_diagnostics(manifest.check()).to_string_result())?;
+
+    manifest.write_to_file(&manifest_path)
+        .map_err(|e| IpcError::from(e).to_string_result())?;
+
+    // Regenerate from the resolved manifest, in case the profile extends another
+    let resolved = manifest::load_and_validate(&config.name)
+        .map_err(|e| IpcError::from(e).to_string_result())?;
+    zprof::shell::generator::write_generated_files(&config.name, &resolved)
+        .map_err(|e| IpcError::from(e).to_string_result())?;
+
+    log::info!("Updated profile '{}' successfully", config.name);
+
+    Ok(config.name)
+}
+
+/// Copy a profile under a new name
+///
+/// Returns the new profile's directory.
+#[tauri::command]
+pub fn copy_profile(source: String, destination: String) -> Result<String, String> {
+    let path = zprof::core::copy::copy_profile(&source, &destination)
+        .map_err(|e| IpcError::from(e).to_string_result())?;
+
+    log::info!("Copied profile '{}' to '{}'", source, destination);
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Rename a profile
+///
+/// Child profiles that extend it, and the active profile setting, follow the
+/// new name. Returns the profile's new directory.
+#[tauri::command]
+pub fn rename_profile(old_name: String, new_name: String) -> Result<String, String> {
+    let rename = zprof::core::copy::rename_profile(&old_name, &new_name)
+        .map_err(|e| IpcError::from(e).to_string_result())?;
+
+    log::info!("Renamed profile '{}' to '{}'", old_name, new_name);
+
+    Ok(rename.profile_path.to_string_lossy().to_string())
+}
+
 /// Delete a profile
 #[tauri::command]
 pub fn delete_profile(name: String) -> Result<(), String> {
@@ -252,73 +343,39 @@ pub fn delete_profile(name: String) -> Result<(), String> {
 }
 
 /// Activate a profile (switch to it)
+///
+/// Returns one warning per activation hook that failed; the switch itself
+/// still happened.
 #[tauri::command]
-pub fn activate_profile(name: String) -> Result<(), String> {
-    // Validate profile exists and is valid
-    let profile_path = zprof::core::profile::get_profile_path(&name)
-        .map_err(|e| IpcError::from(e).to_string_result())?;
-
-    zprof::core::profile::validate_profile(&profile_path)
+pub fn activate_profile(name: String) -> Result<Vec<String>, String> {
+    // Same validation, config/ZDOTDIR update and hooks as `zprof use`
+    let activation = zprof::core::activation::activate_profile(&name)
         .map_err(|e| IpcError::from(e).to_string_result())?;
 
-    // Update config
-    zprof::core::config::update_active_profile(&name)
-        .map_err(|e| IpcError::from(e).to_string_result())?;
-
-    // Update ZDOTDIR in ~/.zshenv
-    use zprof::shell::zdotdir::set_active_profile;
-
-    set_active_profile(&profile_path)
-        .map_err(|e| IpcError::from(e).to_string_result())?;
+    let hook_warnings: Vec<String> = activation
+        .hook_failures
+        .iter()
+        .map(|failure| failure.to_string())
+        .collect();
+    for warning in &hook_warnings {
+        log::warn!("{}", warning);
+    }
 
     log::info!("Activated profile '{}' successfully", name);
 
-    Ok(())
+    Ok(hook_warnings)
 }
 
 /// Get list of available frameworks
 #[tauri::command]
 pub fn get_frameworks() -> Result<Vec<FrameworkInfo>, String> {
-    let frameworks = vec![
-        FrameworkInfo {
-            name: "oh-my-zsh".to_string(),
-            description: "Community-driven zsh framework with 300+ plugins and 140+ themes".to_string(),
-            supports_themes: true,
-            supports_plugins: true,
-        },
-        FrameworkInfo {
-            name: "zimfw".to_string(),
-            description: "Blazing fast zsh framework focused on speed and simplicity".to_string(),
-            supports_themes: true,
-            supports_plugins: true,
-        },
-        FrameworkInfo {
-            name: "prezto".to_string(),
-            description: "Configuration framework for zsh with sane defaults and modules".to_string(),
-            supports_themes: true,
-            supports_plugins: true,
-        },
-        FrameworkInfo {
-            name: "zinit".to_string(),
-            description: "Flexible and fast zsh plugin manager with turbo mode".to_string(),
-            supports_themes: false,
-            supports_plugins: true,
-        },
-        FrameworkInfo {
-            name: "zap".to_string(),
-            description: "Minimal zsh plugin manager, fast and simple".to_string(),
-            supports_themes: false,
-            supports_plugins: true,
-        },
-    ];
-
-    Ok(frameworks)
+    Ok(FrameworkInfo::all())
 }
 
 /// Get available plugins for a specific framework
 #[tauri::command]
 pub fn get_plugins(framework: String) -> Result<Vec<PluginInfo>, String> {
-    use zprof::frameworks::{FrameworkType, plugin::PLUGIN_REGISTRY};
+    use zprof::frameworks::FrameworkType;
 
     // Parse framework type
     let framework_type = match framework.as_str() {
@@ -337,34 +394,13 @@ pub fn get_plugins(framework: String) -> Result<Vec<PluginInfo>, String> {
     };
 
     // Filter plugins compatible with this framework
-    let plugins: Vec<PluginInfo> = PLUGIN_REGISTRY
-        .iter()
-        .filter(|p| p.compatibility.supports_framework(&framework_type))
-        .map(|p| {
-            let category = match p.category {
-                zprof::frameworks::PluginCategory::Git => "git",
-                zprof::frameworks::PluginCategory::Docker => "docker",
-                zprof::frameworks::PluginCategory::Kubernetes => "kubernetes",
-                zprof::frameworks::PluginCategory::Language => "language",
-                zprof::frameworks::PluginCategory::Utility => "utility",
-            };
-
-            PluginInfo {
-                name: p.name.to_string(),
-                description: p.description.to_string(),
-                category: category.to_string(),
-                framework: framework.clone(),
-            }
-        })
-        .collect();
-
-    Ok(plugins)
+    Ok(PluginInfo::for_framework(&framework_type))
 }
 
 /// Get available themes for a specific framework
 #[tauri::command]
 pub fn get_themes(framework: String) -> Result<Vec<ThemeInfo>, String> {
-    use zprof::frameworks::{FrameworkType, theme::THEME_REGISTRY};
+    use zprof::frameworks::FrameworkType;
 
     // Parse framework type
     let framework_type = match framework.as_str() {
@@ -383,18 +419,7 @@ pub fn get_themes(framework: String) -> Result<Vec<ThemeInfo>, String> {
     };
 
     // Filter themes compatible with this framework
-    let themes: Vec<ThemeInfo> = THEME_REGISTRY
-        .iter()
-        .filter(|t| t.compatibility.supports_framework(&framework_type))
-        .map(|t| ThemeInfo {
-            name: t.name.to_string(),
-            description: t.description.to_string(),
-            framework: framework.clone(),
-            preview_url: Some(t.preview.to_string()),
-        })
-        .collect();
-
-    Ok(themes)
+    Ok(ThemeInfo::for_framework(&framework_type))
 }
 
 /// Get available prompt engines
@@ -4,6 +4,7 @@
 //! for transmission across the Tauri IPC boundary.
 
 use serde::{Deserialize, Serialize};
+use zprof::core::diagnostics::Diagnostic;
 
 /// Error type for IPC commands
 ///
@@ -18,6 +19,9 @@ pub struct IpcError {
     /// Optional suggestion for how to resolve the error
     #[serde(skip_serializing_if = "Option::is_none")]
     pub suggestion: Option<String>,
+    /// Problems found in profile.toml, each with the field and location to highlight
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 /// Error codes for different error types
@@ -49,6 +53,7 @@ impl IpcError {
             code,
             message: message.into(),
             suggestion: None,
+            diagnostics: Vec::new(),
         }
     }
 
@@ -58,8 +63,20 @@ impl IpcError {
         self
     }
 
+    /// Attach manifest diagnostics to the error
+    pub fn with_diagnostics(mut self, diagnostics: Vec<Diagnostic>) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+
     /// Convert to a string suitable for Result<T, String>
+    ///
+    /// Errors carrying diagnostics are sent as the serialized `IpcError`, so
+    /// the frontend can parse them and highlight each field.
     pub fn to_string_result(self) -> String {
+        if !self.diagnostics.is_empty() {
+            return serde_json::to_string(&self).unwrap_or(self.message);
+        }
         if let Some(suggestion) = self.suggestion {
             format!("{}\n\nSuggestion: {}", self.message, suggestion)
         } else {
@@ -105,6 +122,7 @@ impl From<anyhow::Error> for IpcError {
             code,
             message,
             suggestion: None,
+            diagnostics: Vec::new(),
         }
     }
 }
@@ -153,6 +171,19 @@ mod tests {
         assert!(result.contains("Suggestion: Use only lowercase letters and hyphens"));
     }
 
+    #[test]
+    fn test_to_string_result_with_diagnostics() {
+        let diagnostic = Diagnostic::error("profile.framework", "profile.framework must be one of: oh-my-zsh, zap")
+            .with_suggestion(Some("oh-my-zsh"));
+        let err = IpcError::new(ErrorCode::ManifestError, "Profile 'work' has an invalid manifest")
+            .with_diagnostics(vec![diagnostic]);
+
+        let parsed: IpcError = serde_json::from_str(&err.to_string_result()).unwrap();
+        assert_eq!(parsed.code, ErrorCode::ManifestError);
+        assert_eq!(parsed.diagnostics[0].field.as_deref(), Some("profile.framework"));
+        assert_eq!(parsed.diagnostics[0].suggestion.as_deref(), Some("oh-my-zsh"));
+    }
+
     #[test]
     fn test_from_anyhow_error() {
         let anyhow_err = anyhow::anyhow!("Profile 'work' not found");
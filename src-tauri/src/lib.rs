@@ -17,6 +17,9 @@ pub fn run() {
             commands::get_profile,
             commands::get_active_profile,
             commands::create_profile,
+            commands::update_profile,
+            commands::copy_profile,
+            commands::rename_profile,
             commands::delete_profile,
             commands::activate_profile,
             commands::get_frameworks,
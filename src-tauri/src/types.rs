@@ -6,63 +6,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// Profile information for display in profile list
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProfileInfo {
-    /// Profile name (unique identifier)
-    pub name: String,
-    /// Framework name (oh-my-zsh, zimfw, etc.)
-    pub framework: String,
-    /// Prompt mode: "prompt_engine" or "framework_theme"
-    pub prompt_mode: String,
-    /// Prompt engine name (if using prompt_engine mode)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub prompt_engine: Option<String>,
-    /// Framework theme name (if using framework_theme mode)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub framework_theme: Option<String>,
-    /// Whether this profile is currently active
-    pub active: bool,
-    /// ISO 8601 timestamp when profile was created
-    pub created_at: String,
-    /// Number of enabled plugins
-    pub plugin_count: usize,
-}
-
-/// Full profile details including configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProfileDetails {
-    /// Profile name
-    pub name: String,
-    /// Framework name
-    pub framework: String,
-    /// Prompt mode discriminator
-    pub prompt_mode: PromptModeInfo,
-    /// List of enabled plugins
-    pub plugins: Vec<String>,
-    /// Environment variables
-    pub env_vars: HashMap<String, String>,
-    /// ISO 8601 timestamp when created
-    pub created_at: String,
-    /// ISO 8601 timestamp when last modified
-    pub modified_at: String,
-}
-
-/// Prompt mode information for profile details
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum PromptModeInfo {
-    /// Using a standalone prompt engine
-    PromptEngine {
-        /// Engine name (starship, powerlevel10k, etc.)
-        engine: String,
-    },
-    /// Using framework's built-in theme system
-    FrameworkTheme {
-        /// Theme name (robbyrussell, agnoster, etc.)
-        theme: String,
-    },
-}
+// Shared with `zprof --format json|toml` so the CLI and GUI describe
+// profiles and registries the same way
+pub use zprof::core::info::{
+    AliasesInfo, FrameworkInfo, PluginInfo, ProfileDetails, ProfileInfo, PromptModeInfo, ThemeInfo,
+};
 
 /// Profile creation/update configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -147,46 +95,6 @@ impl ProfileConfig {
     }
 }
 
-/// Framework information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FrameworkInfo {
-    /// Framework name
-    pub name: String,
-    /// Human-readable description
-    pub description: String,
-    /// Whether this framework supports themes
-    pub supports_themes: bool,
-    /// Whether this framework supports plugins
-    pub supports_plugins: bool,
-}
-
-/// Plugin information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PluginInfo {
-    /// Plugin name
-    pub name: String,
-    /// Description of what the plugin does
-    pub description: String,
-    /// Category (git, docker, utility, etc.)
-    pub category: String,
-    /// Framework this plugin is for
-    pub framework: String,
-}
-
-/// Theme information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ThemeInfo {
-    /// Theme name
-    pub name: String,
-    /// Description of the theme
-    pub description: String,
-    /// Framework this theme is for
-    pub framework: String,
-    /// Optional URL to preview image
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub preview_url: Option<String>,
-}
-
 /// Prompt engine information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PromptEngineInfo {
@@ -78,6 +78,60 @@ export async function createProfile(config: ProfileConfig): Promise<string> {
   }
 }
 
+/**
+ * Update an existing profile from configuration
+ *
+ * Only changed values are written; comments in profile.toml are kept.
+ *
+ * @param config - Profile configuration; `name` selects the profile
+ * @returns Updated profile name
+ * @throws Error if the profile does not exist or the new configuration is invalid
+ */
+export async function updateProfile(config: ProfileConfig): Promise<string> {
+  try {
+    return await invoke<string>("update_profile", { config });
+  } catch (error) {
+    console.error(`Failed to update profile '${config.name}':`, error);
+    throw new Error(`Failed to update profile: ${error}`);
+  }
+}
+
+/**
+ * Copy a profile under a new name
+ *
+ * @param source - Profile to copy
+ * @param destination - Name of the new profile
+ * @returns Path of the new profile directory
+ * @throws Error if the source does not exist or the destination already exists
+ */
+export async function copyProfile(source: string, destination: string): Promise<string> {
+  try {
+    return await invoke<string>("copy_profile", { source, destination });
+  } catch (error) {
+    console.error(`Failed to copy profile '${source}':`, error);
+    throw new Error(`Failed to copy profile: ${error}`);
+  }
+}
+
+/**
+ * Rename a profile
+ *
+ * Profiles that extend it, and the active profile, follow the new name.
+ *
+ * @param oldName - Current profile name
+ * @param newName - New profile name
+ * @returns Path of the renamed profile directory
+ * @throws Error if the profile does not exist or the new name is taken
+ */
+export async function renameProfile(oldName: string, newName: string): Promise<string> {
+  try {
+    return await invoke<string>("rename_profile", { oldName, newName });
+  } catch (error) {
+    console.error(`Failed to rename profile '${oldName}':`, error);
+    throw new Error(`Failed to rename profile: ${error}`);
+  }
+}
+
 /**
  * Delete a profile
  *
@@ -97,11 +151,12 @@ export async function deleteProfile(name: string): Promise<void> {
  * Activate a profile (switch to it)
  *
  * @param name - Profile name to activate
+ * @returns Warnings for activation hooks that failed (the switch still happened)
  * @throws Error if profile cannot be activated (e.g., not found)
  */
-export async function activateProfile(name: string): Promise<void> {
+export async function activateProfile(name: string): Promise<string[]> {
   try {
-    await invoke("activate_profile", { name });
+    return await invoke<string[]>("activate_profile", { name });
   } catch (error) {
     console.error(`Failed to activate profile '${name}':`, error);
     throw new Error(`Failed to activate profile: ${error}`);
@@ -124,3 +124,35 @@ export interface PromptEngineInfo {
   /** Whether this engine is already installed */
   installed?: boolean;
 }
+
+/** A problem found in profile.toml, with the field to highlight */
+export interface ManifestDiagnostic {
+  /** "error" blocks the profile from loading; "warning" does not */
+  severity: "error" | "warning";
+  /** One-line summary */
+  message: string;
+  /** Hints and examples, if any */
+  details?: string;
+  /** Path of the offending field, e.g. "plugins.enabled[2]" */
+  field?: string;
+  /** Suggested replacement for a misspelled value */
+  suggestion?: string;
+  /** 1-based position in profile.toml, if the field is written there */
+  location?: {
+    line: number;
+    column: number;
+    length: number;
+  };
+}
+
+/** Error returned by IPC commands that carry manifest diagnostics (sent as JSON) */
+export interface IpcError {
+  /** Error code, e.g. "manifest_error" */
+  code: string;
+  /** Human-readable error message */
+  message: string;
+  /** How to resolve the error */
+  suggestion?: string;
+  /** Problems found in profile.toml */
+  diagnostics?: ManifestDiagnostic[];
+}
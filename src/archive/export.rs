@@ -32,7 +32,8 @@ pub struct ArchiveMetadata {
 ///
 /// Creates a tar.gz archive containing the profile manifest, generated shell files,
 /// and any custom configuration files. Framework binaries are excluded to keep
-/// the archive size small and maintain portability.
+/// the archive size small and maintain portability. A profile that extends
+/// another is archived with its inheritance resolved, so it imports on its own.
 ///
 /// # Arguments
 ///
@@ -66,8 +67,15 @@ pub fn export_profile(profile_name: &str, output_path: Option<PathBuf>) -> Resul
     let manifest = manifest::load_and_validate(profile_name)
         .context("Cannot export profile with invalid manifest")?;
 
-    // 3. Collect files to include
-    let files_to_include = collect_files(&profile_dir)?;
+    // 3. Collect files to include, leaving out files that hold [env] secrets
+    //    and adding [[snippets]] files, which may live in subdirectories
+    let secrets = secret_files(&manifest, &profile_dir);
+    let mut files_to_include = collect_files(&profile_dir, &secrets)?;
+    for file in snippet_files(&manifest, &profile_dir)? {
+        if !files_to_include.contains(&file) {
+            files_to_include.push(file);
+        }
+    }
     log::info!("Collected {} files for export", files_to_include.len());
 
     if files_to_include.is_empty() {
@@ -77,6 +85,19 @@ pub fn export_profile(profile_name: &str, output_path: Option<PathBuf>) -> Resul
     // 4. Create metadata
     let metadata = create_metadata(&manifest)?;
 
+    // A profile that extends another can't be imported without its parent,
+    // so it is exported in resolved form, as a standalone profile, with the
+    // parent's snippet and hook files bundled alongside
+    let mut inherited = Vec::new();
+    let standalone_manifest = if manifest.profile.extends.is_some() {
+        let mut resolved = manifest::resolve_manifest(&profile_dir.join("profile.toml"))?;
+        resolved.profile.extends = None;
+        inherited = bundle_inherited_files(&mut resolved)?;
+        Some(resolved.to_toml_string()?)
+    } else {
+        None
+    };
+
     // 5. Determine output path
     let archive_path = output_path.unwrap_or_else(|| {
         let cwd = std::env::current_dir().unwrap_or_default();
@@ -92,8 +113,15 @@ pub fn export_profile(profile_name: &str, output_path: Option<PathBuf>) -> Resul
     }
 
     // 7. Create tar.gz archive
-    create_archive(&archive_path, &profile_dir, &files_to_include, &metadata)
-        .context("Failed to create archive")?;
+    create_archive(
+        &archive_path,
+        &profile_dir,
+        &files_to_include,
+        &inherited,
+        standalone_manifest.as_deref(),
+        &metadata,
+    )
+    .context("Failed to create archive")?;
 
     // 8. Validate archive
     validate_archive(&archive_path).context("Archive validation failed")?;
@@ -112,6 +140,101 @@ fn get_profile_dir(profile_name: &str) -> Result<PathBuf> {
         .join(profile_name))
 }
 
+/// Files referenced by `{ file = "..." }` values in `[env]` and in every
+/// `[when.*.env]` overlay, whether or not it applies on this machine
+///
+/// Relative paths are taken relative to the profile directory. Paths are
+/// canonicalized when they exist so symlinks and `..` can't sneak a secret
+/// into the archive.
+fn secret_files(manifest: &manifest::Manifest, profile_dir: &Path) -> Vec<PathBuf> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let conditions = manifest.when.conditions();
+
+    manifest
+        .env
+        .values()
+        .chain(conditions.iter().flat_map(|condition| condition.overlay.env.values()))
+        .filter_map(|value| match value {
+            manifest::EnvValue::Secret(manifest::SecretSource::File(path)) => Some(path),
+            _ => None,
+        })
+        .map(|path| {
+            let path = match path.strip_prefix("~/") {
+                Some(rest) => home.join(rest),
+                None => profile_dir.join(path),
+            };
+            path.canonicalize().unwrap_or(path)
+        })
+        .collect()
+}
+
+/// Files referenced by `[[snippets]]` that live in the profile directory
+///
+/// Snippets inherited through `extends` point into the parent's directory
+/// and are bundled by `bundle_inherited_files` instead.
+fn snippet_files(manifest: &manifest::Manifest, profile_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for file in manifest.snippets.iter().filter_map(|snippet| snippet.file.as_deref()) {
+        if file.starts_with("~/") {
+            continue;
+        }
+        let path = profile_dir.join(file.trim_start_matches("./"));
+        if !path.is_file() {
+            bail!(
+                "✗ Snippet file '{file}' not found in {}\n  → Create it or remove the [[snippets]] entry from profile.toml",
+                profile_dir.display()
+            );
+        }
+        files.push(path);
+    }
+    Ok(files)
+}
+
+/// Directory that inherited files are bundled under in an archive
+const INHERITED_DIR: &str = "inherited";
+
+/// Snippet files and hook scripts a resolved manifest takes from its parents
+///
+/// `resolve_manifest` points them into the parent's directory, which won't
+/// exist where the archive is imported. Each one is rewritten to
+/// `inherited/<parent>/<path>` in `manifest` and returned with the file to
+/// archive under that name.
+fn bundle_inherited_files(manifest: &mut manifest::Manifest) -> Result<Vec<(PathBuf, String)>> {
+    let profiles_dir = dirs::home_dir()
+        .context("Could not find home directory")?
+        .join(".zsh-profiles")
+        .join("profiles");
+
+    let snippets = manifest.snippets.iter_mut().filter_map(|snippet| snippet.file.as_mut());
+    let hooks = [
+        &mut manifest.hooks.on_activate,
+        &mut manifest.hooks.on_deactivate,
+        &mut manifest.hooks.on_shell_start,
+    ]
+    .into_iter()
+    .filter_map(Option::as_mut)
+    .filter(|hook| manifest::hook_script_path(hook).is_some());
+
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    for path in snippets.chain(hooks) {
+        let Some(relative) = path.trim().strip_prefix("~/.zsh-profiles/profiles/") else {
+            continue;
+        };
+        let source = profiles_dir.join(relative);
+        if !source.is_file() {
+            bail!(
+                "✗ Inherited file '{path}' not found\n  → Create it or remove the entry from the parent profile"
+            );
+        }
+        let archived = format!("{INHERITED_DIR}/{relative}");
+        if !files.iter().any(|(_, name)| *name == archived) {
+            files.push((source, archived.clone()));
+        }
+        *path = archived;
+    }
+    Ok(files)
+}
+
 /// Collect files to include in the archive
 ///
 /// Includes:
@@ -124,7 +247,8 @@ fn get_profile_dir(profile_name: &str) -> Result<PathBuf> {
 /// - Framework installations (.oh-my-zsh/, .zimfw/, etc.)
 /// - Cache and temporary files (*.tmp, *.cache, *.log)
 /// - Editor backup files (.swp, .swo, *~)
-fn collect_files(profile_dir: &Path) -> Result<Vec<PathBuf>> {
+/// - Secret files referenced from `[env]`
+fn collect_files(profile_dir: &Path, secrets: &[PathBuf]) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
 
     // Required file
@@ -161,6 +285,12 @@ fn collect_files(profile_dir: &Path) -> Result<Vec<PathBuf>> {
             continue;
         }
 
+        // Never ship a secret's contents
+        if secrets.contains(&path.canonicalize().unwrap_or_else(|_| path.clone())) {
+            log::debug!("Excluding secret file: {path:?}");
+            continue;
+        }
+
         // Skip excluded patterns
         if should_exclude(&path) {
             log::debug!("Excluding file: {path:?}");
@@ -224,10 +354,16 @@ fn create_metadata(manifest: &manifest::Manifest) -> Result<ArchiveMetadata> {
 }
 
 /// Create the tar.gz archive
+///
+/// `inherited` files are archived under the name paired with each one.
+/// `manifest`, when given, is archived as profile.toml instead of the file
+/// on disk.
 fn create_archive(
     archive_path: &Path,
     profile_dir: &Path,
     files: &[PathBuf],
+    inherited: &[(PathBuf, String)],
+    manifest: Option<&str>,
     metadata: &ArchiveMetadata,
 ) -> Result<()> {
     // Create tar.gz file
@@ -257,6 +393,16 @@ fn create_archive(
             .strip_prefix(profile_dir)
             .with_context(|| format!("File not in profile directory: {file_path:?}"))?;
 
+        if let Some(manifest) = manifest.filter(|_| relative_path == Path::new("profile.toml")) {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(manifest.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, relative_path, manifest.as_bytes())
+                .context("Failed to add profile.toml to archive")?;
+            continue;
+        }
+
         let mut file = File::open(file_path)
             .with_context(|| format!("Failed to open file: {file_path:?}"))?;
 
@@ -266,6 +412,16 @@ fn create_archive(
         log::debug!("Added to archive: {relative_path:?}");
     }
 
+    for (file_path, name) in inherited {
+        let mut file = File::open(file_path)
+            .with_context(|| format!("Failed to open file: {file_path:?}"))?;
+
+        tar.append_file(name, &mut file)
+            .with_context(|| format!("Failed to add file to archive: {file_path:?}"))?;
+
+        log::debug!("Added inherited file to archive: {name}");
+    }
+
     // Finalize archive
     tar.finish().context("Failed to finalize tar archive")?;
 
@@ -357,6 +513,21 @@ mod tests {
         assert!(!should_exclude(Path::new("custom.sh")));
     }
 
+    #[test]
+    fn test_collect_files_skips_secret_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let profile_dir = temp_dir.path();
+        fs::write(profile_dir.join("profile.toml"), "").unwrap();
+        fs::write(profile_dir.join("custom.sh"), "").unwrap();
+        fs::write(profile_dir.join("token"), "s3cret").unwrap();
+
+        let secrets = vec![profile_dir.join("token").canonicalize().unwrap()];
+        let files = collect_files(profile_dir, &secrets).unwrap();
+
+        assert!(files.contains(&profile_dir.join("custom.sh")));
+        assert!(!files.contains(&profile_dir.join("token")));
+    }
+
     #[test]
     fn test_format_file_size_bytes() {
         assert_eq!(format_file_size(0), "0 bytes");
@@ -393,11 +564,22 @@ mod tests {
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: PluginsSection {
                 enabled: vec!["git".to_string()],
+                entry: Vec::new(),
             },
             env: HashMap::new(),
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: manifest::CURRENT_SCHEMA_VERSION,
+            hooks: Default::default(),
+            keys: Default::default(),
+            completion: Default::default(),
+            when: Default::default(),
+            snippets: Vec::new(),
         };
 
         let metadata = create_metadata(&manifest).unwrap();
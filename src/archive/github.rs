@@ -11,6 +11,8 @@ use std::io::Write as IoWrite;
 use std::path::{Path, PathBuf};
 
 use crate::archive::import;
+use crate::core::conditions::Machine;
+use crate::core::manifest::Manifest;
 use crate::shell::generator;
 
 /// GitHub import options
@@ -93,8 +95,11 @@ pub fn import_from_github(options: GitHubImportOptions) -> Result<String> {
 
     println!("✓ Found profile manifest");
 
-    // 5. Load and validate manifest (reuse from import module)
-    let mut manifest = match import::load_manifest_from_path(&manifest_path) {
+    // 5. Load and validate manifest (reuse from import module), with this
+    //    machine's [when] overlays
+    let mut manifest = match import::load_manifest_from_path(&manifest_path)
+        .and_then(|m| m.apply_conditions(&Machine::current()))
+    {
         Ok(m) => m,
         Err(e) => {
             // Clean up temp dir on manifest load failure
@@ -136,7 +141,10 @@ pub fn import_from_github(options: GitHubImportOptions) -> Result<String> {
     }
 
     // 9. Copy files from repo to profile directory
-    if let Err(e) = copy_repo_files(&temp_dir, &profile_dir) {
+    let manifest_dir = manifest_path.parent().unwrap_or(&temp_dir);
+    if let Err(e) = copy_repo_files(&temp_dir, &profile_dir)
+        .and_then(|()| import::copy_snippet_files(manifest_dir, &profile_dir, &manifest))
+    {
         // Clean up both temp dir and partial profile on copy failure
         let _ = fs::remove_dir_all(&temp_dir);
         let _ = fs::remove_dir_all(&profile_dir);
@@ -176,6 +184,29 @@ pub fn import_from_github(options: GitHubImportOptions) -> Result<String> {
     Ok(profile_name)
 }
 
+/// Load the manifest from a GitHub repository without importing it
+///
+/// The repository is cloned to a temp directory, which is removed again
+/// once the manifest has been read.
+pub fn load_manifest_from_github(username: &str, repo_name: &str) -> Result<Manifest> {
+    let repo_url = format!("https://github.com/{username}/{repo_name}");
+    println!("→ Cloning repository: {repo_url}");
+
+    let temp_dir = create_temp_clone_dir()?;
+    let manifest = clone_repository(&repo_url, &temp_dir)
+        .with_context(|| format!("Failed to clone repository: {repo_url}"))
+        .and_then(|()| {
+            find_manifest_in_repo(&temp_dir).context("Failed to find profile.toml in repository")
+        })
+        .and_then(|manifest_path| {
+            import::load_manifest_from_path(&manifest_path)
+                .context("Failed to load manifest from repository")
+        });
+    let _ = fs::remove_dir_all(&temp_dir);
+
+    manifest
+}
+
 /// Parse GitHub URL in format: github:user/repo
 ///
 /// # Arguments
@@ -9,11 +9,12 @@ use flate2::read::GzDecoder;
 use serde_json;
 use std::fs::{self, File};
 use std::io::{self, Write as IoWrite};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use tar::Archive;
 
 use crate::archive::export::ArchiveMetadata;
-use crate::core::manifest::Manifest;
+use crate::core::conditions::Machine;
+use crate::core::manifest::{self, Manifest};
 use crate::shell::generator;
 
 /// Import options for profile import
@@ -104,9 +105,11 @@ pub fn import_profile(options: ImportOptions) -> Result<String> {
         }
     };
 
-    // 7. Load manifest from temp directory
+    // 7. Load manifest from temp directory, with this machine's [when] overlays
     let manifest_path = temp_dir.join("profile.toml");
-    let mut manifest = match load_manifest_from_path(&manifest_path) {
+    let mut manifest = match load_manifest_from_path(&manifest_path)
+        .and_then(|m| m.apply_conditions(&Machine::current()))
+    {
         Ok(m) => m,
         Err(e) => {
             // Clean up temp dir on manifest load failure
@@ -129,7 +132,9 @@ pub fn import_profile(options: ImportOptions) -> Result<String> {
     }
 
     // 9. Copy files from temp to profile directory
-    if let Err(e) = copy_profile_files(&temp_dir, &profile_dir) {
+    if let Err(e) = copy_profile_files(&temp_dir, &profile_dir)
+        .and_then(|()| copy_snippet_files(&temp_dir, &profile_dir, &manifest))
+    {
         // Clean up both temp dir and partial profile on copy failure
         let _ = fs::remove_dir_all(&temp_dir);
         let _ = fs::remove_dir_all(&profile_dir);
@@ -241,7 +246,7 @@ pub fn load_manifest_from_path(manifest_path: &Path) -> Result<Manifest> {
     let toml_content = fs::read_to_string(manifest_path)
         .with_context(|| format!("Failed to read manifest: {manifest_path:?}"))?;
 
-    let manifest: Manifest = toml::from_str(&toml_content).with_context(|| {
+    let manifest: Manifest = manifest::parse_manifest(&toml_content).with_context(|| {
         "✗ Failed to parse manifest TOML\n  → The profile.toml in the archive is invalid".to_string()
     })?;
 
@@ -257,6 +262,30 @@ pub fn load_manifest_from_path(manifest_path: &Path) -> Result<Manifest> {
     Ok(manifest)
 }
 
+/// Load the manifest from a .zprof archive without importing it
+///
+/// The archive is extracted to a temp directory, which is removed again
+/// once the manifest has been read.
+pub fn load_manifest_from_archive(archive_path: &Path) -> Result<Manifest> {
+    ensure!(
+        archive_path.exists(),
+        "✗ Archive not found: {}\n  → Check the file path and try again",
+        archive_path.display()
+    );
+
+    let temp_dir = create_temp_extraction_dir()?;
+    let manifest = extract_archive(archive_path, &temp_dir)
+        .context("Failed to extract archive")
+        .and_then(|()| validate_archive_contents(&temp_dir).context("Archive validation failed"))
+        .and_then(|_| {
+            load_manifest_from_path(&temp_dir.join("profile.toml"))
+                .context("Failed to load manifest from archive")
+        });
+    let _ = fs::remove_dir_all(&temp_dir);
+
+    manifest
+}
+
 /// Handle name conflict resolution
 ///
 /// If profile already exists and not force mode:
@@ -352,7 +381,7 @@ fn prompt_new_name() -> Result<String> {
 /// - Any custom configuration files
 ///
 /// Skips:
-/// - .zshrc and .zshenv (will be regenerated)
+/// - .zshrc, .zshenv and .zprofile (will be regenerated)
 /// - metadata.json (only used during import)
 fn copy_profile_files(temp_dir: &Path, profile_dir: &Path) -> Result<()> {
     // Copy profile.toml
@@ -376,6 +405,7 @@ fn copy_profile_files(temp_dir: &Path, profile_dir: &Path) -> Result<()> {
         if filename == "metadata.json"
             || filename == ".zshrc"
             || filename == ".zshenv"
+            || filename == ".zprofile"
             || filename == "profile.toml" // Already copied
         {
             continue;
@@ -391,6 +421,40 @@ fn copy_profile_files(temp_dir: &Path, profile_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Copy the files referenced by `[[snippets]]`, keeping their relative paths
+///
+/// Top-level files are already copied with the other custom files; this picks
+/// up snippets kept in subdirectories such as `snippets/`. Paths that would
+/// leave the profile directory are ignored.
+///
+/// This function is public to allow reuse by GitHub import module
+pub fn copy_snippet_files(src_dir: &Path, profile_dir: &Path, manifest: &Manifest) -> Result<()> {
+    for file in manifest.snippets.iter().filter_map(|snippet| snippet.file.as_deref()) {
+        let relative = Path::new(file);
+        let inside = relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        if !inside || file.starts_with("~/") {
+            continue;
+        }
+
+        let src = src_dir.join(relative);
+        if !src.is_file() {
+            log::warn!("Snippet file not found in import source: {file}");
+            continue;
+        }
+
+        let dst = profile_dir.join(relative);
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create {parent:?}"))?;
+        }
+        fs::copy(&src, &dst).with_context(|| format!("Failed to copy snippet file {file}"))?;
+        log::info!("Copied snippet file: {file}");
+    }
+
+    Ok(())
+}
+
 /// Install framework and plugins per manifest
 ///
 /// This is an integration point for framework installation.
@@ -0,0 +1,138 @@
+//! Explain and resolve directory-based profile switching
+//!
+//! `status` walks through the `.zprof-profile` marker and every
+//! `[[auto.rules]]` entry for a directory. `resolve` is what the generated
+//! `.zshrc` calls on `cd`: it prints `profile<TAB>root<TAB>mode` for a match
+//! with an existing profile and nothing otherwise.
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use std::path::{Path, PathBuf};
+
+use crate::core::auto::{self, AutoMatch, MatchSource, RepoRemotes};
+use crate::core::config::{self, AutoRule};
+use crate::core::{activation, profile};
+
+#[derive(Debug, Args)]
+pub struct AutoArgs {
+    #[command(subcommand)]
+    pub action: AutoAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AutoAction {
+    /// Explain which marker or rule picks the profile for a directory
+    Status {
+        /// Directory to check (default: the current directory)
+        dir: Option<PathBuf>,
+    },
+    /// Print the profile for a directory, for the shell's cd hook
+    #[command(hide = true)]
+    Resolve {
+        /// Directory to check (default: the current directory)
+        dir: Option<PathBuf>,
+    },
+}
+
+pub fn execute(args: AutoArgs) -> Result<()> {
+    match args.action {
+        AutoAction::Status { dir } => status(&directory(dir)?),
+        AutoAction::Resolve { dir } => resolve(&directory(dir)?),
+    }
+}
+
+fn resolve(dir: &Path) -> Result<()> {
+    let auto = config::load_config()?.auto;
+    if let Some(found) = auto::resolve(dir, &auto)? {
+        if profile_exists(&found.profile) {
+            println!("{}\t{}\t{}", found.profile, found.root.display(), auto.mode.name());
+        }
+    }
+    Ok(())
+}
+
+fn status(dir: &Path) -> Result<()> {
+    let config = config::load_config()?;
+    let current = activation::session_profile().or(config.active_profile.clone());
+
+    println!();
+    println!("Directory: {}", dir.display());
+    println!();
+
+    let mut chosen: Option<AutoMatch> = None;
+    match auto::find_marker(dir) {
+        Ok(Some(found)) => {
+            if let MatchSource::Marker(marker) = &found.source {
+                println!("  ✓ {} → '{}'", marker.display(), found.profile);
+            }
+            chosen = Some(found);
+        }
+        Ok(None) => println!("  · No {} in this directory or above", auto::MARKER_FILE),
+        Err(e) => println!("  ✗ {e:#}"),
+    }
+
+    let mut repo = RepoRemotes::new(dir);
+    for (index, rule) in config.auto.rules.iter().enumerate() {
+        let label = format!("Rule {} ({}) → '{}'", index + 1, describe(rule), rule.profile);
+        match auto::check_rule(index + 1, rule, dir, &mut repo) {
+            Ok(Some(found)) => {
+                let detail = match (&found.remote_url, &chosen) {
+                    (_, Some(_)) => " (matches, but an earlier match wins)".to_string(),
+                    (Some(url), None) => format!(" (matches {url})"),
+                    (None, None) => String::new(),
+                };
+                println!("  ✓ {label}{detail}");
+                chosen.get_or_insert(found);
+            }
+            Ok(None) => println!("  · {label}: no match"),
+            Err(e) => println!("  ✗ {label}: {e:#}"),
+        }
+    }
+    if config.auto.rules.is_empty() {
+        println!("  · No [[auto.rules]] in config.toml");
+    }
+    println!();
+
+    match &chosen {
+        Some(found) => {
+            let source = match &found.source {
+                MatchSource::Marker(marker) => marker.display().to_string(),
+                MatchSource::Rule(index) => format!("rule {index}"),
+            };
+            println!("Profile for this directory: {} (from {source})", found.profile);
+            println!("  Applies to: {}", found.root.display());
+            if !profile_exists(&found.profile) {
+                println!("  ⚠ Profile '{}' doesn't exist, so shells won't switch to it", found.profile);
+            }
+        }
+        None => println!("No marker or rule matches; shells keep their profile here"),
+    }
+    if let Some(current) = &current {
+        println!("Current profile: {current}");
+    }
+    println!("Mode: {}", config.auto.mode.name());
+    println!();
+
+    Ok(())
+}
+
+fn describe(rule: &AutoRule) -> String {
+    match (&rule.path, &rule.glob, &rule.remote) {
+        (Some(path), None, None) => format!("path = \"{path}\""),
+        (None, Some(glob), None) => format!("glob = \"{glob}\""),
+        (None, None, Some(remote)) => format!("remote = \"{remote}\""),
+        _ => "invalid".to_string(),
+    }
+}
+
+fn directory(dir: Option<PathBuf>) -> Result<PathBuf> {
+    let cwd = std::env::current_dir().context("Failed to read the current directory")?;
+    Ok(match dir {
+        Some(dir) => cwd.join(dir),
+        None => cwd,
+    })
+}
+
+fn profile_exists(name: &str) -> bool {
+    profile::get_profiles_dir().is_ok_and(|dir| dir.join(name).join("profile.toml").exists())
+}
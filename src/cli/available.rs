@@ -1,6 +1,11 @@
 use anyhow::Result;
 use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+use serde::Serialize;
 
+use crate::cli::completions;
+use crate::cli::format::{self, OutputFormat};
+use crate::core::info::{FrameworkInfo, PluginInfo, ThemeInfo};
 use crate::frameworks::{plugin, theme, FrameworkType};
 
 /// List available frameworks, plugins, and themes
@@ -17,18 +22,34 @@ pub enum AvailableResource {
     /// List all available plugins (optionally filtered by framework)
     Plugins {
         /// Framework to filter plugins by
-        #[arg(short, long)]
+        #[arg(short, long, add = ArgValueCandidates::new(completions::framework_names))]
         framework: Option<String>,
     },
     /// List all available themes (optionally filtered by framework)
     Themes {
         /// Framework to filter themes by
-        #[arg(short, long)]
+        #[arg(short, long, add = ArgValueCandidates::new(completions::framework_names))]
         framework: Option<String>,
     },
 }
 
-pub fn execute(args: AvailableArgs) -> Result<()> {
+/// What `--format json|toml` prints: the one list that was asked for
+///
+/// Without `--framework`, plugins and themes are listed once per framework
+/// that supports them.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Available {
+    Frameworks(Vec<FrameworkInfo>),
+    Plugins(Vec<PluginInfo>),
+    Themes(Vec<ThemeInfo>),
+}
+
+pub fn execute(args: AvailableArgs, format: OutputFormat) -> Result<()> {
+    if !format.is_plain() {
+        return format::print(format, &available(args.resource)?);
+    }
+
     match args.resource {
         AvailableResource::Frameworks => list_frameworks(),
         AvailableResource::Plugins { framework } => list_plugins(framework),
@@ -36,6 +57,25 @@ pub fn execute(args: AvailableArgs) -> Result<()> {
     }
 }
 
+fn available(resource: AvailableResource) -> Result<Available> {
+    let frameworks = |filter: Option<String>| -> Result<Vec<FrameworkType>> {
+        match filter {
+            Some(name) => Ok(vec![parse_framework(&name)?]),
+            None => Ok(FrameworkType::ALL.to_vec()),
+        }
+    };
+
+    Ok(match resource {
+        AvailableResource::Frameworks => Available::Frameworks(FrameworkInfo::all()),
+        AvailableResource::Plugins { framework } => {
+            Available::Plugins(frameworks(framework)?.iter().flat_map(PluginInfo::for_framework).collect())
+        }
+        AvailableResource::Themes { framework } => {
+            Available::Themes(frameworks(framework)?.iter().flat_map(ThemeInfo::for_framework).collect())
+        }
+    })
+}
+
 fn list_frameworks() -> Result<()> {
     println!("\nSupported Frameworks:\n");
 
@@ -0,0 +1,125 @@
+//! Measure and compare interactive shell startup time of profiles
+//!
+//! Runs the measurements in `core::bench`, prints mean, p50 and p95 per
+//! profile, and flags any profile that got noticeably slower than its
+//! baseline. The baseline only moves to a faster result, or to a slower one
+//! with `--accept`, so a regression stays flagged until it is dealt with.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+
+use crate::cli::completions;
+use crate::core::bench::{self, BenchResult, DEFAULT_RUNS};
+use crate::core::{manifest, profile};
+
+/// Breakdown rows shown per profile; the rest are summed up in one line
+const BREAKDOWN_ROWS: usize = 10;
+
+#[derive(Debug, Args)]
+pub struct BenchArgs {
+    /// Profiles to measure (default: all profiles)
+    #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+    pub profiles: Vec<String>,
+
+    /// Number of timed shell starts per profile
+    #[arg(short = 'n', long, default_value_t = DEFAULT_RUNS)]
+    pub runs: usize,
+
+    /// Also profile startup with zsh/zprof and show the time spent in each plugin
+    #[arg(long)]
+    pub breakdown: bool,
+
+    /// Make this run the new baseline, even if it is slower
+    #[arg(long)]
+    pub accept: bool,
+}
+
+pub fn execute(args: BenchArgs) -> Result<()> {
+    let profiles = if args.profiles.is_empty() {
+        profile::list_available_profiles()?
+    } else {
+        args.profiles.clone()
+    };
+
+    println!();
+    println!(
+        "Timing startup of {} profile(s), {} run(s) each...",
+        profiles.len(),
+        args.runs
+    );
+
+    let mut results: Vec<(BenchResult, Option<BenchResult>)> = Vec::new();
+    for name in &profiles {
+        let mut result = bench::measure(name, args.runs)?;
+        if args.breakdown {
+            let manifest_obj = manifest::load_and_validate(name)
+                .with_context(|| format!("Failed to load profile '{name}'"))?;
+            result.breakdown = bench::breakdown(name, &manifest_obj)?;
+        }
+        let baseline = bench::load_baseline(name)?;
+        if args.accept || baseline.as_ref().is_none_or(|b| result.mean_ms < b.mean_ms) {
+            bench::save_baseline(&result)?;
+        }
+        results.push((result, baseline));
+    }
+
+    print_results(&results, args.accept);
+    Ok(())
+}
+
+fn print_results(results: &[(BenchResult, Option<BenchResult>)], accepted: bool) {
+    let width = results.iter().map(|(r, _)| r.profile.len()).max().unwrap_or(0).max("PROFILE".len());
+
+    println!();
+    println!("  {:<width$}  {:>10}  {:>10}  {:>10}", "PROFILE", "MEAN", "P50", "P95");
+    let mut regressed = Vec::new();
+    for (result, previous) in results {
+        let mut line = format!(
+            "  {:<width$}  {:>10}  {:>10}  {:>10}",
+            result.profile,
+            format_ms(result.mean_ms),
+            format_ms(result.p50_ms),
+            format_ms(result.p95_ms)
+        );
+        if let Some(previous) = previous.as_ref().filter(|p| result.regressed_from(p)) {
+            let slower = (result.mean_ms / previous.mean_ms - 1.0) * 100.0;
+            line.push_str(&format!(
+                "  ⚠ {slower:.0}% slower than {} ({})",
+                previous.measured_at.format("%Y-%m-%d %H:%M"),
+                format_ms(previous.mean_ms)
+            ));
+            regressed.push(result.profile.as_str());
+        }
+        println!("{line}");
+    }
+    println!();
+
+    for (result, _) in results.iter().filter(|(r, _)| !r.breakdown.is_empty()) {
+        println!("Time spent in functions for '{}':", result.profile);
+        let name_width = result.breakdown.iter().take(BREAKDOWN_ROWS).map(|c| c.name.len()).max().unwrap_or(0);
+        for cost in result.breakdown.iter().take(BREAKDOWN_ROWS) {
+            println!("  {:<name_width$}  {:>10}", cost.name, format_ms(cost.ms));
+        }
+        let rest = &result.breakdown[result.breakdown.len().min(BREAKDOWN_ROWS)..];
+        if !rest.is_empty() {
+            let ms = rest.iter().map(|c| c.ms).sum();
+            println!("  ... {} more, {} in total", rest.len(), format_ms(ms));
+        }
+        println!();
+    }
+
+    if !regressed.is_empty() {
+        println!("  → Run 'zprof bench {} --breakdown' to see where the time goes", regressed.join(" "));
+        if accepted {
+            println!("  ✓ Accepted as the new baseline");
+        } else {
+            println!("  → If the slowdown is expected, run 'zprof bench {} --accept' to make it the new baseline", regressed.join(" "));
+        }
+        println!();
+    }
+}
+
+fn format_ms(ms: f64) -> String {
+    format!("{ms:.1} ms")
+}
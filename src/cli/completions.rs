@@ -0,0 +1,154 @@
+//! Shell completion scripts and the value completers behind them
+//!
+//! The printed scripts are thin: on every TAB they run zprof again with
+//! `COMPLETE=<shell>` set, and `main` answers from the clap definition before
+//! parsing anything. Profile names, presets, plugins and themes therefore
+//! always match what is installed, and the functions here supply them.
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+use clap_complete::env::{Bash, EnvCompleter, Fish, Zsh};
+use clap_complete::{ArgValueCompleter, CompletionCandidate, PathCompleter};
+use std::ffi::OsStr;
+
+use crate::core::{manifest, profile};
+use crate::frameworks::{plugin, theme, FrameworkType};
+use crate::presets::PRESET_REGISTRY;
+use crate::prompts::engine::PROMPT_ENGINE_NAMES;
+
+/// Environment variable the scripts set when asking zprof for completions
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+#[derive(Debug, Args)]
+pub struct CompletionsArgs {
+    /// Shell to print the completion script for
+    #[arg(value_enum)]
+    pub shell: CompletionShell,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Fish,
+    Zsh,
+}
+
+pub fn execute(args: CompletionsArgs) -> Result<()> {
+    let completer: &dyn EnvCompleter = match args.shell {
+        CompletionShell::Bash => &Bash,
+        CompletionShell::Fish => &Fish,
+        CompletionShell::Zsh => &Zsh,
+    };
+    let mut stdout = std::io::stdout().lock();
+    completer
+        .write_registration(COMPLETE_VAR, "zprof", "zprof", "zprof", &mut stdout)
+        .context("Failed to write completion script")?;
+    Ok(())
+}
+
+/// Installed profiles, described by their framework
+pub fn profile_names() -> Vec<CompletionCandidate> {
+    profile::list_available_profiles()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|name| {
+            let framework = profile::load_profile_metadata(&name).ok().map(|metadata| metadata.framework);
+            CompletionCandidate::new(name).help(framework.map(Into::into))
+        })
+        .collect()
+}
+
+/// `.zprof` archives, descending into directories
+pub fn archive_paths() -> ArgValueCompleter {
+    ArgValueCompleter::new(
+        PathCompleter::file().filter(|path| path.is_dir() || path.extension().is_some_and(|ext| ext == "zprof")),
+    )
+}
+
+/// Installed profiles and `.zprof` archives, for commands that take either
+pub fn profiles_or_archives() -> ArgValueCompleter {
+    let archives = archive_paths();
+    ArgValueCompleter::new(move |current: &OsStr| {
+        let prefix = current.to_string_lossy();
+        let mut candidates: Vec<_> = profile_names()
+            .into_iter()
+            .filter(|candidate| candidate.get_value().to_string_lossy().starts_with(prefix.as_ref()))
+            .collect();
+        candidates.extend(archives.complete(current));
+        candidates
+    })
+}
+
+pub fn preset_ids() -> Vec<CompletionCandidate> {
+    PRESET_REGISTRY
+        .iter()
+        .map(|preset| CompletionCandidate::new(preset.id).help(Some(preset.description.into())))
+        .collect()
+}
+
+pub fn framework_names() -> Vec<CompletionCandidate> {
+    FrameworkType::ALL.iter().map(|framework| CompletionCandidate::new(framework.name())).collect()
+}
+
+pub fn engine_names() -> Vec<CompletionCandidate> {
+    PROMPT_ENGINE_NAMES.iter().map(|&name| CompletionCandidate::new(name)).collect()
+}
+
+/// Plugins from the registry, limited to the framework on the command line
+pub fn plugin_names() -> Vec<CompletionCandidate> {
+    let plugins = match framework_from_args() {
+        Some(framework) => plugin::get_plugins_for_framework(&framework),
+        None => plugin::PLUGIN_REGISTRY.to_vec(),
+    };
+    plugins
+        .into_iter()
+        .map(|plugin| CompletionCandidate::new(plugin.name).help(Some(plugin.description.into())))
+        .collect()
+}
+
+/// Plugins the profile on the command line has enabled
+pub fn enabled_plugin_names() -> Vec<CompletionCandidate> {
+    profile_from_args()
+        .and_then(|name| manifest::load_unresolved(&name).ok())
+        .map(|manifest| manifest.plugins.enabled.into_iter().map(CompletionCandidate::new).collect())
+        .unwrap_or_default()
+}
+
+/// Themes from the registry, limited to the framework on the command line
+pub fn theme_names() -> Vec<CompletionCandidate> {
+    let themes = match framework_from_args() {
+        Some(framework) => theme::get_themes_for_framework(&framework),
+        None => theme::THEME_REGISTRY.to_vec(),
+    };
+    themes
+        .into_iter()
+        .map(|theme| CompletionCandidate::new(theme.name).help(Some(theme.description.into())))
+        .collect()
+}
+
+/// Words of the command line being completed, after the program name
+///
+/// The scripts call `zprof -- zprof <words...>`.
+fn completing_args() -> Vec<String> {
+    std::env::args().skip_while(|arg| arg != "--").skip(2).collect()
+}
+
+/// The first word that names an installed profile
+fn profile_from_args() -> Option<String> {
+    let profiles = profile::list_available_profiles().ok()?;
+    completing_args().into_iter().find(|arg| profiles.contains(arg))
+}
+
+/// The framework given with `--framework`, or else that of the named profile
+fn framework_from_args() -> Option<FrameworkType> {
+    let args = completing_args();
+    let given = args.iter().enumerate().find_map(|(i, arg)| match arg.as_str() {
+        "--framework" | "-f" => args.get(i + 1).cloned(),
+        _ => arg.strip_prefix("--framework=").map(String::from),
+    });
+    let name = match given {
+        Some(name) => name,
+        None => profile::load_profile_metadata(&profile_from_args()?).ok()?.framework,
+    };
+    FrameworkType::from_name(&name)
+}
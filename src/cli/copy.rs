@@ -0,0 +1,32 @@
+use anyhow::Result;
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+
+use crate::cli::completions;
+use crate::core::copy;
+
+#[derive(Debug, Args)]
+pub struct CopyArgs {
+    /// Name of the profile to copy
+    #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+    pub source: String,
+
+    /// Name of the new profile
+    pub destination: String,
+}
+
+pub fn execute(args: CopyArgs) -> Result<()> {
+    // Duplicates the directory, updates profile.name and regenerates
+    let profile_path = copy::copy_profile(&args.source, &args.destination)?;
+
+    println!();
+    println!("✓ Copied profile '{}' to '{}'", args.source, args.destination);
+    println!();
+    println!("  Location: {}", profile_path.display());
+    println!();
+    println!("  → Edit the copy: zprof edit {}", args.destination);
+    println!("  → Switch to it:  zprof use {}", args.destination);
+    println!();
+
+    Ok(())
+}
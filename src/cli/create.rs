@@ -5,14 +5,17 @@
 
 use anyhow::{bail, Context, Result};
 use clap::Args;
+use clap_complete::ArgValueCandidates;
 use dialoguer::Confirm;
-use regex::Regex;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::cli::completions;
 use crate::core::config::Config;
 use crate::core::filesystem::{self, copy_dir_recursive, create_shared_history, get_zprof_dir};
 use crate::core::manifest::{Manifest, PromptMode};
+use crate::core::profile::validate_profile_name;
+use crate::core::schema;
 use crate::frameworks::detect_existing_framework;
 use crate::frameworks::installer::{self, WizardState};
 use crate::tui::{framework_select, plugin_browser, preset_select, prompt_mode_select, setup_mode_select, theme_select};
@@ -29,7 +32,7 @@ pub struct CreateArgs {
     ///
     /// Skip the interactive wizard and use a pre-configured preset.
     /// Example: zprof create work --preset performance
-    #[arg(long, value_name = "PRESET_NAME")]
+    #[arg(long, value_name = "PRESET_NAME", add = ArgValueCandidates::new(completions::preset_ids))]
     pub preset: Option<String>,
 }
 
@@ -344,6 +347,9 @@ pub fn execute(args: CreateArgs) -> Result<()> {
     manifest
         .write_to_file(&manifest_path)
         .context("Failed to write profile manifest")?;
+    if let Err(e) = schema::write_schema_file() {
+        log::warn!("Could not write profile.toml JSON Schema: {e:#}");
+    }
 
     // 6.5. Generate shell configuration files (Story 1.8)
     generator::write_generated_files(&args.name, &manifest)
@@ -358,35 +364,6 @@ pub fn execute(args: CreateArgs) -> Result<()> {
     Ok(())
 }
 
-/// Validate profile name against allowed pattern
-///
-/// Profile names must:
-/// - Be non-empty
-/// - Contain only alphanumeric characters and hyphens
-/// - Not contain path traversal attempts
-pub(crate) fn validate_profile_name(name: &str) -> Result<()> {
-    if name.is_empty() {
-        bail!("✗ Error: Profile name cannot be empty");
-    }
-
-    // Check for valid characters (alphanumeric and hyphens)
-    let valid_pattern = Regex::new(r"^[a-zA-Z0-9\-]+$").unwrap();
-    if !valid_pattern.is_match(name) {
-        bail!(
-            "✗ Error: Invalid profile name '{name}'\n  → Use alphanumeric characters and hyphens only"
-        );
-    }
-
-    // Check for path traversal attempts
-    if name.contains("..") || name.contains('/') || name.contains('\\') {
-        bail!(
-            "✗ Error: Invalid profile name '{name}'\n  → Profile names cannot contain path separators"
-        );
-    }
-
-    Ok(())
-}
-
 /// Get the profile directory path
 pub(crate) fn get_profile_dir(name: &str) -> Result<PathBuf> {
     let base_dir = get_zprof_dir()?;
@@ -6,9 +6,11 @@
 use anyhow::{bail, Context, Result};
 use std::fs;
 
-use crate::cli::create::{get_profile_dir, update_global_config, validate_profile_name};
+use crate::cli::create::{get_profile_dir, update_global_config};
 use crate::core::filesystem::{self, create_shared_history};
 use crate::core::manifest::Manifest;
+use crate::core::profile::validate_profile_name;
+use crate::core::schema;
 use crate::frameworks::installer::{self, WizardState};
 use crate::presets::Preset;
 use crate::shell::generator;
@@ -72,6 +74,9 @@ pub fn create_from_preset(profile_name: &str, preset: &Preset, interactive: bool
     manifest
         .write_to_file(&manifest_path)
         .context("Failed to write profile manifest")?;
+    if let Err(e) = schema::write_schema_file() {
+        log::warn!("Could not write profile.toml JSON Schema: {e:#}");
+    }
 
     // 5. Generate shell configuration files
     generator::write_generated_files(profile_name, &manifest)
@@ -1,13 +1,28 @@
 use anyhow::{Context, Result};
 use clap::Args;
+use serde::Serialize;
+use std::path::PathBuf;
 
-use crate::core::{config::Config, profile};
+use crate::cli::format::{self, OutputFormat};
+use crate::core::info::ProfileInfo;
+use crate::core::{activation, config::Config, profile};
 
 /// Display the currently active profile
 #[derive(Debug, Args)]
 pub struct CurrentArgs {}
 
-pub fn execute(_args: CurrentArgs) -> Result<()> {
+/// What `--format json|toml` prints
+#[derive(Debug, Serialize)]
+struct CurrentProfile {
+    /// The profile this shell uses: its `zprof shell` session's, else the active one
+    profile: Option<ProfileInfo>,
+    /// The profile new terminals use
+    active_profile: Option<String>,
+    /// Set inside a `zprof shell` or `zprof run` session
+    session_profile: Option<String>,
+}
+
+pub fn execute(_args: CurrentArgs, format: OutputFormat) -> Result<()> {
     // Get config path
     let config_path = profile::get_config_path()?;
 
@@ -19,6 +34,23 @@ pub fn execute(_args: CurrentArgs) -> Result<()> {
         );
     }
 
+    if !format.is_plain() {
+        return print_structured(format, config_path);
+    }
+
+    // A 'zprof shell' session overrides the profile from config.toml
+    if let Some(session_profile) = activation::session_profile() {
+        let metadata = profile::load_profile_metadata(&session_profile)
+            .context("✗ Failed to load the profile of this session")?;
+        print_profile(&metadata, " (this session only)");
+
+        let active_profile = Config::load_from_file(config_path).ok().and_then(|c| c.active_profile);
+        if let Some(active) = active_profile.filter(|active| *active != session_profile) {
+            println!("\nNew terminals use '{active}'");
+        }
+        return Ok(());
+    }
+
     // Check if config file exists
     if !config_path.exists() {
         println!("No active profile. Use 'zprof use <name>' to activate a profile.");
@@ -49,19 +81,53 @@ pub fn execute(_args: CurrentArgs) -> Result<()> {
     let metadata = profile::load_profile_metadata(&active_profile_name)
         .context("✗ Failed to load active profile")?;
 
-    // Display profile information
-    println!("Current profile: {}\n", metadata.name);
+    print_profile(&metadata, "");
+
+    Ok(())
+}
+
+fn print_structured(format: OutputFormat, config_path: PathBuf) -> Result<()> {
+    let active_profile = if config_path.exists() {
+        Config::load_from_file(config_path)
+            .context("✗ Failed to read config file")?
+            .active_profile
+    } else {
+        None
+    };
+    let session_profile = activation::session_profile();
+
+    let profile = match session_profile.as_ref().or(active_profile.as_ref()) {
+        Some(name) => {
+            let metadata = profile::load_profile_metadata(name).context("✗ Failed to load the current profile")?;
+            Some(ProfileInfo::load(profile::ProfileInfo {
+                is_active: active_profile.as_ref() == Some(name),
+                name: metadata.name,
+                framework: metadata.framework,
+            }))
+        }
+        None => None,
+    };
+
+    let current = CurrentProfile {
+        profile,
+        active_profile,
+        session_profile,
+    };
+    format::print(format, &current)
+}
+
+/// Print a profile's name, framework and creation date
+fn print_profile(metadata: &profile::ProfileMetadataFull, note: &str) {
+    println!("Current profile: {}{note}\n", metadata.name);
     println!("Framework: {}", metadata.framework);
 
     // Format and display creation date if available
-    if let Some(created) = metadata.created {
-        match format_date(&created) {
+    if let Some(created) = &metadata.created {
+        match format_date(created) {
             Ok(formatted_date) => println!("Created: {formatted_date}"),
             Err(_) => println!("Created: {created}"), // Fallback to raw timestamp if parsing fails
         }
     }
-
-    Ok(())
 }
 
 /// Format ISO 8601 timestamp to human-readable format (e.g., "Oct 31, 2025")
@@ -1,13 +1,16 @@
 use anyhow::{Context, Result};
 use clap::Args;
+use clap_complete::ArgValueCandidates;
 use std::io::{self, Write};
 use std::path::Path;
 
+use crate::cli::completions;
 use crate::core::{filesystem, profile};
 
 #[derive(Debug, Args)]
 pub struct DeleteArgs {
     /// Name of the profile to delete
+    #[arg(add = ArgValueCandidates::new(completions::profile_names))]
     pub profile_name: String,
 }
 
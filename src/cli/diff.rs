@@ -0,0 +1,212 @@
+//! Compare two profiles before switching to or importing one
+//!
+//! Either side can be an installed profile, a `.zprof` archive or a GitHub
+//! repository. The manifests are compared semantically (framework, prompt,
+//! plugins, environment); `--generated` also diffs the shell files zprof
+//! would generate from each.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use similar::TextDiff;
+use std::path::{Path, PathBuf};
+
+use crate::cli::completions;
+use crate::archive::{github, import};
+use crate::core::conditions::Machine;
+use crate::core::diff::{self, Change};
+use crate::core::manifest::{self, Manifest, SnippetPhase};
+use crate::shell::generator;
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// Profile name, .zprof archive or GitHub repository (user/repo) to compare from
+    #[arg(add = completions::profiles_or_archives())]
+    pub from: String,
+
+    /// Profile name, .zprof archive or GitHub repository (user/repo) to compare to
+    #[arg(add = completions::profiles_or_archives())]
+    pub to: String,
+
+    /// Also show a unified diff of the generated .zshenv, .zshrc and framework files
+    #[arg(long)]
+    pub generated: bool,
+}
+
+/// Where one side of the comparison comes from
+#[derive(Debug, Clone, PartialEq)]
+enum Source {
+    Profile(String),
+    Archive(PathBuf),
+    GitHub { username: String, repo_name: String },
+}
+
+impl Source {
+    /// Work out what a command-line argument refers to
+    ///
+    /// Existing files and `.zprof` paths are archives, `github:user/repo` and
+    /// `user/repo` are repositories, and anything else is a profile name.
+    fn parse(spec: &str) -> Result<Self> {
+        if spec.starts_with("github:") {
+            let (username, repo_name) = github::parse_github_url(spec)?;
+            return Ok(Source::GitHub { username, repo_name });
+        }
+
+        let path = Path::new(spec);
+        if spec.ends_with(".zprof") || path.is_file() {
+            return Ok(Source::Archive(path.to_path_buf()));
+        }
+
+        if spec.contains('/') {
+            let (username, repo_name) = github::parse_github_url(&format!("github:{spec}"))?;
+            return Ok(Source::GitHub { username, repo_name });
+        }
+
+        Ok(Source::Profile(spec.to_string()))
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Source::Profile(name) => name.clone(),
+            Source::Archive(path) => path.display().to_string(),
+            Source::GitHub { username, repo_name } => format!("github:{username}/{repo_name}"),
+        }
+    }
+
+    /// The manifest on this side, with `extends` resolved for installed profiles
+    ///
+    /// Every side gets the `[when]` overlays matching this machine, as
+    /// installed profiles do when loaded, so they compare like with like.
+    fn load(&self) -> Result<Manifest> {
+        match self {
+            Source::Profile(name) => manifest::load_and_validate(name)
+                .with_context(|| format!("Failed to load profile '{name}'")),
+            Source::Archive(path) => import::load_manifest_from_archive(path)?.apply_conditions(&Machine::current()),
+            Source::GitHub { username, repo_name } => {
+                github::load_manifest_from_github(username, repo_name)?.apply_conditions(&Machine::current())
+            }
+        }
+    }
+}
+
+pub fn execute(args: DiffArgs) -> Result<()> {
+    let from = Source::parse(&args.from)?;
+    let to = Source::parse(&args.to)?;
+    let from_manifest = from.load()?;
+    let to_manifest = to.load()?;
+
+    println!();
+    println!("Comparing {} → {}", from.label(), to.label());
+    println!();
+
+    let changes = diff::diff_manifests(&from_manifest, &to_manifest);
+    print_changes(&changes);
+
+    if args.generated {
+        let from_files = generated_files(&from_manifest)?;
+        let to_files = generated_files(&to_manifest)?;
+        let mut differs = false;
+        for name in GENERATED_FILES {
+            // A file only one side generates diffs against an empty one
+            let old = from_files.iter().find(|(n, _)| n == name).map_or("", |(_, c)| c);
+            let new = to_files.iter().find(|(n, _)| n == name).map_or("", |(_, c)| c);
+            if old == new {
+                continue;
+            }
+            differs = true;
+            let text_diff = TextDiff::from_lines(old, new);
+            let from_header = format!("a/{name}");
+            let to_header = format!("b/{name}");
+            print!(
+                "{}",
+                text_diff.unified_diff().context_radius(3).header(&from_header, &to_header)
+            );
+            println!();
+        }
+        if !differs {
+            println!("✓ Generated files are identical");
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the semantic changes, grouped by section
+fn print_changes(changes: &[Change]) {
+    if changes.is_empty() {
+        println!("✓ No differences in framework, prompt, plugins or environment");
+        println!();
+        return;
+    }
+
+    for change in changes.iter().filter(|c| !c.is_plugin() && !c.is_env()) {
+        println!("  {change}");
+    }
+    for (title, changes) in [
+        ("Plugins", changes.iter().filter(|c| c.is_plugin()).collect::<Vec<_>>()),
+        ("Environment", changes.iter().filter(|c| c.is_env()).collect()),
+    ] {
+        if changes.is_empty() {
+            continue;
+        }
+        println!();
+        println!("  {title}:");
+        for change in changes {
+            println!("    {change}");
+        }
+    }
+    println!();
+}
+
+/// Generated shell files, in the order they are shown
+const GENERATED_FILES: &[&str] = &[".zshenv", ".zprofile", ".zshrc", ".zimrc"];
+
+/// The shell files zprof would generate for a manifest
+///
+/// `# Generated:` timestamp lines are left out so they don't show up as a
+/// difference.
+fn generated_files(manifest: &Manifest) -> Result<Vec<(&'static str, String)>> {
+    let mut files = vec![
+        (".zshenv", generator::generate_zshenv_from_manifest(manifest)?),
+        (".zshrc", generator::generate_zshrc_from_manifest(manifest)?),
+    ];
+    if manifest.snippets_for(SnippetPhase::Zprofile).next().is_some() {
+        files.push((".zprofile", generator::generate_zprofile_from_manifest(manifest)?));
+    }
+    if manifest.profile.framework == "zimfw" {
+        files.push((".zimrc", generator::generate_zimrc_from_manifest(manifest)?));
+    }
+
+    Ok(files
+        .into_iter()
+        .map(|(name, content)| {
+            let content = content
+                .lines()
+                .filter(|line| !line.starts_with("# Generated: "))
+                .map(|line| format!("{line}\n"))
+                .collect();
+            (name, content)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_parse() {
+        assert_eq!(Source::parse("work").unwrap(), Source::Profile("work".to_string()));
+        assert_eq!(
+            Source::parse("backup/work.zprof").unwrap(),
+            Source::Archive(PathBuf::from("backup/work.zprof"))
+        );
+        let github = Source::GitHub {
+            username: "octo".to_string(),
+            repo_name: "dotfiles".to_string(),
+        };
+        assert_eq!(Source::parse("octo/dotfiles").unwrap(), github);
+        assert_eq!(Source::parse("github:octo/dotfiles").unwrap(), github);
+        assert!(Source::parse("a/b/c").is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! Check that zprof and every profile are set up correctly
+//!
+//! Runs the checks in `core::doctor` and reports each as passed, warned or
+//! failed. `--fix` applies the repairs that can't lose data and checks
+//! again; `--json` (short for `--format json`) prints the report for scripts.
+
+use anyhow::{bail, Result};
+use clap::Args;
+use serde::Serialize;
+
+use crate::cli::format::{self, OutputFormat};
+use crate::core::doctor::{self, Check, Repair, Status};
+
+#[derive(Debug, Args)]
+pub struct DoctorArgs {
+    /// Apply safe repairs, then check again
+    #[arg(long)]
+    pub fix: bool,
+
+    /// Print the report as JSON (same as --format json)
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Everything `--format json|toml` prints
+#[derive(Debug, Serialize)]
+struct Report {
+    checks: Vec<Check>,
+    /// Repairs `--fix` applied
+    fixed: Vec<String>,
+    /// Repairs `--fix` attempted that failed, with the reason
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fix_errors: Vec<String>,
+    passed: usize,
+    warnings: usize,
+    failures: usize,
+}
+
+pub fn execute(args: DoctorArgs, format: OutputFormat) -> Result<()> {
+    let format = if args.json { OutputFormat::Json } else { format };
+
+    let mut checks = doctor::run_checks()?;
+
+    let mut fixed = Vec::new();
+    let mut fix_errors = Vec::new();
+    if args.fix {
+        let mut repairs: Vec<Repair> = Vec::new();
+        for repair in checks.iter().filter_map(|c| c.repair.clone()) {
+            if !repairs.contains(&repair) {
+                repairs.push(repair);
+            }
+        }
+        for repair in &repairs {
+            match repair.apply() {
+                Ok(()) => fixed.push(repair.to_string()),
+                Err(e) => fix_errors.push(format!("{repair}: {e:#}")),
+            }
+        }
+        if !repairs.is_empty() {
+            checks = doctor::run_checks()?;
+        }
+    }
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let report = Report {
+        passed: count(Status::Pass),
+        warnings: count(Status::Warn),
+        failures: count(Status::Fail),
+        checks,
+        fixed,
+        fix_errors,
+    };
+
+    if !format.is_plain() {
+        format::print(format, &report)?;
+    } else {
+        print_report(&report, args.fix);
+    }
+
+    if report.failures > 0 {
+        bail!("Doctor found {} problem(s)", report.failures);
+    }
+    Ok(())
+}
+
+fn print_report(report: &Report, fix: bool) {
+    println!();
+    println!("zprof doctor");
+    println!();
+    for check in &report.checks {
+        let symbol = match check.status {
+            Status::Pass => "✓",
+            Status::Warn => "⚠",
+            Status::Fail => "✗",
+        };
+        println!("  {symbol} {}: {}", check.name, check.message);
+        if check.status == Status::Pass {
+            continue;
+        }
+        if let Some(hint) = &check.hint {
+            println!("      → {hint}");
+        }
+    }
+    println!();
+
+    if !report.fixed.is_empty() {
+        println!("Repairs applied:");
+        for repair in &report.fixed {
+            println!("  ✓ {repair}");
+        }
+        println!();
+    }
+    if !report.fix_errors.is_empty() {
+        println!("Repairs that failed:");
+        for error in &report.fix_errors {
+            println!("  ✗ {error}");
+        }
+        println!();
+    }
+
+    println!(
+        "{} passed, {} warning(s), {} failed",
+        report.passed, report.warnings, report.failures
+    );
+    let fixable = report.checks.iter().filter(|c| c.repair.is_some()).count();
+    if fixable > 0 && !fix {
+        println!("  → {fixable} can be fixed automatically with 'zprof doctor --fix'");
+    }
+    println!();
+}
@@ -6,18 +6,24 @@
 use anyhow::{bail, Context, Result};
 use chrono::Utc;
 use clap::Args;
+use clap_complete::ArgValueCandidates;
 use std::env;
 use std::fs;
 use std::io::{self, Write as IoWrite};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use crate::core::manifest;
+use crate::cli::completions;
+use crate::cli::migrate::{self, backup_manifest};
+use crate::cli::regenerate::{install_plugin_sources, regenerate_descendants};
+use crate::core::document::ManifestDocument;
+use crate::core::manifest::{self, Manifest};
 use crate::shell::generator;
 
 #[derive(Debug, Args)]
 pub struct EditArgs {
     /// Name of the profile to edit
+    #[arg(add = ArgValueCandidates::new(completions::profile_names))]
     pub profile_name: String,
 }
 
@@ -60,8 +66,11 @@ pub fn execute(args: EditArgs) -> Result<()> {
             Ok(manifest) => {
                 // Validation succeeded
                 println!("✓ TOML manifest validated successfully");
+                print_diagnostics(&manifest_path, false);
+                migrate::warn_if_outdated(&args.profile_name);
 
                 // 6. Regenerate shell files
+                install_plugin_sources(&args.profile_name, &manifest)?;
                 println!("→ Regenerating shell configuration...");
                 generator::write_generated_files(&args.profile_name, &manifest)
                     .context("Failed to regenerate shell configuration")?;
@@ -81,14 +90,20 @@ pub fn execute(args: EditArgs) -> Result<()> {
                 // Clean up backup
                 fs::remove_file(&backup_path).context("Failed to remove backup")?;
 
+                // Profiles extending this one inherit the change
+                regenerate_descendants(&args.profile_name, false)?;
+
                 return Ok(());
             }
             Err(e) => {
                 // Validation failed
                 println!();
                 println!("✗ TOML validation failed:");
-                println!("{e:#}");
                 println!();
+                if !print_diagnostics(&manifest_path, true) {
+                    println!("{e:#}");
+                    println!();
+                }
 
                 // Prompt for action
                 let action = prompt_validation_failure()?;
@@ -123,6 +138,105 @@ pub fn execute(args: EditArgs) -> Result<()> {
     }
 }
 
+/// A change made by `update_manifest`
+pub struct ManifestUpdate {
+    /// The profile's manifest after the change, with `extends` resolved
+    pub manifest: Manifest,
+    /// Copy of profile.toml from before the change
+    pub backup_path: PathBuf,
+}
+
+/// Change a profile's manifest without opening an editor
+///
+/// Used by `zprof plugin`, `theme`, `engine` and `env`. `change` edits the
+/// parsed profile.toml, which is then written back with its comments and
+/// layout intact. A backup is taken first; if the result doesn't validate,
+/// the file is put back as it was. Otherwise plugin sources are installed
+/// and the shell files regenerated.
+pub fn update_manifest(
+    profile_name: &str,
+    action: &str,
+    change: impl FnOnce(&mut toml::Table) -> Result<()>,
+) -> Result<ManifestUpdate> {
+    let manifest_path = manifest::get_manifest_path(profile_name);
+    if !manifest_path.exists() {
+        bail!(
+            "Profile '{profile_name}' not found.\n  → Run 'zprof list' to see available profiles"
+        );
+    }
+
+    let mut document = ManifestDocument::load(&manifest_path)?;
+    let mut table = document.table()?;
+    change(&mut table)?;
+    if let Some(toml::Value::Table(profile)) = table.get_mut("profile") {
+        let now = toml::Value::try_from(Utc::now()).context("Failed to serialize timestamp")?;
+        profile.insert("modified".to_string(), now);
+    }
+    document.set_table(&table);
+    // Refuse to write something that wouldn't load
+    manifest::parse_manifest(&document.to_string()).context("Updated manifest does not parse")?;
+
+    let backup_path = backup_manifest(profile_name, &manifest_path, action)?;
+    document.save(&manifest_path)?;
+
+    // Inherited settings can only be checked once the file is in place
+    let manifest = match manifest::load_and_validate(profile_name) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            fs::copy(&backup_path, &manifest_path).context("Failed to restore profile.toml from backup")?;
+            return Err(e.context(format!("Change rejected; {} was not modified", manifest_path.display())));
+        }
+    };
+
+    install_plugin_sources(profile_name, &manifest)?;
+    generator::write_generated_files(profile_name, &manifest)
+        .context("Failed to regenerate shell configuration")?;
+
+    Ok(ManifestUpdate { manifest, backup_path })
+}
+
+/// Print where the backup went and how to pick up the change
+///
+/// Profiles that extend this one are offered a regenerate, as after `zprof edit`.
+pub fn finish_update(profile_name: &str, update: &ManifestUpdate) -> Result<()> {
+    println!("  Backup: {}", update.backup_path.display());
+    println!();
+    println!("  → Run 'zprof use {profile_name}' to activate changes");
+    println!();
+
+    regenerate_descendants(profile_name, false)
+}
+
+/// Print every problem in the manifest with its location in the file
+///
+/// Warnings are always printed; errors only when `errors` is set. Returns
+/// false if no errors were found, so the caller can fall back to the
+/// plain error message.
+fn print_diagnostics(manifest_path: &Path, errors: bool) -> bool {
+    let diagnostics = match manifest::diagnose(manifest_path) {
+        Ok(diagnostics) => diagnostics,
+        Err(e) => {
+            log::debug!("Could not diagnose manifest: {e:#}");
+            return false;
+        }
+    };
+
+    for diagnostic in diagnostics.iter().filter(|d| errors || !d.is_error()) {
+        println!("{}", diagnostic.render(manifest_path));
+    }
+
+    let error_count = diagnostics.iter().filter(|d| d.is_error()).count();
+    if errors && error_count > 0 {
+        println!(
+            "✗ Found {error_count} error{} in {}",
+            if error_count == 1 { "" } else { "s" },
+            manifest_path.display()
+        );
+        println!();
+    }
+    error_count > 0
+}
+
 fn get_profile_dir(profile_name: &str) -> Result<PathBuf> {
     let home = dirs::home_dir().context("Could not find home directory")?;
 
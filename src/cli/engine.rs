@@ -0,0 +1,129 @@
+//! Set a profile's prompt engine without opening an editor
+//!
+//! The engine is installed first if it isn't already, so the profile works
+//! as soon as it is activated.
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+
+use crate::cli::completions;
+use crate::cli::edit::{finish_update, update_manifest};
+use crate::core::diagnostics;
+use crate::core::diff::describe_prompt;
+use crate::core::lint::PROMPT_ENGINE_ALIASES;
+use crate::core::manifest::{self, PromptMode};
+use crate::prompts::engine::{PromptEngine, PROMPT_ENGINE_NAMES};
+use crate::prompts::installer::EngineInstaller;
+
+#[derive(Debug, Args)]
+pub struct EngineArgs {
+    #[command(subcommand)]
+    pub action: EngineAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EngineAction {
+    /// Use a prompt engine for a profile's prompt, replacing any framework theme
+    Set {
+        /// Name of the profile to change
+        #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+        profile_name: String,
+        /// Prompt engine to use (starship, powerlevel10k, oh-my-posh, pure, spaceship)
+        #[arg(add = ArgValueCandidates::new(completions::engine_names))]
+        engine: String,
+    },
+}
+
+pub fn execute(args: EngineArgs) -> Result<()> {
+    match args.action {
+        EngineAction::Set { profile_name, engine } => set(&profile_name, &engine),
+    }
+}
+
+fn set(profile_name: &str, engine: &str) -> Result<()> {
+    let current = manifest::load_and_validate(profile_name)
+        .with_context(|| format!("Failed to load profile '{profile_name}'"))?;
+    let name = engine_name(engine)?;
+
+    let prompt_mode = PromptMode::PromptEngine { engine: name.to_string() };
+    if current.profile.prompt_mode == prompt_mode {
+        println!("  ℹ '{profile_name}' already uses prompt engine '{name}'");
+        return Ok(());
+    }
+
+    install_if_missing(name)?;
+
+    let update = update_manifest(profile_name, "engine", |table| {
+        let profile = table
+            .get_mut("profile")
+            .and_then(toml::Value::as_table_mut)
+            .context("No [profile] section in profile.toml")?;
+        // Prompt settings are replaced as a unit
+        profile.remove("framework_theme");
+        profile.remove("theme");
+        profile.insert("prompt_mode".to_string(), "prompt_engine".into());
+        profile.insert("prompt_engine".to_string(), name.into());
+        Ok(())
+    })?;
+
+    println!();
+    println!("✓ Set prompt engine '{name}' for '{profile_name}'");
+    println!(
+        "  Prompt: {} → {}",
+        describe_prompt(&current.profile.prompt_mode),
+        describe_prompt(&prompt_mode)
+    );
+    finish_update(profile_name, &update)
+}
+
+/// Install the prompt engine called `name` unless it already is
+///
+/// Names that aren't a prompt engine are left alone. `theme set` uses this
+/// too, for themes that are also engines (pure, spaceship, ...).
+pub fn install_if_missing(name: &str) -> Result<()> {
+    let Some(prompt_engine) = PromptEngine::from_name(name) else {
+        return Ok(());
+    };
+
+    let installer = EngineInstaller::new()?;
+    if !installer.is_installed(&prompt_engine)? {
+        println!("→ Installing {}...", prompt_engine.name());
+        installer
+            .install(&prompt_engine)
+            .with_context(|| format!("Failed to install '{name}'"))?;
+    }
+    Ok(())
+}
+
+/// The name profile.toml uses for an engine, accepting short aliases
+fn engine_name(engine: &str) -> Result<&'static str> {
+    let lower = engine.to_lowercase();
+    if let Some(name) = PROMPT_ENGINE_NAMES.iter().find(|name| **name == lower) {
+        return Ok(name);
+    }
+    if let Some((_, name)) = PROMPT_ENGINE_ALIASES.iter().find(|(alias, _)| *alias == lower) {
+        return Ok(name);
+    }
+
+    match diagnostics::did_you_mean(engine, PROMPT_ENGINE_NAMES.iter().copied()) {
+        Some(suggestion) => bail!("✗ Error: Unknown prompt engine '{engine}'\n  → Did you mean '{suggestion}'?"),
+        None => bail!(
+            "✗ Error: Unknown prompt engine '{engine}'\n  → Supported engines: {}",
+            PROMPT_ENGINE_NAMES.join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engine_name_accepts_aliases() {
+        assert_eq!(engine_name("Starship").unwrap(), "starship");
+        assert_eq!(engine_name("p10k").unwrap(), "powerlevel10k");
+        assert_eq!(engine_name("ohmyposh").unwrap(), "oh-my-posh");
+        assert!(engine_name("starshp").unwrap_err().to_string().contains("Did you mean 'starship'?"));
+    }
+}
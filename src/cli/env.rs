@@ -0,0 +1,108 @@
+//! Set and unset a profile's environment variables without opening an editor
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+
+use crate::cli::completions;
+use crate::cli::edit::{finish_update, update_manifest};
+use crate::core::manifest;
+
+#[derive(Debug, Args)]
+pub struct EnvArgs {
+    #[command(subcommand)]
+    pub action: EnvAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EnvAction {
+    /// Set environment variables in a profile
+    Set {
+        /// Name of the profile to change
+        #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+        profile_name: String,
+        /// Variables to set, as KEY=VALUE
+        #[arg(required = true, value_name = "KEY=VALUE")]
+        variables: Vec<String>,
+    },
+    /// Remove environment variables from a profile
+    Unset {
+        /// Name of the profile to change
+        #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+        profile_name: String,
+        /// Variables to remove
+        #[arg(required = true, value_name = "KEY")]
+        keys: Vec<String>,
+    },
+}
+
+pub fn execute(args: EnvArgs) -> Result<()> {
+    match args.action {
+        EnvAction::Set { profile_name, variables } => set(&profile_name, &variables),
+        EnvAction::Unset { profile_name, keys } => unset(&profile_name, &keys),
+    }
+}
+
+fn set(profile_name: &str, variables: &[String]) -> Result<()> {
+    let assignments = variables
+        .iter()
+        .map(|variable| match variable.split_once('=') {
+            Some((key, value)) if !key.is_empty() => Ok((key, value)),
+            _ => bail!("✗ Error: Expected KEY=VALUE, got '{variable}'\n  → Example: zprof env set {profile_name} EDITOR=nvim"),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let update = update_manifest(profile_name, "env", |table| {
+        let env = table
+            .entry("env")
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .context("[env] in profile.toml is not a table")?;
+        for (key, value) in &assignments {
+            env.insert(key.to_string(), (*value).into());
+        }
+        Ok(())
+    })?;
+
+    println!();
+    for (key, value) in &assignments {
+        println!("✓ Set {key}={value} in '{profile_name}'");
+    }
+    finish_update(profile_name, &update)
+}
+
+fn unset(profile_name: &str, keys: &[String]) -> Result<()> {
+    let own = manifest::load_unresolved(profile_name)
+        .with_context(|| format!("Failed to load profile '{profile_name}'"))?;
+
+    for key in keys {
+        if own.env.contains_key(key) {
+            continue;
+        }
+        if let Some(parent) = &own.profile.extends {
+            let resolved = manifest::load_and_validate(profile_name)
+                .with_context(|| format!("Failed to load profile '{profile_name}'"))?;
+            if resolved.env.contains_key(key) {
+                bail!(
+                    "✗ Error: {key} is inherited from '{parent}'\n  → Unset it there with 'zprof env unset {parent} {key}', or override it with 'zprof env set {profile_name} {key}=...'"
+                );
+            }
+        }
+        bail!("✗ Error: {key} is not set in '{profile_name}'");
+    }
+
+    let update = update_manifest(profile_name, "env", |table| {
+        if let Some(toml::Value::Table(env)) = table.get_mut("env") {
+            for key in keys {
+                env.remove(key);
+            }
+        }
+        Ok(())
+    })?;
+
+    println!();
+    for key in keys {
+        println!("✓ Unset {key} in '{profile_name}'");
+    }
+    finish_update(profile_name, &update)
+}
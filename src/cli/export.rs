@@ -1,13 +1,16 @@
 use anyhow::{Context, Result};
 use clap::Args;
+use clap_complete::ArgValueCandidates;
 use std::path::PathBuf;
 
+use crate::cli::completions;
 use crate::archive::export;
 
 /// Export a profile to a .zprof archive
 #[derive(Debug, Args)]
 pub struct ExportArgs {
     /// Name of the profile to export
+    #[arg(add = ArgValueCandidates::new(completions::profile_names))]
     pub profile_name: String,
 
     /// Output path for .zprof archive (default: ./<profile-name>.zprof)
@@ -0,0 +1,51 @@
+//! The global `--format` option
+//!
+//! Commands that describe state (`list`, `show`, `current`, `available`,
+//! `version`, `doctor`) print it for people by default, or as JSON or TOML
+//! for scripts. Structured output always carries `format_version`, which
+//! changes only when an existing field is renamed or removed.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// Version of the structured output layout
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Plain,
+    Json,
+    Toml,
+}
+
+impl OutputFormat {
+    pub fn is_plain(self) -> bool {
+        self == OutputFormat::Plain
+    }
+}
+
+#[derive(Serialize)]
+struct Versioned<'a, T> {
+    format_version: u32,
+    #[serde(flatten)]
+    data: &'a T,
+}
+
+/// Print `data` as JSON or TOML, tagged with `format_version`
+///
+/// Plain output is each command's own; callers only get here otherwise.
+pub fn print<T: Serialize>(format: OutputFormat, data: &T) -> Result<()> {
+    let versioned = Versioned {
+        format_version: FORMAT_VERSION,
+        data,
+    };
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&versioned)?),
+        OutputFormat::Toml => print!("{}", toml::to_string_pretty(&versioned)?),
+        OutputFormat::Plain => unreachable!("plain output is printed by each command"),
+    }
+    Ok(())
+}
@@ -2,12 +2,14 @@ use anyhow::{Context, Result};
 use clap::Args;
 use std::path::PathBuf;
 
+use crate::cli::completions;
 use crate::archive::{github, import};
 
 /// Import a profile from a .zprof archive or GitHub repository
 #[derive(Debug, Args)]
 pub struct ImportArgs {
     /// Path to .zprof archive file OR github:user/repo
+    #[arg(add = completions::archive_paths())]
     pub source: String,
 
     /// Override profile name from archive/repo
@@ -0,0 +1,187 @@
+//! Check profile manifests against the plugin, theme and engine registries
+//!
+//! Reports everything `zprof edit` would reject, plus the registry rules in
+//! `core::lint`: plugins and themes the framework can't load, missing plugin
+//! dependencies, and misspellings. `--fix` applies the corrections that can't
+//! change what the user meant, after backing up profile.toml.
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::completions;
+use crate::cli::migrate::backup_manifest;
+use crate::core::conditions::Machine;
+use crate::core::diagnostics::Diagnostic;
+use crate::core::document::ManifestDocument;
+use crate::core::lint::{self, Fix};
+use crate::core::{manifest, profile};
+
+#[derive(Debug, Args)]
+pub struct LintArgs {
+    /// Name of the profile to lint
+    #[arg(required_unless_present = "all", conflicts_with = "all", add = ArgValueCandidates::new(completions::profile_names))]
+    pub profile_name: Option<String>,
+
+    /// Lint every profile
+    #[arg(long)]
+    pub all: bool,
+
+    /// Apply safe corrections (misspelled names, duplicates, missing dependencies)
+    #[arg(long)]
+    pub fix: bool,
+}
+
+pub fn execute(args: LintArgs) -> Result<()> {
+    let profile_names = match args.profile_name {
+        Some(name) => vec![name],
+        None => profile::list_available_profiles()?,
+    };
+
+    let mut errors = 0;
+    let mut warnings = 0;
+    let mut fixable = 0;
+
+    println!();
+    for name in &profile_names {
+        let manifest_path = manifest::get_manifest_path(name);
+        if !manifest_path.exists() {
+            if args.all {
+                // Directories without a manifest aren't profiles
+                continue;
+            }
+            bail!(
+                "✗ Error: Profile manifest not found\n  Path: {manifest_path:?}\n  → Run 'zprof list' to see available profiles"
+            );
+        }
+
+        let mut problems = lint_profile(name, &manifest_path)?;
+        if args.fix {
+            let (applied, backup_path) = fix_profile(name, &manifest_path, &problems)?;
+            if !applied.is_empty() {
+                println!("✓ {name}: applied {} fix(es)", applied.len());
+                for fix in &applied {
+                    println!("    - {fix}");
+                }
+                if let Some(backup_path) = backup_path {
+                    println!("    Backup: {}", backup_path.display());
+                }
+                println!("  → Run 'zprof regenerate {name}' to apply the changes");
+                println!();
+                problems = lint_profile(name, &manifest_path)?;
+            }
+        }
+
+        if problems.is_empty() {
+            println!("✓ {name}: no problems found");
+            continue;
+        }
+
+        println!("→ {name}:");
+        println!();
+        for problem in &problems {
+            print!("{}", problem.render(&manifest_path));
+            if let (Some(parent), Some(_)) = (&problem.inherited_from, Fix::for_diagnostic(problem)) {
+                println!("  → Run 'zprof lint {parent} --fix' to correct it in '{parent}'");
+            }
+            println!();
+            if problem.is_error() {
+                errors += 1;
+            } else {
+                warnings += 1;
+            }
+        }
+        fixable += problems.iter().filter_map(own_fix).count();
+    }
+    println!();
+
+    if errors + warnings > 0 {
+        println!("Found {errors} error(s) and {warnings} warning(s)");
+        if fixable > 0 && !args.fix {
+            println!("  → {fixable} can be fixed automatically with 'zprof lint --fix'");
+        }
+        println!();
+    }
+
+    if errors > 0 {
+        bail!("Lint found {errors} error(s)");
+    }
+
+    Ok(())
+}
+
+/// All problems in one profile, ordered by where they are in profile.toml
+///
+/// Registry rules are only checked once the manifest parses; until then the
+/// parse errors are all there is to report.
+pub fn lint_profile(profile_name: &str, manifest_path: &Path) -> Result<Vec<Diagnostic>> {
+    let mut problems = manifest::diagnose(manifest_path)?;
+
+    let resolved = manifest::load_unresolved(profile_name).and_then(|own| {
+        let manifest = if own.profile.extends.is_some() {
+            manifest::resolve_manifest(manifest_path)?
+        } else {
+            own
+        };
+        manifest.apply_conditions(&Machine::current())
+    });
+    if let Ok(resolved) = resolved {
+        let source = fs::read_to_string(manifest_path)
+            .with_context(|| format!("Failed to read profile.toml at {manifest_path:?}"))?;
+        let mut lints = lint::lint(&resolved);
+        manifest::locate_in_chain(&mut lints, &source);
+        problems.extend(lints);
+    }
+
+    problems.sort_by_key(|p| p.location.as_ref().map_or(usize::MAX, |l| l.line));
+    Ok(problems)
+}
+
+/// The fix for a problem written in the profile's own file
+///
+/// Problems inherited through `extends` can only be fixed in the parent
+/// that sets the value, so `--fix` leaves them alone.
+fn own_fix(problem: &Diagnostic) -> Option<Fix> {
+    match problem.inherited_from {
+        Some(_) => None,
+        None => Fix::for_diagnostic(problem),
+    }
+}
+
+/// Apply the fixes for `problems` to a profile.toml
+///
+/// Returns the fixes that changed the file and the path of the backup taken
+/// before it was rewritten, if it was.
+fn fix_profile(
+    profile_name: &str,
+    manifest_path: &Path,
+    problems: &[Diagnostic],
+) -> Result<(Vec<Fix>, Option<PathBuf>)> {
+    let mut fixes: Vec<Fix> = Vec::new();
+    for fix in problems.iter().filter_map(own_fix) {
+        if !fixes.contains(&fix) {
+            fixes.push(fix);
+        }
+    }
+    if fixes.is_empty() {
+        return Ok((fixes, None));
+    }
+
+    let mut document = ManifestDocument::load(manifest_path)?;
+    let mut table = document.table()?;
+    fixes.retain(|fix| fix.apply(&mut table));
+    if fixes.is_empty() {
+        return Ok((fixes, None));
+    }
+
+    document.set_table(&table);
+    // Refuse to write something that wouldn't load
+    manifest::parse_manifest(&document.to_string()).context("Fixed manifest does not parse")?;
+
+    let backup_path = backup_manifest(profile_name, manifest_path, "lint")?;
+    document.save(manifest_path)?;
+
+    Ok((fixes, Some(backup_path)))
+}
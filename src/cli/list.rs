@@ -1,13 +1,22 @@
 use anyhow::{Context, Result};
 use clap::Args;
+use serde::Serialize;
 
+use crate::cli::format::{self, OutputFormat};
+use crate::core::info::ProfileInfo;
 use crate::core::{config::Config, profile};
 
 /// List all available zsh profiles
 #[derive(Debug, Args)]
 pub struct ListArgs {}
 
-pub fn execute(_args: ListArgs) -> Result<()> {
+/// What `--format json|toml` prints
+#[derive(Debug, Serialize)]
+struct ProfileList {
+    profiles: Vec<ProfileInfo>,
+}
+
+pub fn execute(_args: ListArgs, format: OutputFormat) -> Result<()> {
     // Get the profiles directory path
     let profiles_dir = profile::get_profiles_dir()?;
     let config_path = profile::get_config_path();
@@ -34,6 +43,11 @@ pub fn execute(_args: ListArgs) -> Result<()> {
     let profiles = profile::scan_profiles(&profiles_dir, active_profile.as_deref())
         .context("Failed to scan profiles directory")?;
 
+    if !format.is_plain() {
+        let profiles = profiles.into_iter().map(ProfileInfo::load).collect();
+        return format::print(format, &ProfileList { profiles });
+    }
+
     // Handle empty profiles directory
     if profiles.is_empty() {
         println!("No profiles found. Create your first profile with 'zprof create <name>'");
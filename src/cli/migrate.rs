@@ -0,0 +1,205 @@
+//! Upgrade profile manifests to the current schema
+//!
+//! Loading a profile already migrates it in memory; this command rewrites
+//! profile.toml on disk so the warning goes away and older fields are gone.
+//! Only the migrated keys change; comments and layout are kept. The original
+//! file is copied to `cache/backups` before it is replaced.
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cli::completions;
+use crate::core::document::ManifestDocument;
+use crate::core::{filesystem, manifest, profile};
+
+#[derive(Debug, Args)]
+pub struct MigrateArgs {
+    /// Name of the profile to migrate
+    #[arg(required_unless_present = "all", conflicts_with = "all", add = ArgValueCandidates::new(completions::profile_names))]
+    pub profile_name: Option<String>,
+
+    /// Migrate every profile
+    #[arg(long)]
+    pub all: bool,
+
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub fn execute(args: MigrateArgs) -> Result<()> {
+    let profile_names = match args.profile_name {
+        Some(name) => vec![name],
+        None => profile::list_available_profiles()?,
+    };
+
+    let mut migrated = 0;
+    let mut failed = 0;
+
+    println!();
+    for name in &profile_names {
+        let manifest_path = manifest::get_manifest_path(name);
+        if !manifest_path.exists() {
+            if args.all {
+                // Directories without a manifest aren't profiles
+                continue;
+            }
+            bail!(
+                "✗ Error: Profile manifest not found\n  Path: {manifest_path:?}\n  → Run 'zprof list' to see available profiles"
+            );
+        }
+
+        match migrate_profile(name, &manifest_path, args.dry_run) {
+            Ok((report, _)) if report.is_up_to_date() => {
+                println!("✓ {name}: already on schema version {}", report.to_version);
+            }
+            Ok((report, backup_path)) => {
+                migrated += 1;
+                if args.dry_run {
+                    println!(
+                        "→ {name}: would migrate schema version {} → {}",
+                        report.from_version, report.to_version
+                    );
+                } else {
+                    println!(
+                        "✓ {name}: migrated schema version {} → {}",
+                        report.from_version, report.to_version
+                    );
+                }
+                for change in &report.changes {
+                    println!("    - {change}");
+                }
+                if let Some(backup_path) = backup_path {
+                    println!("    Backup: {}", backup_path.display());
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                println!("✗ {name}: {e:#}");
+            }
+        }
+    }
+    println!();
+
+    if args.dry_run && migrated > 0 {
+        println!("Dry run: no files were changed. Run without --dry-run to apply.");
+        println!();
+    }
+
+    if failed > 0 {
+        bail!("{failed} profile(s) could not be migrated");
+    }
+
+    Ok(())
+}
+
+/// Migrate one profile.toml in place
+///
+/// Returns the migration report and, if the file was rewritten, the path of
+/// the backup taken beforehand. Nothing is written for a dry run or when the
+/// manifest is already current.
+pub fn migrate_profile(
+    profile_name: &str,
+    manifest_path: &Path,
+    dry_run: bool,
+) -> Result<(manifest::MigrationReport, Option<PathBuf>)> {
+    let mut document = ManifestDocument::load(manifest_path)?;
+    let mut table = document.table()?;
+    let report = manifest::migrate_table(&mut table)?;
+
+    if report.is_up_to_date() || dry_run {
+        return Ok((report, None));
+    }
+
+    document.set_table(&table);
+    // Refuse to write something that wouldn't load
+    manifest::parse_manifest(&document.to_string()).context("Migrated manifest does not parse")?;
+
+    let backup_path = backup_manifest(profile_name, manifest_path, "migrate")?;
+    document.save(manifest_path)?;
+
+    Ok((report, Some(backup_path)))
+}
+
+/// Warn on stderr if a profile's profile.toml is on an older schema
+///
+/// Called by the commands that show or edit one profile. Loading a manifest
+/// only logs this, so lists and structured output aren't cluttered with it.
+pub fn warn_if_outdated(profile_name: &str) {
+    let manifest_path = manifest::get_manifest_path(profile_name);
+    if let Ok((report, _)) = migrate_profile(profile_name, &manifest_path, true) {
+        if !report.is_up_to_date() {
+            eprintln!(
+                "⚠ Warning: Profile '{profile_name}' uses manifest schema version {} (current is {})\n  → Run 'zprof migrate {profile_name}' to upgrade profile.toml",
+                report.from_version, report.to_version
+            );
+        }
+    }
+}
+
+/// Copy a profile.toml to cache/backups before `action` rewrites it
+///
+/// An existing backup is never overwritten: if two rewrites land in the same
+/// millisecond, the later backup gets a `-1`, `-2`, ... suffix.
+pub fn backup_manifest(profile_name: &str, manifest_path: &Path, action: &str) -> Result<PathBuf> {
+    let backups_dir = filesystem::get_zprof_dir()?.join("cache").join("backups");
+    fs::create_dir_all(&backups_dir).context("Failed to create backups directory")?;
+
+    let timestamp = Utc::now().format("%Y%m%d-%H%M%S%.3f");
+    let name = format!("{profile_name}.profile.toml.pre-{action}.{timestamp}");
+    copy_to_new_file(manifest_path, &backups_dir, &name)
+}
+
+/// Copy `source` to `dir/name`, or to `dir/name-N` for the first N that isn't taken
+fn copy_to_new_file(source: &Path, dir: &Path, name: &str) -> Result<PathBuf> {
+    let mut backup_path = dir.join(name);
+    let mut suffix = 0;
+    let mut backup = loop {
+        match fs::OpenOptions::new().write(true).create_new(true).open(&backup_path) {
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                suffix += 1;
+                backup_path = dir.join(format!("{name}-{suffix}"));
+            }
+            result => {
+                break result.with_context(|| format!("Failed to create backup at {backup_path:?}"))?
+            }
+        }
+    };
+
+    let mut original = fs::File::open(source).with_context(|| format!("Failed to read {source:?}"))?;
+    io::copy(&mut original, &mut backup)
+        .with_context(|| format!("Failed to create backup at {backup_path:?}"))?;
+
+    Ok(backup_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_copy_to_new_file_never_overwrites() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let source = temp_dir.path().join("profile.toml");
+        let backups_dir = temp_dir.path().join("backups");
+        fs::create_dir_all(&backups_dir)?;
+
+        fs::write(&source, "first")?;
+        let first = copy_to_new_file(&source, &backups_dir, "work.pre-edit")?;
+        fs::write(&source, "second")?;
+        let second = copy_to_new_file(&source, &backups_dir, "work.pre-edit")?;
+
+        assert_eq!(first, backups_dir.join("work.pre-edit"));
+        assert_eq!(second, backups_dir.join("work.pre-edit-1"));
+        assert_eq!(fs::read_to_string(first)?, "first");
+        assert_eq!(fs::read_to_string(second)?, "second");
+
+        Ok(())
+    }
+}
@@ -1,17 +1,34 @@
+pub mod auto;
 pub mod available;
+pub mod bench;
 pub mod create;
+pub mod completions;
+pub mod copy;
 pub mod create_from_preset;
 pub mod current;
 pub mod delete;
+pub mod diff;
+pub mod doctor;
 pub mod edit;
+pub mod engine;
+pub mod env;
 pub mod export;
+pub mod format;
 pub mod gui;
 pub mod import;
 pub mod init;
+pub mod lint;
 pub mod list;
+pub mod migrate;
+pub mod plugin;
 pub mod regenerate;
+pub mod rename;
 pub mod rollback;
+pub mod run;
+pub mod schema;
+pub mod shell;
 pub mod show;
+pub mod theme;
 pub mod uninstall;
 pub mod use_cmd;
 pub mod version;
@@ -0,0 +1,236 @@
+//! Enable, disable and list plugins without opening an editor
+//!
+//! Plugins are checked against the registry for the profile's framework
+//! before anything is written, using the same rules as `zprof lint`.
+//! Registry dependencies of a new plugin are enabled along with it.
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+
+use crate::cli::completions;
+use crate::cli::edit::{finish_update, update_manifest};
+use crate::core::lint;
+use crate::core::manifest::{self, Manifest};
+use crate::frameworks::{plugin, FrameworkType};
+
+#[derive(Debug, Args)]
+pub struct PluginArgs {
+    #[command(subcommand)]
+    pub action: PluginAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PluginAction {
+    /// Enable plugins in a profile
+    Add {
+        /// Name of the profile to change
+        #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+        profile_name: String,
+        /// Plugins to enable
+        #[arg(required = true, add = ArgValueCandidates::new(completions::plugin_names))]
+        plugins: Vec<String>,
+    },
+    /// Disable plugins in a profile
+    Remove {
+        /// Name of the profile to change
+        #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+        profile_name: String,
+        /// Plugins to disable
+        #[arg(required = true, add = ArgValueCandidates::new(completions::enabled_plugin_names))]
+        plugins: Vec<String>,
+    },
+    /// List a profile's plugins in load order
+    List {
+        /// Name of the profile
+        #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+        profile_name: String,
+    },
+}
+
+pub fn execute(args: PluginArgs) -> Result<()> {
+    match args.action {
+        PluginAction::Add { profile_name, plugins } => add(&profile_name, &plugins),
+        PluginAction::Remove { profile_name, plugins } => remove(&profile_name, &plugins),
+        PluginAction::List { profile_name } => list(&profile_name),
+    }
+}
+
+fn add(profile_name: &str, names: &[String]) -> Result<()> {
+    let current = load(profile_name)?;
+    let framework = framework_of(&current)?;
+    let enabled = current.plugins.names();
+
+    // Dependencies go in ahead of the plugins that need them
+    let mut added: Vec<String> = Vec::new();
+    let mut dependencies: Vec<(String, String)> = Vec::new();
+    for name in names {
+        check_plugin(name, &framework)?;
+        if enabled.contains(name) {
+            println!("  ℹ Plugin '{name}' is already enabled in '{profile_name}'");
+            continue;
+        }
+        let required = plugin::find_plugin(name).map(|p| p.compatibility.dependencies).unwrap_or_default();
+        for dependency in required.iter().map(|d| d.to_string()) {
+            if !enabled.contains(&dependency) && !added.contains(&dependency) {
+                added.push(dependency.clone());
+                dependencies.push((dependency, name.clone()));
+            }
+        }
+        if !added.contains(name) {
+            added.push(name.clone());
+        }
+    }
+
+    if added.is_empty() {
+        return Ok(());
+    }
+
+    let update = update_manifest(profile_name, "plugin", |table| {
+        let plugins = table
+            .entry("plugins")
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .context("[plugins] in profile.toml is not a table")?;
+        let list = plugins
+            .entry("enabled")
+            .or_insert_with(|| toml::Value::Array(Vec::new()))
+            .as_array_mut()
+            .context("plugins.enabled in profile.toml is not a list")?;
+        list.extend(added.iter().map(|name| toml::Value::from(name.as_str())));
+        Ok(())
+    })?;
+
+    println!();
+    for name in added.iter().filter(|name| !dependencies.iter().any(|(d, _)| d == *name)) {
+        println!("✓ Enabled plugin '{name}' in '{profile_name}'");
+    }
+    for (dependency, dependent) in &dependencies {
+        println!("✓ Enabled plugin '{dependency}' (required by '{dependent}')");
+    }
+    finish_update(profile_name, &update)
+}
+
+fn remove(profile_name: &str, names: &[String]) -> Result<()> {
+    let own = manifest::load_unresolved(profile_name)
+        .with_context(|| format!("Failed to load profile '{profile_name}'"))?;
+    let own_plugins = own.plugins.names();
+
+    for name in names {
+        if own_plugins.contains(name) {
+            continue;
+        }
+        if let Some(parent) = &own.profile.extends {
+            let inherited = load(profile_name)?.plugins.names();
+            if inherited.contains(name) {
+                bail!(
+                    "✗ Error: Plugin '{name}' is inherited from '{parent}'\n  → Remove it there with 'zprof plugin remove {parent} {name}'"
+                );
+            }
+        }
+        bail!(
+            "✗ Error: Plugin '{name}' is not enabled in '{profile_name}'\n  → Run 'zprof plugin list {profile_name}' to see its plugins"
+        );
+    }
+
+    let update = update_manifest(profile_name, "plugin", |table| {
+        if let Some(toml::Value::Table(plugins)) = table.get_mut("plugins") {
+            let listed = |value: &toml::Value| {
+                value
+                    .as_str()
+                    .or_else(|| value.get("name").and_then(toml::Value::as_str))
+                    .is_some_and(|name| names.iter().any(|n| n == name))
+            };
+            for key in ["enabled", "entry"] {
+                if let Some(toml::Value::Array(list)) = plugins.get_mut(key) {
+                    list.retain(|value| !listed(value));
+                }
+            }
+            if plugins.get("entry").and_then(toml::Value::as_array).is_some_and(Vec::is_empty) {
+                plugins.remove("entry");
+            }
+        }
+        Ok(())
+    })?;
+
+    println!();
+    for name in names {
+        println!("✓ Disabled plugin '{name}' in '{profile_name}'");
+    }
+    let remaining = update.manifest.plugins.names();
+    for name in &remaining {
+        let required = plugin::find_plugin(name).map(|p| p.compatibility.dependencies).unwrap_or_default();
+        for dependency in required.iter().filter(|d| names.iter().any(|n| n == *d)) {
+            println!("  ⚠ '{name}' depends on '{dependency}'");
+        }
+    }
+    finish_update(profile_name, &update)
+}
+
+fn list(profile_name: &str) -> Result<()> {
+    let resolved = load(profile_name)?;
+    let own = manifest::load_unresolved(profile_name)
+        .with_context(|| format!("Failed to load profile '{profile_name}'"))?;
+    let own_plugins = own.plugins.names();
+    let plugins = resolved.plugins.resolved();
+
+    println!();
+    println!("Plugins in '{profile_name}' ({}):", resolved.profile.framework);
+    println!();
+    if plugins.is_empty() {
+        println!("  (none)");
+    }
+    for entry in &plugins {
+        let mut notes = Vec::new();
+        if let Some(source) = &entry.source {
+            notes.push(source.clone());
+        }
+        if let Some(parent) = own.profile.extends.as_ref().filter(|_| !own_plugins.contains(&entry.name)) {
+            notes.push(format!("from {parent}"));
+        }
+        if notes.is_empty() {
+            println!("  {}", entry.name);
+        } else {
+            println!("  {} ({})", entry.name, notes.join(", "));
+        }
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Reject plugins the profile's framework can't load
+///
+/// Frameworks bundle many plugins the registry doesn't list, so unknown
+/// names are allowed unless they look like a typo of a registry plugin or
+/// the framework needs a repository to install them from.
+fn check_plugin(name: &str, framework: &FrameworkType) -> Result<()> {
+    let framework_name = framework.name();
+    match plugin::find_plugin(name) {
+        Some(registered) if !registered.compatibility.supports_framework(framework) => bail!(
+            "✗ Error: Plugin '{name}' is not available for {framework_name}\n  → Run 'zprof available plugins --framework {framework_name}' to see plugins for {framework_name}"
+        ),
+        Some(_) => Ok(()),
+        None => {
+            if let Some(suggestion) = lint::misspelled_plugin("", name).and_then(|d| d.suggestion) {
+                bail!("✗ Error: Plugin '{name}' is not in the plugin registry\n  → Did you mean '{suggestion}'?");
+            }
+            if lint::REPO_FRAMEWORKS.contains(framework) {
+                bail!(
+                    "✗ Error: {framework_name} has no repository to install plugin '{name}' from\n  → Add it with a source in 'zprof edit'\n\nExample:\n  [[plugins.entry]]\n  name = \"{name}\"\n  source = \"user/{name}\""
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The profile's manifest with `extends` resolved
+fn load(profile_name: &str) -> Result<Manifest> {
+    manifest::load_and_validate(profile_name).with_context(|| format!("Failed to load profile '{profile_name}'"))
+}
+
+fn framework_of(manifest: &Manifest) -> Result<FrameworkType> {
+    FrameworkType::from_name(&manifest.profile.framework)
+        .with_context(|| format!("Unsupported framework: {}", manifest.profile.framework))
+}
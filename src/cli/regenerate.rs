@@ -5,14 +5,24 @@
 
 use anyhow::{Context, Result};
 use clap::Args;
+use clap_complete::ArgValueCandidates;
+use dialoguer::Confirm;
+use std::io::IsTerminal;
 
-use crate::core::manifest;
+use crate::cli::completions;
+use crate::core::{bench, manifest, profile};
+use crate::frameworks::{installer, FrameworkType};
 use crate::shell::generator;
 
 #[derive(Debug, Args)]
 pub struct RegenerateArgs {
     /// Name of the profile to regenerate
+    #[arg(add = ArgValueCandidates::new(completions::profile_names))]
     pub profile_name: String,
+
+    /// Also regenerate profiles that extend this one, without asking
+    #[arg(long)]
+    pub children: bool,
 }
 
 /// Execute the regenerate command
@@ -26,6 +36,9 @@ pub fn execute(args: RegenerateArgs) -> Result<()> {
     let manifest_obj = manifest::load_and_validate(&args.profile_name)
         .context("Cannot regenerate from invalid manifest")?;
 
+    // Clone plugins that point at a custom source before generating
+    install_plugin_sources(&args.profile_name, &manifest_obj)?;
+
     // Generate shell files (Story 2.2 core functionality)
     generator::write_generated_files(&args.profile_name, &manifest_obj)
         .context("Failed to generate shell configuration files")?;
@@ -41,11 +54,77 @@ pub fn execute(args: RegenerateArgs) -> Result<()> {
     println!("    - .zshenv");
     println!();
     println!("  → Run 'zprof use {}' to activate changes", args.profile_name);
+    // A stored benchmark means startup time is being watched for regressions
+    if let Ok(Some(baseline)) = bench::load_baseline(&args.profile_name) {
+        println!(
+            "  → Run 'zprof bench {}' to compare startup time with the baseline ({:.1} ms)",
+            args.profile_name, baseline.mean_ms
+        );
+    }
     println!();
 
+    regenerate_descendants(&args.profile_name, args.children)?;
+
     Ok(())
 }
 
+/// Offer to regenerate profiles that extend `profile_name`
+///
+/// Children inherit from the parent's manifest, so their generated files go
+/// stale whenever the parent changes. Asks first unless `assume_yes` is set;
+/// when stdin is not a terminal, only prints a hint.
+pub fn regenerate_descendants(profile_name: &str, assume_yes: bool) -> Result<()> {
+    let descendants = manifest::find_descendants(profile_name)?;
+    if descendants.is_empty() {
+        return Ok(());
+    }
+
+    let list = descendants.join(", ");
+    let confirmed = if assume_yes {
+        true
+    } else if std::io::stdin().is_terminal() {
+        Confirm::new()
+            .with_prompt(format!(
+                "{} profile(s) extend '{profile_name}' ({list}). Regenerate them too?",
+                descendants.len()
+            ))
+            .default(true)
+            .interact()
+            .context("Failed to read user input for child profile regeneration")?
+    } else {
+        false
+    };
+
+    if !confirmed {
+        println!("  ℹ Profiles extending '{profile_name}' were not regenerated: {list}");
+        println!("  → Run 'zprof regenerate {profile_name} --children' to update them");
+        return Ok(());
+    }
+
+    for child in &descendants {
+        let child_manifest = manifest::load_and_validate(child)
+            .with_context(|| format!("Cannot regenerate '{child}' from invalid manifest"))?;
+        install_plugin_sources(child, &child_manifest)?;
+        generator::write_generated_files(child, &child_manifest)
+            .with_context(|| format!("Failed to generate shell configuration for '{child}'"))?;
+        println!("✓ Regenerated {child}");
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Clone any `[[plugins.entry]]` sources the framework can't fetch itself
+pub fn install_plugin_sources(profile_name: &str, manifest_obj: &manifest::Manifest) -> Result<()> {
+    let Some(framework) = FrameworkType::from_name(&manifest_obj.profile.framework) else {
+        return Ok(());
+    };
+    let profile_dir = profile::get_profile_path(profile_name)?;
+
+    installer::install_plugin_sources(&framework, &manifest_obj.plugins, &profile_dir)
+        .context("Failed to install plugins from custom sources")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,6 +134,7 @@ mod tests {
         // Test that we can construct the args struct
         let args = RegenerateArgs {
             profile_name: "work".to_string(),
+            children: false,
         };
         assert_eq!(args.profile_name, "work");
     }
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+
+use crate::cli::completions;
+use crate::core::copy;
+
+#[derive(Debug, Args)]
+pub struct RenameArgs {
+    /// Current name of the profile
+    #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+    pub old_name: String,
+
+    /// New name for the profile
+    pub new_name: String,
+}
+
+pub fn execute(args: RenameArgs) -> Result<()> {
+    // Moves the directory and updates profile.name, child profiles' extends,
+    // and config.toml and ~/.zshenv if the profile is active
+    let rename = copy::rename_profile(&args.old_name, &args.new_name)?;
+
+    println!();
+    println!("✓ Renamed profile '{}' to '{}'", args.old_name, args.new_name);
+    println!();
+    println!("  Location: {}", rename.profile_path.display());
+    for child in &rename.updated_children {
+        println!("  Updated extends in: {child}");
+    }
+    if rename.was_active {
+        println!();
+        println!("  → Start a new shell session to pick up the new location: exec zsh");
+    }
+    println!();
+
+    Ok(())
+}
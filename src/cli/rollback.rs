@@ -1,9 +1,11 @@
 use anyhow::{Context, Result};
 use clap::Args;
+use clap_complete::ArgValueCandidates;
 use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 
+use crate::cli::completions;
 use crate::core::{filesystem, profile};
 use crate::frameworks::FrameworkType;
 
@@ -14,7 +16,7 @@ pub struct RollbackArgs {
     pub yes: bool,
 
     /// Specific profile to rollback from (defaults to searching all profiles)
-    #[arg(short = 'p', long = "profile")]
+    #[arg(short = 'p', long = "profile", add = ArgValueCandidates::new(completions::profile_names))]
     pub profile: Option<String>,
 }
 
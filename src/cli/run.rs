@@ -0,0 +1,58 @@
+//! Run one command in a shell started with a profile
+//!
+//! The command runs in `zsh -i -c`, so the profile's aliases, functions and
+//! environment are all available, and zprof exits with its status. Like
+//! `zprof shell`, the active profile is not changed.
+
+use anyhow::Result;
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+
+use crate::cli::completions;
+use crate::cli::shell::exec;
+use crate::core::activation;
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// Name of the profile to run the command with
+    #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+    pub profile_name: String,
+
+    /// Command to run, after `--`. The first word is read as zsh code, so it
+    /// can be an alias, function or pipeline; the rest are passed literally
+    #[arg(last = true, required = true)]
+    pub command: Vec<String>,
+}
+
+pub fn execute(args: RunArgs) -> Result<()> {
+    let script = command_script(&args.command);
+    let command = activation::session_command(&args.profile_name, &["-i", "-c", &script])?;
+    exec(command)
+}
+
+/// Join the command into the script for `zsh -c`
+fn command_script(words: &[String]) -> String {
+    let mut script = words[0].clone();
+    for word in &words[1..] {
+        script.push(' ');
+        script.push_str(&format!("'{}'", word.replace('\'', r"'\''")));
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_script_quotes_arguments_only() {
+        let words = |w: &[&str]| w.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(command_script(&words(&["gst"])), "gst");
+        assert_eq!(command_script(&words(&["ll | head -3"])), "ll | head -3");
+        assert_eq!(
+            command_script(&words(&["echo", "it's", "$HOME"])),
+            r"echo 'it'\''s' '$HOME'"
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Print or install the JSON Schema for profile.toml
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::core::schema;
+
+#[derive(Debug, Args)]
+pub struct SchemaArgs {
+    /// Write the schema to ~/.zsh-profiles/profile.schema.json instead of printing it
+    #[arg(long)]
+    pub write: bool,
+}
+
+pub fn execute(args: SchemaArgs) -> Result<()> {
+    if args.write {
+        let schema_path = schema::write_schema_file()?;
+        println!("✓ JSON Schema written to {}", schema_path.display());
+        println!();
+        println!("  Profiles created by zprof reference it with:");
+        println!("    {}", schema::SCHEMA_DIRECTIVE);
+        return Ok(());
+    }
+
+    println!("{}", schema::manifest_schema_string()?);
+    Ok(())
+}
@@ -0,0 +1,60 @@
+//! Start zsh with a profile for this terminal session only
+//!
+//! Unlike `zprof use`, nothing is written: config.toml and `~/.zshenv` are
+//! left alone, so other terminals keep the active profile.
+
+use anyhow::{anyhow, Result};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+use std::process::Command;
+
+use crate::cli::completions;
+use crate::core::activation;
+
+#[derive(Debug, Args)]
+pub struct ShellArgs {
+    /// Name of the profile to start zsh with
+    #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+    pub profile_name: String,
+}
+
+pub fn execute(args: ShellArgs) -> Result<()> {
+    let command = activation::session_command(&args.profile_name, &["-i"])?;
+
+    if let Some(current) = activation::session_profile() {
+        eprintln!("  ℹ Starting a '{}' session inside the '{current}' session", args.profile_name);
+    }
+    eprintln!(
+        "→ Starting zsh with profile '{}' for this session; type 'exit' to leave it",
+        args.profile_name
+    );
+
+    exec(command)
+}
+
+/// Replace zprof with `command`, so its exit status is zprof's
+///
+/// Where processes can't be replaced, the command runs as a child and zprof
+/// exits with its status.
+pub fn exec(mut command: Command) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // exec only returns if zsh could not be started
+        Err(start_error(command.exec()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let status = command.status().map_err(start_error)?;
+        std::process::exit(status.code().unwrap_or(1));
+    }
+}
+
+fn start_error(error: std::io::Error) -> anyhow::Error {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        anyhow!("✗ Error: zsh is not installed\n  → Install zsh, then run this command again")
+    } else {
+        anyhow::Error::new(error).context("Failed to start zsh")
+    }
+}
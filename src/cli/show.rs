@@ -1,16 +1,35 @@
 use anyhow::{Context, Result};
 use clap::Args;
+use clap_complete::ArgValueCandidates;
+use serde::Serialize;
 
+use crate::cli::completions;
+use crate::cli::format::{self, OutputFormat};
+use crate::cli::migrate;
+use crate::core::conditions::Machine;
+use crate::core::info::ProfileDetails;
+use crate::core::manifest::WhenResolve;
 use crate::core::{manifest, profile};
 
 /// Show detailed information about a profile
 #[derive(Debug, Args)]
 pub struct ShowArgs {
     /// Name of the profile to show (defaults to current profile if not specified)
+    #[arg(add = ArgValueCandidates::new(completions::profile_names))]
     pub profile_name: Option<String>,
+
+    /// Include settings inherited through `extends`
+    #[arg(long)]
+    pub resolved: bool,
+}
+
+/// What `--format json|toml` prints
+#[derive(Debug, Serialize)]
+struct ShownProfile {
+    profile: ProfileDetails,
 }
 
-pub fn execute(args: ShowArgs) -> Result<()> {
+pub fn execute(args: ShowArgs, format: OutputFormat) -> Result<()> {
     // Determine which profile to show
     let profile_name = match args.profile_name {
         Some(name) => name,
@@ -31,19 +50,56 @@ pub fn execute(args: ShowArgs) -> Result<()> {
         }
     };
 
-    display_profile_details(&profile_name)?;
+    if !format.is_plain() {
+        return print_structured(&profile_name, args.resolved, format);
+    }
+
+    migrate::warn_if_outdated(&profile_name);
+    display_profile(&profile_name, args.resolved)?;
 
     Ok(())
 }
 
+/// Print a profile's details as JSON or TOML, with the same `--resolved` rules
+fn print_structured(profile_name: &str, resolved: bool, format: OutputFormat) -> Result<()> {
+    let resolved_manifest = manifest::load_and_validate(profile_name)
+        .context("Failed to load profile manifest")?;
+    let framework = resolved_manifest.profile.framework.clone();
+    let prompt_mode = resolved_manifest.profile.prompt_mode.clone();
+
+    let mut profile = if resolved || resolved_manifest.profile.extends.is_none() {
+        ProfileDetails::from(resolved_manifest)
+    } else {
+        ProfileDetails::from(manifest::load_unresolved(profile_name).context("Failed to load profile manifest")?)
+    };
+    profile.framework = framework;
+    profile.prompt_mode = prompt_mode.into();
+
+    format::print(format, &ShownProfile { profile })
+}
+
 /// Display detailed information about a profile
 ///
 /// This is a public utility function that can be used by other CLI commands
 /// to display profile information in a consistent format.
 pub fn display_profile_details(profile_name: &str) -> Result<()> {
+    display_profile(profile_name, false)
+}
+
+/// Display a profile, either as written or with inherited settings merged in
+///
+/// Framework and theme always come from the resolved manifest, since a
+/// profile that extends another may not set them itself.
+fn display_profile(profile_name: &str, resolved: bool) -> Result<()> {
     // Load manifest to get detailed configuration
-    let manifest_obj = manifest::load_and_validate(profile_name)
+    let resolved_manifest = manifest::load_and_validate(profile_name)
         .context("Failed to load profile manifest")?;
+    let extends = resolved_manifest.profile.extends.clone();
+    let manifest_obj = if resolved || extends.is_none() {
+        resolved_manifest.clone()
+    } else {
+        manifest::load_unresolved(profile_name).context("Failed to load profile manifest")?
+    };
 
     // Load metadata for creation date
     let metadata = profile::load_profile_metadata(profile_name)
@@ -52,8 +108,11 @@ pub fn display_profile_details(profile_name: &str) -> Result<()> {
     // Display profile information
     println!();
     println!("Profile: {}", manifest_obj.profile.name);
-    println!("Framework: {}", manifest_obj.profile.framework);
-    println!("Theme: {}", manifest_obj.profile.theme());
+    if let Some(parent) = &extends {
+        println!("Extends: {parent}");
+    }
+    println!("Framework: {}", resolved_manifest.profile.framework);
+    println!("Theme: {}", resolved_manifest.profile.theme());
 
     // Show creation date if available
     if let Some(created) = metadata.created {
@@ -65,13 +124,52 @@ pub fn display_profile_details(profile_name: &str) -> Result<()> {
 
     println!();
 
+    if extends.is_some() && !resolved {
+        println!("(Settings defined in this profile only - use --resolved to include inherited ones)");
+        println!();
+    }
+
+    // Display which [when] conditions hold on this machine
+    let when = &resolved_manifest.when;
+    if !when.is_empty() {
+        let resolve = match when.resolve {
+            WhenResolve::Generate => "applied when generating",
+            WhenResolve::Shell => "checked at shell start",
+        };
+        println!("Conditions ({resolve}):");
+        let machine = Machine::current();
+        for condition in when.conditions() {
+            if condition.matches(&machine) {
+                println!("  ✓ {} (matches this machine)", condition.label());
+            } else {
+                println!("  ✗ {}", condition.label());
+            }
+        }
+        println!();
+    }
+
     // Display plugins
-    if manifest_obj.plugins.enabled.is_empty() {
+    let plugins = manifest_obj.plugins.resolved();
+    if plugins.is_empty() {
         println!("Plugins: (none)");
     } else {
-        println!("Plugins ({}):", manifest_obj.plugins.enabled.len());
-        for plugin in &manifest_obj.plugins.enabled {
-            println!("  - {plugin}");
+        println!("Plugins ({}):", plugins.len());
+        for plugin in &plugins {
+            let mut details = Vec::new();
+            if let Some(source) = &plugin.source {
+                details.push(format!("from {source}"));
+            }
+            if let Some(git_ref) = &plugin.git_ref {
+                details.push(format!("@ {git_ref}"));
+            }
+            if plugin.defer {
+                details.push("deferred".to_string());
+            }
+            if details.is_empty() {
+                println!("  - {}", plugin.name);
+            } else {
+                println!("  - {} ({})", plugin.name, details.join(", "));
+            }
         }
     }
     println!();
@@ -85,6 +183,118 @@ pub fn display_profile_details(profile_name: &str) -> Result<()> {
         println!();
     }
 
+    // Display PATH entries in the order they end up in $PATH
+    let path = &manifest_obj.path;
+    if !path.is_empty() {
+        let suffix = if path.skip_missing { " (missing directories skipped)" } else { "" };
+        println!("PATH{suffix}:");
+        for dir in &path.prepend {
+            println!("  + {dir}");
+        }
+        println!("  $PATH");
+        for dir in &path.append {
+            println!("  + {dir}");
+        }
+        println!();
+    }
+
+    // Display shell options
+    let options = &manifest_obj.options;
+    if !options.is_empty() {
+        println!("Shell Options:");
+        if !options.set.is_empty() {
+            println!("  set:   {}", options.set.join(", "));
+        }
+        if !options.unset.is_empty() {
+            println!("  unset: {}", options.unset.join(", "));
+        }
+        println!();
+    }
+
+    // Display aliases
+    let aliases = &manifest_obj.aliases;
+    if !aliases.is_empty() {
+        let total = aliases.plain.len() + aliases.global.len() + aliases.suffix.len();
+        println!("Aliases ({total}):");
+        for (flag, map) in [("", &aliases.plain), ("-g ", &aliases.global), ("-s ", &aliases.suffix)] {
+            let mut names: Vec<&String> = map.keys().collect();
+            names.sort();
+            for name in names {
+                println!("  {flag}{name}={}", map[name]);
+            }
+        }
+        println!();
+    }
+
+    // Display completion settings
+    let completion = &manifest_obj.completion;
+    if !completion.is_empty() {
+        println!("Completion:");
+        if !completion.matchers.is_empty() {
+            println!("  matchers: {}", completion.matchers.join(" "));
+        }
+        let flags: Vec<&str> = [
+            ("menu select", completion.menu_select),
+            ("cache", completion.cache),
+            ("fast init", completion.fast_init),
+            ("zprof completions", completion.zprof),
+        ]
+        .into_iter()
+        .filter_map(|(label, on)| on.then_some(label))
+        .collect();
+        if !flags.is_empty() {
+            println!("  {}", flags.join(", "));
+        }
+        for dir in &completion.fpath {
+            println!("  fpath + {dir}");
+        }
+        println!();
+    }
+
+    // Display key bindings
+    let keys = &manifest_obj.keys;
+    if !keys.is_empty() {
+        match keys.mode {
+            Some(mode) => println!("Key Bindings ({} mode):", format!("{mode:?}").to_lowercase()),
+            None => println!("Key Bindings:"),
+        }
+        for binding in &keys.bind {
+            let keymap = binding.keymap.as_deref().unwrap_or("main");
+            println!("  {} → {} ({keymap})", binding.keys, binding.widget);
+        }
+        println!();
+    }
+
+    // Display hooks
+    let hooks = &manifest_obj.hooks;
+    if !hooks.is_empty() {
+        println!("Hooks (timeout {}s):", hooks.timeout().as_secs());
+        for (key, hook) in [
+            ("on_activate", &hooks.on_activate),
+            ("on_deactivate", &hooks.on_deactivate),
+            ("on_shell_start", &hooks.on_shell_start),
+        ] {
+            if let Some(hook) = hook {
+                println!("  {key}: {hook}");
+            }
+        }
+        println!();
+    }
+
+    // Display snippets in the order they are listed
+    if !manifest_obj.snippets.is_empty() {
+        println!("Snippets ({}):", manifest_obj.snippets.len());
+        for snippet in &manifest_obj.snippets {
+            let source = match (&snippet.file, &snippet.code) {
+                (Some(file), _) => file.clone(),
+                (None, Some(code)) => format!("inline, {} line(s)", code.trim_end().lines().count()),
+                (None, None) => String::new(),
+            };
+            println!("  - {}: {source}", snippet.phase.as_str());
+        }
+        println!();
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,139 @@
+//! Set a profile's framework theme without opening an editor
+//!
+//! As with `engine set`, a theme that zprof knows how to install is installed
+//! first if it isn't already, and an oh-my-zsh theme that isn't there fails
+//! before profile.toml is touched.
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+
+use crate::cli::{completions, engine};
+use crate::cli::edit::{finish_update, update_manifest};
+use crate::core::diagnostics;
+use crate::core::diff::describe_prompt;
+use crate::core::manifest::{self, PromptMode};
+use crate::core::profile;
+use crate::frameworks::{installer, theme, FrameworkType};
+use crate::prompts::engine::PromptEngine;
+
+#[derive(Debug, Args)]
+pub struct ThemeArgs {
+    #[command(subcommand)]
+    pub action: ThemeAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ThemeAction {
+    /// Use a framework theme for a profile's prompt, replacing any prompt engine
+    Set {
+        /// Name of the profile to change
+        #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+        profile_name: String,
+        /// Theme to use
+        #[arg(add = ArgValueCandidates::new(completions::theme_names))]
+        theme: String,
+    },
+}
+
+pub fn execute(args: ThemeArgs) -> Result<()> {
+    match args.action {
+        ThemeAction::Set { profile_name, theme } => set(&profile_name, &theme),
+    }
+}
+
+fn set(profile_name: &str, name: &str) -> Result<()> {
+    let current = manifest::load_and_validate(profile_name)
+        .with_context(|| format!("Failed to load profile '{profile_name}'"))?;
+    let framework = FrameworkType::from_name(&current.profile.framework)
+        .with_context(|| format!("Unsupported framework: {}", current.profile.framework))?;
+    check_theme(name, &framework)?;
+
+    let prompt_mode = PromptMode::FrameworkTheme { theme: name.to_string() };
+    if current.profile.prompt_mode == prompt_mode {
+        println!("  ℹ '{profile_name}' already uses theme '{name}'");
+        return Ok(());
+    }
+
+    engine::install_if_missing(name)?;
+    if framework == FrameworkType::OhMyZsh {
+        check_oh_my_zsh_theme_installed(profile_name, name)?;
+    }
+
+    let update = update_manifest(profile_name, "theme", |table| {
+        let profile = table
+            .get_mut("profile")
+            .and_then(toml::Value::as_table_mut)
+            .context("No [profile] section in profile.toml")?;
+        // Prompt settings are replaced as a unit
+        profile.remove("prompt_engine");
+        profile.remove("theme");
+        profile.insert("prompt_mode".to_string(), "framework_theme".into());
+        profile.insert("framework_theme".to_string(), name.into());
+        Ok(())
+    })?;
+
+    println!();
+    println!("✓ Set theme '{name}' for '{profile_name}'");
+    println!(
+        "  Prompt: {} → {}",
+        describe_prompt(&current.profile.prompt_mode),
+        describe_prompt(&prompt_mode)
+    );
+    finish_update(profile_name, &update)
+}
+
+/// Reject themes the profile's framework can't load
+///
+/// As with plugins, themes missing from the registry are allowed unless they
+/// look like a typo or the framework can't install them.
+fn check_theme(name: &str, framework: &FrameworkType) -> Result<()> {
+    let framework_name = framework.name();
+    match theme::THEME_REGISTRY.iter().find(|t| t.name == name) {
+        Some(registered) if !registered.compatibility.supports_framework(framework) => bail!(
+            "✗ Error: Theme '{name}' is not available for {framework_name}\n  → Run 'zprof available themes --framework {framework_name}' to see themes for {framework_name}"
+        ),
+        Some(_) => Ok(()),
+        None => {
+            let candidates = theme::get_themes_for_framework(framework);
+            if let Some(suggestion) = diagnostics::did_you_mean(name, candidates.iter().map(|t| t.name)) {
+                bail!("✗ Error: Theme '{name}' is not in the theme registry\n  → Did you mean '{suggestion}'?");
+            }
+            if *framework == FrameworkType::Zap {
+                bail!(
+                    "✗ Error: zap has no repository to install theme '{name}' from\n  → Run 'zprof available themes --framework zap' to see themes for zap"
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Fail unless oh-my-zsh can find the theme in the profile's installation
+///
+/// oh-my-zsh loads `<name>.zsh-theme` from `custom/themes`, `custom` or
+/// `themes`. Themes that are prompt engines live elsewhere, and a profile
+/// without oh-my-zsh installed yet has nothing to check against.
+fn check_oh_my_zsh_theme_installed(profile_name: &str, name: &str) -> Result<()> {
+    if PromptEngine::from_name(name).is_some() {
+        return Ok(());
+    }
+
+    let omz_dir = installer::framework_dir(&FrameworkType::OhMyZsh, &profile::get_profile_path(profile_name)?);
+    if !omz_dir.is_dir() {
+        return Ok(());
+    }
+
+    let file = format!("{name}.zsh-theme");
+    let custom_dir = omz_dir.join("custom").join("themes");
+    let found = [custom_dir.join(&file), omz_dir.join("custom").join(&file), omz_dir.join("themes").join(&file)]
+        .iter()
+        .any(|path| path.is_file());
+    if !found {
+        bail!(
+            "✗ Error: Theme '{name}' is not installed in '{profile_name}'\n  → Put {file} in {} or run 'zprof available themes --framework oh-my-zsh' to see bundled themes",
+            custom_dir.display()
+        );
+    }
+    Ok(())
+}
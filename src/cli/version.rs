@@ -1,11 +1,29 @@
 use anyhow::Result;
 use clap::Args;
+use serde::Serialize;
+
+use crate::cli::format::{self, OutputFormat};
 
 /// Display version information
 #[derive(Debug, Args)]
 pub struct VersionArgs {}
 
-pub fn execute(_args: VersionArgs) -> Result<()> {
+/// What `--format json|toml` prints
+#[derive(Debug, Serialize)]
+struct VersionInfo {
+    name: &'static str,
+    version: &'static str,
+}
+
+pub fn execute(_args: VersionArgs, format: OutputFormat) -> Result<()> {
+    if !format.is_plain() {
+        let info = VersionInfo {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+        };
+        return format::print(format, &info);
+    }
+
     println!("zprof {}", env!("CARGO_PKG_VERSION"));
     Ok(())
 }
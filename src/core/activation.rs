@@ -0,0 +1,327 @@
+//! Profile activation
+//!
+//! Switching profiles is shared by `zprof use` and the GUI: both validate the
+//! target, run the outgoing profile's `on_deactivate` hook, point config.toml
+//! and `~/.zshenv` at the new profile, then run its `on_activate` hook.
+//!
+//! Hooks run with `$SHELL` (falling back to `/bin/sh`) in the profile
+//! directory, with `ZPROF_PROFILE` and `ZPROF_PROFILE_DIR` set. A hook that
+//! fails or runs past its timeout does not undo the switch; it is reported
+//! back as a `HookFailure`.
+//!
+//! A session (`zprof shell`, `zprof run`) is lighter: zsh is started with the
+//! profile's `ZDOTDIR` and `ZPROF_PROFILE` in its environment only, so
+//! nothing is written and no hooks run.
+
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::core::{config, filesystem, manifest, profile};
+use crate::shell::zdotdir;
+
+/// Result of switching to a profile
+#[derive(Debug)]
+pub struct Activation {
+    /// Directory of the newly active profile
+    pub profile_path: PathBuf,
+    /// Hooks that failed or timed out during the switch
+    pub hook_failures: Vec<HookFailure>,
+}
+
+/// A hook that did not complete successfully
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookFailure {
+    pub profile: String,
+    pub hook: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for HookFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} hook for '{}' failed: {}", self.hook, self.profile, self.message)
+    }
+}
+
+/// Switch the active profile, running deactivate/activate hooks
+///
+/// Activating the profile that is already active re-applies config.toml and
+/// `~/.zshenv` but runs no hooks.
+pub fn activate_profile(profile_name: &str) -> Result<Activation> {
+    let profile_path = profile::get_profile_path(profile_name)?;
+    profile::validate_profile(&profile_path)?;
+
+    // Invalid manifests prevent profile activation
+    let manifest = manifest::load_and_validate(profile_name)
+        .context("Cannot switch to profile with invalid manifest")?;
+
+    // Shared history must exist for cross-profile history sharing
+    filesystem::create_shared_history().context("Failed to create shared history file")?;
+
+    let previous_profile = config::load_config()?.active_profile;
+    let switching = previous_profile.as_deref() != Some(profile_name);
+    let mut hook_failures = Vec::new();
+
+    if let Some(previous) = previous_profile.as_deref().filter(|_| switching) {
+        // The previous profile may have been deleted or broken since it was activated
+        if let Ok(previous_manifest) = manifest::load_and_validate(previous) {
+            if let Some(hook) = &previous_manifest.hooks.on_deactivate {
+                let previous_path = profile::get_profile_path(previous)?;
+                if let Err(e) = run_hook(previous, &previous_path, hook, previous_manifest.hooks.timeout()) {
+                    hook_failures.push(HookFailure {
+                        profile: previous.to_string(),
+                        hook: "on_deactivate",
+                        message: format!("{e:#}"),
+                    });
+                }
+            }
+        }
+    }
+
+    config::update_active_profile(profile_name)
+        .context("Failed to update active profile in config")?;
+
+    // ZDOTDIR in ~/.zshenv persists across all future shell sessions
+    zdotdir::set_active_profile(&profile_path)
+        .context("Failed to set ZDOTDIR for new profile")?;
+
+    if switching {
+        if let Some(hook) = &manifest.hooks.on_activate {
+            if let Err(e) = run_hook(profile_name, &profile_path, hook, manifest.hooks.timeout()) {
+                hook_failures.push(HookFailure {
+                    profile: profile_name.to_string(),
+                    hook: "on_activate",
+                    message: format!("{e:#}"),
+                });
+            }
+        }
+    }
+
+    Ok(Activation {
+        profile_path,
+        hook_failures,
+    })
+}
+
+/// How long to keep collecting stderr after a hook exits
+///
+/// Background processes a hook starts (`ssh-agent`, `kubectl proxy &`)
+/// inherit its stderr and can hold the pipe open indefinitely.
+const STDERR_GRACE: Duration = Duration::from_millis(200);
+
+/// Run a hook command or script, stopping it after `timeout`
+///
+/// Output on stdout is passed through; stderr is captured so it can be
+/// included in the error if the hook fails.
+pub fn run_hook(profile_name: &str, profile_path: &Path, hook: &str, timeout: Duration) -> Result<()> {
+    let shell = std::env::var("SHELL")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string());
+
+    let mut command = Command::new(&shell);
+    match manifest::hook_script_path(hook) {
+        Some(script) => {
+            let script_path = resolve_script_path(script, profile_path)?;
+            if !script_path.is_file() {
+                bail!("script not found: {}", script_path.display());
+            }
+            command.arg(script_path);
+        }
+        None => {
+            command.arg("-c").arg(hook);
+        }
+    }
+
+    // Own process group, so a timeout stops everything the hook started
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let mut child = command
+        .current_dir(profile_path)
+        .env("ZPROF_PROFILE", profile_name)
+        .env("ZPROF_PROFILE_DIR", profile_path)
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("could not start {shell}"))?;
+
+    // Drain stderr on a separate thread so a chatty hook can't block on a full pipe
+    let (stderr_sender, stderr_chunks) = mpsc::channel::<Vec<u8>>();
+    if let Some(mut pipe) = child.stderr.take() {
+        thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            while let Ok(read @ 1..) = pipe.read(&mut buffer) {
+                if stderr_sender.send(buffer[..read].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            #[cfg(unix)]
+            let _ = Command::new("kill")
+                .args(["-KILL", "--", &format!("-{}", child.id())])
+                .status();
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!("timed out after {}s and was stopped", timeout.as_secs());
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+
+    // The pipe closes once every writer is gone, unless the hook left a
+    // background process holding it; then settle for what has arrived
+    let mut stderr = Vec::new();
+    let deadline = Instant::now() + STDERR_GRACE;
+    while let Ok(chunk) = stderr_chunks.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        stderr.extend(chunk);
+    }
+    let stderr = String::from_utf8_lossy(&stderr);
+
+    if !status.success() {
+        let code = status
+            .code()
+            .map_or_else(|| "a signal".to_string(), |c| format!("status {c}"));
+        let stderr = stderr.trim();
+        if stderr.is_empty() {
+            bail!("exited with {code}");
+        }
+        bail!("exited with {code}\n  {}", stderr.replace('\n', "\n  "));
+    }
+
+    Ok(())
+}
+
+/// Resolve a hook script path: `~/` is the home directory, relative paths
+/// are relative to the profile directory
+fn resolve_script_path(script: &str, profile_path: &Path) -> Result<PathBuf> {
+    if let Some(rest) = script.strip_prefix("~/") {
+        let home = dirs::home_dir().context("Could not find home directory")?;
+        return Ok(home.join(rest));
+    }
+
+    Ok(profile_path.join(script))
+}
+
+/// Environment variable naming the profile a session was started with
+pub const SESSION_PROFILE_VAR: &str = "ZPROF_PROFILE";
+
+/// A `zsh` command, with `args`, that starts with `profile_name` for its
+/// own session without activating the profile
+pub fn session_command(profile_name: &str, args: &[&str]) -> Result<Command> {
+    profile::validate_profile_name(profile_name)?;
+    let profile_path = profile::get_profile_path(profile_name)?;
+    profile::validate_profile(&profile_path)?;
+    manifest::load_and_validate(profile_name)
+        .context("Cannot start a session with a profile that has an invalid manifest")?;
+    filesystem::create_shared_history().context("Failed to create shared history file")?;
+
+    let mut command = Command::new("zsh");
+    command
+        .args(args)
+        .env("ZDOTDIR", &profile_path)
+        .env(SESSION_PROFILE_VAR, profile_name);
+    Ok(command)
+}
+
+/// Profile of the session this process runs in, if it was started by
+/// `zprof shell` or `zprof run`
+pub fn session_profile() -> Option<String> {
+    std::env::var(SESSION_PROFILE_VAR).ok().filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_run_hook_command_gets_profile_env() {
+        let temp_dir = TempDir::new().unwrap();
+        run_hook(
+            "work",
+            temp_dir.path(),
+            "echo \"$ZPROF_PROFILE\" > activated",
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+        let written = std::fs::read_to_string(temp_dir.path().join("activated")).unwrap();
+        assert_eq!(written.trim(), "work");
+    }
+
+    #[test]
+    fn test_run_hook_script_relative_to_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("hooks")).unwrap();
+        std::fs::write(temp_dir.path().join("hooks/activate.sh"), "touch from-script\n").unwrap();
+
+        run_hook("work", temp_dir.path(), "./hooks/activate.sh", Duration::from_secs(5)).unwrap();
+        assert!(temp_dir.path().join("from-script").exists());
+
+        let err = run_hook("work", temp_dir.path(), "./hooks/missing.sh", Duration::from_secs(5))
+            .unwrap_err();
+        assert!(err.to_string().contains("script not found"));
+    }
+
+    #[test]
+    fn test_run_hook_reports_exit_status_and_stderr() {
+        let temp_dir = TempDir::new().unwrap();
+        let err = run_hook(
+            "work",
+            temp_dir.path(),
+            "echo 'no such context' >&2; exit 3",
+            Duration::from_secs(5),
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("exited with status 3"), "{err}");
+        assert!(err.contains("no such context"), "{err}");
+    }
+
+    #[test]
+    fn test_run_hook_times_out() {
+        let temp_dir = TempDir::new().unwrap();
+        let started = Instant::now();
+        let err = run_hook("work", temp_dir.path(), "sleep 5", Duration::from_secs(1))
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("timed out after 1s"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_run_hook_returns_while_background_process_holds_stderr() {
+        let temp_dir = TempDir::new().unwrap();
+        let started = Instant::now();
+        let err = run_hook(
+            "work",
+            temp_dir.path(),
+            "sleep 5 & echo 'agent started' >&2; exit 2",
+            Duration::from_secs(10),
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("exited with status 2"), "{err}");
+        assert!(err.contains("agent started"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+}
@@ -0,0 +1,303 @@
+//! Pick a profile from the directory the shell is in
+//!
+//! A `.zprof-profile` file in a directory or any of its parents names the
+//! profile for that tree, and the nearest one wins. Without a marker, the
+//! `[[auto.rules]]` in config.toml are checked in order. The generated
+//! `.zshrc` asks `zprof auto resolve` on each `cd` and offers to restart the
+//! shell with `zprof shell` when the answer isn't its own profile.
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::config::{AutoConfig, AutoRule};
+use crate::core::profile;
+
+/// Marker file naming the profile for the directory tree it is in
+pub const MARKER_FILE: &str = ".zprof-profile";
+
+/// What made a directory want a profile
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchSource {
+    /// A marker file, by path
+    Marker(PathBuf),
+    /// An `[[auto.rules]]` entry, numbered from 1
+    Rule(usize),
+}
+
+/// The profile a directory wants
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoMatch {
+    pub profile: String,
+    /// Top of the directory tree the match covers
+    pub root: PathBuf,
+    pub source: MatchSource,
+    /// For a remote rule, the remote URL that matched
+    pub remote_url: Option<String>,
+}
+
+/// The profile `dir` wants, if any
+///
+/// Rules that can't be used (no matcher, bad glob) are skipped here;
+/// `zprof auto status` reports them.
+pub fn resolve(dir: &Path, auto: &AutoConfig) -> Result<Option<AutoMatch>> {
+    if let Some(found) = find_marker(dir)? {
+        return Ok(Some(found));
+    }
+
+    let mut repo = RepoRemotes::new(dir);
+    for (index, rule) in auto.rules.iter().enumerate() {
+        if let Ok(Some(found)) = check_rule(index + 1, rule, dir, &mut repo) {
+            return Ok(Some(found));
+        }
+    }
+    Ok(None)
+}
+
+/// The nearest `.zprof-profile` at or above `dir`
+pub fn find_marker(dir: &Path) -> Result<Option<AutoMatch>> {
+    for ancestor in dir.ancestors() {
+        let marker = ancestor.join(MARKER_FILE);
+        if !marker.is_file() {
+            continue;
+        }
+        let content =
+            fs::read_to_string(&marker).with_context(|| format!("Failed to read {}", marker.display()))?;
+        let Some(profile) = content.lines().map(str::trim).find(|line| !line.is_empty() && !line.starts_with('#'))
+        else {
+            bail!("{} doesn't name a profile", marker.display());
+        };
+        // The name ends up in a path and in `zprof shell`, so a marker from
+        // a cloned repository must not point outside the profiles directory
+        if profile::validate_profile_name(profile).is_err() {
+            bail!("{} names '{profile}', which isn't a valid profile name", marker.display());
+        }
+        return Ok(Some(AutoMatch {
+            profile: profile.to_string(),
+            root: ancestor.to_path_buf(),
+            source: MatchSource::Marker(marker),
+            remote_url: None,
+        }));
+    }
+    Ok(None)
+}
+
+/// Whether rule number `index` matches `dir`; errors explain unusable rules
+pub fn check_rule(index: usize, rule: &AutoRule, dir: &Path, repo: &mut RepoRemotes) -> Result<Option<AutoMatch>> {
+    if profile::validate_profile_name(&rule.profile).is_err() {
+        bail!("'{}' isn't a valid profile name", rule.profile);
+    }
+    let found = |root: PathBuf, remote_url: Option<String>| AutoMatch {
+        profile: rule.profile.clone(),
+        root,
+        source: MatchSource::Rule(index),
+        remote_url,
+    };
+
+    match (&rule.path, &rule.glob, &rule.remote) {
+        (Some(path), None, None) => {
+            let root = expand_home(path.trim_end_matches('/'));
+            Ok(dir.starts_with(&root).then(|| found(root, None)))
+        }
+        (None, Some(glob), None) => {
+            let pattern = glob_pattern(&expand_home(glob.trim_end_matches('/')).to_string_lossy());
+            let tree = Regex::new(&format!("^({pattern})(?:/.*)?$"))
+                .with_context(|| format!("invalid glob '{glob}'"))?;
+            let dir = dir.to_string_lossy();
+            Ok(tree.captures(&dir).map(|captures| found(PathBuf::from(&captures[1]), None)))
+        }
+        (None, None, Some(remote)) => {
+            let pattern = Regex::new(&format!("^{}$", glob_pattern(&normalize_remote(remote))))
+                .with_context(|| format!("invalid remote pattern '{remote}'"))?;
+            let Some((workdir, urls)) = repo.get() else {
+                return Ok(None);
+            };
+            Ok(urls
+                .iter()
+                .find(|url| pattern.is_match(&normalize_remote(url)))
+                .map(|url| found(workdir.clone(), Some(url.clone()))))
+        }
+        _ => bail!("set exactly one of path, glob or remote"),
+    }
+}
+
+/// Remotes of the git repository containing a directory, looked up once
+pub struct RepoRemotes {
+    dir: PathBuf,
+    remotes: Option<Option<(PathBuf, Vec<String>)>>,
+}
+
+impl RepoRemotes {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            remotes: None,
+        }
+    }
+
+    /// Work tree and remote URLs, `origin` first; None outside a repository
+    fn get(&mut self) -> Option<&(PathBuf, Vec<String>)> {
+        let dir = &self.dir;
+        self.remotes
+            .get_or_insert_with(|| {
+                let repo = git2::Repository::discover(dir).ok()?;
+                let workdir = repo.workdir()?.to_path_buf();
+                let mut names: Vec<String> = repo.remotes().ok()?.iter().flatten().map(String::from).collect();
+                names.sort_by_key(|name| name != "origin");
+                let urls = names
+                    .iter()
+                    .filter_map(|name| repo.find_remote(name).ok()?.url().map(String::from))
+                    .collect();
+                // Rebuilt from components to drop git2's trailing slash
+                Some((workdir.components().collect(), urls))
+            })
+            .as_ref()
+    }
+}
+
+/// Regex for a glob: `*` and `?` stay within one path segment, `**` doesn't
+fn glob_pattern(glob: &str) -> String {
+    let mut pattern = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern
+}
+
+/// A remote URL as `host/path`, without scheme, user or `.git`
+///
+/// `git@github.com:acme/api.git` and `https://github.com/acme/api` both
+/// become `github.com/acme/api`.
+fn normalize_remote(url: &str) -> String {
+    let url = url.trim();
+    let (has_scheme, rest) = match url.split_once("://") {
+        Some((_, rest)) => (true, rest),
+        None => (false, url),
+    };
+    let rest = match rest.split_once('@') {
+        Some((user, host)) if !user.contains('/') => host,
+        _ => rest,
+    };
+    // scp-style `host:path`
+    let rest = if has_scheme { rest.to_string() } else { rest.replacen(':', "/", 1) };
+    rest.trim_end_matches('/').trim_end_matches(".git").to_string()
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ if path == "~" => dirs::home_dir().unwrap_or_else(|| PathBuf::from(path)),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn rule(profile: &str, path: Option<&str>, glob: Option<&str>, remote: Option<&str>) -> AutoRule {
+        AutoRule {
+            profile: profile.to_string(),
+            path: path.map(String::from),
+            glob: glob.map(String::from),
+            remote: remote.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_normalize_remote() {
+        assert_eq!(normalize_remote("git@github.com:acme/api.git"), "github.com/acme/api");
+        assert_eq!(normalize_remote("https://github.com/acme/api"), "github.com/acme/api");
+        assert_eq!(normalize_remote("ssh://git@github.com/acme/api.git/"), "github.com/acme/api");
+        assert_eq!(normalize_remote("https://git.example.com:8443/acme/api"), "git.example.com:8443/acme/api");
+    }
+
+    #[test]
+    fn test_path_and_glob_rules_match_trees() {
+        let dir = Path::new("/src/clients/acme/api");
+        let mut repo = RepoRemotes::new(dir);
+
+        let path = rule("client", Some("/src/clients/"), None, None);
+        let found = check_rule(1, &path, dir, &mut repo).unwrap().unwrap();
+        assert_eq!(found.root, PathBuf::from("/src/clients"));
+        let other = rule("client", Some("/src/client"), None, None);
+        assert!(check_rule(1, &other, dir, &mut repo).unwrap().is_none());
+
+        let glob = rule("client", None, Some("/src/clients/*"), None);
+        let found = check_rule(2, &glob, dir, &mut repo).unwrap().unwrap();
+        assert_eq!(found.root, PathBuf::from("/src/clients/acme"));
+        assert_eq!(found.source, MatchSource::Rule(2));
+
+        let deep = rule("client", None, Some("/src/**/api"), None);
+        assert!(check_rule(3, &deep, dir, &mut repo).unwrap().is_some());
+        let single = rule("client", None, Some("/src/*/api"), None);
+        assert!(check_rule(4, &single, dir, &mut repo).unwrap().is_none());
+
+        let both = rule("client", Some("/src"), Some("/src/*"), None);
+        assert!(check_rule(5, &both, dir, &mut repo).is_err());
+
+        let escape = rule("../../repo/.evil", Some("/src"), None, None);
+        assert!(check_rule(6, &escape, dir, &mut repo).is_err());
+    }
+
+    #[test]
+    fn test_marker_wins_over_rules() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        let nested = project.join("src").join("deep");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(project.join(MARKER_FILE), "# team profile\nwork\n").unwrap();
+
+        let auto = AutoConfig {
+            rules: vec![rule("other", Some(&temp.path().display().to_string()), None, None)],
+            ..Default::default()
+        };
+        let found = resolve(&nested, &auto).unwrap().unwrap();
+        assert_eq!(found.profile, "work");
+        assert_eq!(found.root, project);
+
+        let found = resolve(temp.path(), &auto).unwrap().unwrap();
+        assert_eq!(found.profile, "other");
+        assert_eq!(found.source, MatchSource::Rule(1));
+
+        for name in ["../../repo/.evil", "/tmp/evil"] {
+            fs::write(project.join(MARKER_FILE), format!("{name}\n")).unwrap();
+            assert!(resolve(&nested, &auto).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_remote_rule_matches_repository_remotes() {
+        let temp = TempDir::new().unwrap();
+        let repo = git2::Repository::init(temp.path()).unwrap();
+        repo.remote("upstream", "https://github.com/other/api.git").unwrap();
+        repo.remote("origin", "git@github.com:acme/api.git").unwrap();
+        let nested = temp.path().join("src");
+        fs::create_dir_all(&nested).unwrap();
+
+        let mut remotes = RepoRemotes::new(&nested);
+        let acme = rule("acme", None, None, Some("github.com/acme/*"));
+        let found = check_rule(1, &acme, &nested, &mut remotes).unwrap().unwrap();
+        assert_eq!(found.remote_url.as_deref(), Some("git@github.com:acme/api.git"));
+        assert_eq!(found.root.canonicalize().unwrap(), temp.path().canonicalize().unwrap());
+
+        let elsewhere = rule("acme", None, None, Some("gitlab.com/acme/*"));
+        assert!(check_rule(2, &elsewhere, &nested, &mut remotes).unwrap().is_none());
+    }
+}
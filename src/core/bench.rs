@@ -0,0 +1,462 @@
+//! Measure interactive shell startup time per profile
+//!
+//! Each run starts `zsh -i -c exit` with `ZDOTDIR` pointed at the profile, so
+//! it times the profile's own `.zshenv` and `.zshrc` plus the system startup
+//! files without going through `~/.zshenv`. A baseline result for each
+//! profile is kept in `cache/bench/` so a later run, typically after
+//! `zprof regenerate`, can be checked against it. The baseline is the best
+//! result so far, unless a slower one was accepted in its place.
+//!
+//! The breakdown loads `zsh/zprof` before the profile's files and maps the
+//! time spent in each function to the plugin whose file defined it, using
+//! zsh's `$functions_source`.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::core::{filesystem, manifest::Manifest, profile};
+use crate::frameworks::{installer, FrameworkType};
+
+/// Timed runs per profile when `--runs` isn't given
+pub const DEFAULT_RUNS: usize = 10;
+
+/// A shell that takes longer than this to start, or to be profiled, is stopped
+const RUN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How much slower than the last result a run must be to count as a
+/// regression; the absolute minimum keeps noise on fast profiles from counting
+const REGRESSION_RATIO: f64 = 1.2;
+const REGRESSION_MIN_MS: f64 = 5.0;
+
+/// Separates `zprof` output from the function sources in the breakdown run
+const SOURCES_MARKER: &str = "@@zprof-sources";
+
+/// Startup time of one profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub profile: String,
+    pub runs: usize,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub measured_at: DateTime<Utc>,
+    /// Time spent in each plugin, when measured with `--breakdown`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breakdown: Vec<Cost>,
+}
+
+/// Time spent in functions belonging to one plugin, the framework, or a
+/// function nothing else claims (such as `compinit`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cost {
+    pub name: String,
+    pub ms: f64,
+}
+
+impl BenchResult {
+    /// Summarise timed runs; `samples` must not be empty
+    pub fn from_samples(profile_name: &str, samples: &[Duration]) -> Self {
+        let mut ms: Vec<f64> = samples.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        ms.sort_by(f64::total_cmp);
+
+        Self {
+            profile: profile_name.to_string(),
+            runs: ms.len(),
+            mean_ms: ms.iter().sum::<f64>() / ms.len() as f64,
+            p50_ms: percentile(&ms, 0.50),
+            p95_ms: percentile(&ms, 0.95),
+            measured_at: Utc::now(),
+            breakdown: Vec::new(),
+        }
+    }
+
+    /// Whether this result is enough slower than `previous` to report
+    pub fn regressed_from(&self, previous: &BenchResult) -> bool {
+        self.mean_ms > previous.mean_ms * REGRESSION_RATIO
+            && self.mean_ms - previous.mean_ms >= REGRESSION_MIN_MS
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[f64], quantile: f64) -> f64 {
+    let rank = (quantile * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Time `runs` interactive shell starts of a profile
+///
+/// One untimed run goes first so the results don't include a cold disk cache.
+pub fn measure(profile_name: &str, runs: usize) -> Result<BenchResult> {
+    if runs == 0 {
+        bail!("✗ Error: --runs must be at least 1");
+    }
+    let profile_path = generated_profile_path(profile_name)?;
+
+    time_startup(&profile_path)?;
+    let samples = (0..runs)
+        .map(|_| time_startup(&profile_path))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(BenchResult::from_samples(profile_name, &samples))
+}
+
+/// Attribute a profile's startup time to its plugins using `zsh/zprof`
+///
+/// Only time spent inside functions is seen; code a plugin runs at the top
+/// level of its file while being sourced isn't counted.
+pub fn breakdown(profile_name: &str, manifest: &Manifest) -> Result<Vec<Cost>> {
+    let profile_path = generated_profile_path(profile_name)?;
+
+    // A throwaway ZDOTDIR whose .zshenv loads zprof, then hands over to the profile
+    let wrapper_dir = bench_dir()?.join(format!("{profile_name}.zdotdir"));
+    filesystem::create_directory(&wrapper_dir)?;
+    let profile_dir = profile_path.display().to_string().replace('\'', r"'\''");
+    fs::write(
+        wrapper_dir.join(".zshenv"),
+        format!(
+            "zmodload zsh/zprof\nZDOTDIR='{profile_dir}'\n[[ -f \"$ZDOTDIR/.zshenv\" ]] && source \"$ZDOTDIR/.zshenv\"\n"
+        ),
+    )
+    .context("Failed to write the zprof startup file")?;
+
+    let script = format!(
+        "zprof; print -r -- {SOURCES_MARKER}; for f in ${{(k)functions_source}}; do print -r -- \"$f\"$'\\t'\"${{functions_source[$f]}}\"; done"
+    );
+    // Written to a file rather than a pipe, which could fill up while the
+    // shell is being waited on
+    let report_path = wrapper_dir.join("zprof.out");
+    let output = File::create(&report_path)
+        .context("Failed to create the zprof output file")
+        .and_then(|report| {
+            let mut command = zsh_command(&wrapper_dir, &script);
+            command.stdout(report);
+            run_bounded(command)
+        })
+        .and_then(|finished| Ok((finished, fs::read(&report_path)?)));
+    let _ = fs::remove_dir_all(&wrapper_dir);
+    let (finished, stdout) = output?;
+    match finished {
+        None => bail!(
+            "✗ Error: zsh took longer than {}s while profiling '{profile_name}'\n  → Check the profile's .zshrc for commands waiting on input",
+            RUN_TIMEOUT.as_secs()
+        ),
+        Some((status, _)) if !status.success() => bail!(
+            "✗ Error: zsh exited with {status} while profiling '{profile_name}'\n  → Run 'zprof bench {profile_name}' without --breakdown to check the profile starts"
+        ),
+        Some(_) => {}
+    }
+
+    let stdout = String::from_utf8_lossy(&stdout);
+    let (report, sources) = stdout.split_once(SOURCES_MARKER).unwrap_or((&stdout, ""));
+    let sources: HashMap<&str, &str> = sources.lines().filter_map(|line| line.split_once('\t')).collect();
+
+    let framework = FrameworkType::from_name(&manifest.profile.framework);
+    let owners = PluginOwners::new(manifest, framework.as_ref(), &profile_path);
+    let mut costs: Vec<Cost> = Vec::new();
+    for (function, ms) in parse_zprof(report) {
+        let name = owners.owner(&function, sources.get(function.as_str()).copied());
+        match costs.iter_mut().find(|c| c.name == name) {
+            Some(cost) => cost.ms += ms,
+            None => costs.push(Cost { name, ms }),
+        }
+    }
+    costs.sort_by(|a, b| b.ms.total_cmp(&a.ms));
+
+    Ok(costs)
+}
+
+/// Self time in ms of each function in the first table of `zprof` output
+fn parse_zprof(report: &str) -> Vec<(String, f64)> {
+    // num) calls  total  avg  total%  self  avg  self%  name
+    let row = Regex::new(
+        r"^\s*\d+\)\s+\d+\s+[\d.]+\s+[\d.]+\s+[\d.]+%\s+([\d.]+)\s+[\d.]+\s+[\d.]+%\s+(\S+)\s*$",
+    )
+    .expect("zprof row pattern is valid");
+
+    let mut functions = Vec::new();
+    for line in report.lines() {
+        match row.captures(line) {
+            Some(captures) => {
+                if let Ok(ms) = captures[1].parse::<f64>() {
+                    functions.push((captures[2].to_string(), ms));
+                }
+            }
+            // The call graph that follows the table repeats every function
+            None if !functions.is_empty() => break,
+            None => {}
+        }
+    }
+    functions
+}
+
+/// Decides which plugin a function belongs to from the file that defined it
+struct PluginOwners {
+    /// Plugin name and the directory names it may be installed under
+    plugins: Vec<(String, Vec<String>)>,
+    profile_path: PathBuf,
+    /// Framework checkout and the name to report its own functions under
+    framework: Option<(PathBuf, String)>,
+}
+
+impl PluginOwners {
+    fn new(manifest: &Manifest, framework: Option<&FrameworkType>, profile_path: &Path) -> Self {
+        let plugins = manifest
+            .plugins
+            .resolved()
+            .into_iter()
+            .map(|entry| {
+                let mut dirs = vec![entry.name.clone()];
+                if let Some(repo) = framework.and_then(|f| entry.repo_for(f)) {
+                    let repo = repo.trim_end_matches('/').trim_end_matches(".git");
+                    if let Some(dir) = repo.rsplit('/').next().filter(|d| !d.is_empty()) {
+                        dirs.push(dir.to_string());
+                    }
+                }
+                (entry.name, dirs)
+            })
+            .collect();
+
+        Self {
+            plugins,
+            profile_path: profile_path.to_path_buf(),
+            framework: framework.map(|f| (installer::framework_dir(f, profile_path), f.name().to_string())),
+        }
+    }
+
+    fn owner(&self, function: &str, source: Option<&str>) -> String {
+        let Some(relative) = source.and_then(|s| Path::new(s).strip_prefix(&self.profile_path).ok()) else {
+            return function.to_string();
+        };
+
+        let components: Vec<&str> = relative.iter().filter_map(|c| c.to_str()).collect();
+        for (name, dirs) in &self.plugins {
+            // zinit checks repositories out as `user---repo`
+            let installed_here = components.iter().any(|component| {
+                dirs.iter()
+                    .any(|dir| *component == dir || component.ends_with(&format!("---{dir}")))
+            });
+            if installed_here {
+                return name.clone();
+            }
+        }
+
+        match &self.framework {
+            Some((dir, name)) if self.profile_path.join(relative).starts_with(dir) => name.clone(),
+            _ => function.to_string(),
+        }
+    }
+}
+
+/// Start one interactive shell for the profile and time it until it exits
+fn time_startup(profile_path: &Path) -> Result<Duration> {
+    let Some((status, elapsed)) = run_bounded(zsh_command(profile_path, "exit"))? else {
+        bail!(
+            "✗ Error: zsh took longer than {}s to start with {}\n  → Check the profile's .zshrc for commands waiting on input",
+            RUN_TIMEOUT.as_secs(),
+            profile_path.display()
+        );
+    };
+
+    if !status.success() {
+        bail!("✗ Error: zsh exited with {status} when starting with {}", profile_path.display());
+    }
+    Ok(elapsed)
+}
+
+/// Run `command` until it exits, returning its status and how long it ran
+///
+/// The command gets its own process group so one that hangs can be stopped,
+/// along with anything it started. Returns `None` if it was stopped after
+/// `RUN_TIMEOUT`.
+fn run_bounded(mut command: Command) -> Result<Option<(ExitStatus, Duration)>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let started = Instant::now();
+    let mut child = command.spawn().map_err(spawn_error)?;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some((status, started.elapsed())));
+        }
+        if started.elapsed() >= RUN_TIMEOUT {
+            #[cfg(unix)]
+            let _ = Command::new("kill")
+                .args(["-KILL", "--", &format!("-{}", child.id())])
+                .status();
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        // Short enough not to skew results measured in tens of milliseconds
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// `zsh -i -c <script>` reading its startup files from `zdotdir`
+fn zsh_command(zdotdir: &Path, script: &str) -> Command {
+    let mut command = Command::new("zsh");
+    command
+        .args(["-i", "-c", script])
+        .env("ZDOTDIR", zdotdir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    command
+}
+
+fn spawn_error(error: std::io::Error) -> anyhow::Error {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        anyhow::anyhow!("✗ Error: zsh is not installed\n  → Install zsh to benchmark profiles")
+    } else {
+        anyhow::Error::new(error).context("Failed to start zsh")
+    }
+}
+
+/// Directory of a profile whose shell files have been generated
+fn generated_profile_path(profile_name: &str) -> Result<PathBuf> {
+    let profile_path = profile::get_profile_path(profile_name)?;
+    if !profile_path.exists() {
+        bail!("✗ Error: Profile '{profile_name}' not found\n  → Run 'zprof list' to see available profiles");
+    }
+    if !profile_path.join(".zshrc").exists() {
+        bail!(
+            "✗ Error: Profile '{profile_name}' has no .zshrc\n  → Run 'zprof regenerate {profile_name}' to generate it"
+        );
+    }
+    Ok(profile_path)
+}
+
+fn bench_dir() -> Result<PathBuf> {
+    Ok(filesystem::get_zprof_dir()?.join("cache").join("bench"))
+}
+
+/// The baseline result for a profile, if it has been benchmarked
+pub fn load_baseline(profile_name: &str) -> Result<Option<BenchResult>> {
+    let path = bench_dir()?.join(format!("{profile_name}.json"));
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    // A result from an older zprof that no longer parses is just discarded
+    Ok(serde_json::from_str(&content).ok())
+}
+
+/// Store a result as the profile's baseline
+pub fn save_baseline(result: &BenchResult) -> Result<()> {
+    let dir = bench_dir()?;
+    filesystem::create_directory(&dir)?;
+    let path = dir.join(format!("{}.json", result.profile));
+    let content = serde_json::to_string_pretty(result).context("Failed to serialize benchmark result")?;
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(mean_ms: f64) -> BenchResult {
+        BenchResult {
+            profile: "work".to_string(),
+            runs: 10,
+            mean_ms,
+            p50_ms: mean_ms,
+            p95_ms: mean_ms,
+            measured_at: Utc::now(),
+            breakdown: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_from_samples_statistics() {
+        let samples: Vec<Duration> = [30, 10, 20, 50, 40, 60, 70, 80, 90, 100]
+            .iter()
+            .map(|ms| Duration::from_millis(*ms))
+            .collect();
+        let result = BenchResult::from_samples("work", &samples);
+
+        assert_eq!(result.runs, 10);
+        assert!((result.mean_ms - 55.0).abs() < 1e-9);
+        assert!((result.p50_ms - 50.0).abs() < 1e-9);
+        assert!((result.p95_ms - 100.0).abs() < 1e-9);
+
+        let single = BenchResult::from_samples("work", &[Duration::from_millis(12)]);
+        assert!((single.p50_ms - 12.0).abs() < 1e-9 && (single.p95_ms - 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_regression_needs_relative_and_absolute_slowdown() {
+        assert!(result(60.0).regressed_from(&result(40.0)));
+        // 50% slower, but only by 3ms
+        assert!(!result(9.0).regressed_from(&result(6.0)));
+        // 8ms slower, but only by 10%
+        assert!(!result(88.0).regressed_from(&result(80.0)));
+        assert!(!result(30.0).regressed_from(&result(40.0)));
+    }
+
+    #[test]
+    fn test_parse_zprof_reads_first_table_only() {
+        let report = "\
+num  calls                time                       self            name
+-----------------------------------------------------------------------------------
+ 1)    1          12.50    12.50   50.00%     10.00    10.00   40.00%  compinit
+ 2)    3           7.50     2.50   30.00%      7.50     2.50   30.00%  _zsh_autosuggest_bind_widgets
+ 3)    1           2.50     2.50   10.00%      2.50     2.50   10.00%  git_prompt_info
+
+-----------------------------------------------------------------------------------
+
+ 1)    1          12.50    12.50   50.00%     10.00    10.00   40.00%  compinit [1]
+";
+        let functions = parse_zprof(report);
+        assert_eq!(
+            functions,
+            vec![
+                ("compinit".to_string(), 10.0),
+                ("_zsh_autosuggest_bind_widgets".to_string(), 7.5),
+                ("git_prompt_info".to_string(), 2.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_owner_matches_plugin_directories() {
+        let profile_path = PathBuf::from("/home/me/.zsh-profiles/profiles/work");
+        let source = |rest: &str| profile_path.join(rest).display().to_string();
+        let toml = r#"
+[profile]
+name = "work"
+framework = "oh-my-zsh"
+prompt_mode = "framework_theme"
+framework_theme = "robbyrussell"
+
+[plugins]
+enabled = ["git", "zsh-autosuggestions"]
+"#;
+        let manifest: Manifest = toml::from_str(toml).unwrap();
+        let owners = PluginOwners::new(&manifest, Some(&FrameworkType::OhMyZsh), &profile_path);
+
+        let git = source(".oh-my-zsh/plugins/git/git.plugin.zsh");
+        assert_eq!(owners.owner("git_prompt_info", Some(&git)), "git");
+        let autosuggest = source(".oh-my-zsh/custom/plugins/zsh-autosuggestions/src/bind.zsh");
+        assert_eq!(owners.owner("_zsh_autosuggest_bind_widgets", Some(&autosuggest)), "zsh-autosuggestions");
+        let lib = source(".oh-my-zsh/lib/git.zsh");
+        assert_eq!(owners.owner("git_current_branch", Some(&lib)), "oh-my-zsh");
+        assert_eq!(owners.owner("compinit", Some("/usr/share/zsh/functions/Completion/compinit")), "compinit");
+        assert_eq!(owners.owner("precmd", None), "precmd");
+
+        let zinit = PluginOwners::new(&manifest, Some(&FrameworkType::Zinit), &profile_path);
+        let checkout = source(".zinit/plugins/zsh-users---zsh-autosuggestions/zsh-autosuggestions.zsh");
+        assert_eq!(zinit.owner("_zsh_autosuggest_start", Some(&checkout)), "zsh-autosuggestions");
+    }
+}
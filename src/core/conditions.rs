@@ -0,0 +1,221 @@
+//! Host-, OS- and environment-conditional manifest sections
+//!
+//! `[when.host."name"]`, `[when.os."name"]` and `[when.env."VAR"]` tables in
+//! profile.toml hold overlays that only apply on some machines. This module
+//! decides which of them match the current machine, and produces the zsh test
+//! used when they are emitted as guarded blocks instead.
+
+use std::collections::HashMap;
+use std::process::Command;
+
+use crate::core::manifest::{Overlay, WhenSection};
+
+/// Operating system names accepted in `[when.os."..."]`, with the `$OSTYPE`
+/// prefix zsh reports for each
+pub const OS_NAMES: &[(&str, &str)] = &[
+    ("linux", "linux"),
+    ("macos", "darwin"),
+    ("freebsd", "freebsd"),
+    ("openbsd", "openbsd"),
+    ("netbsd", "netbsd"),
+];
+
+/// Kind of condition an overlay is keyed by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionKind {
+    Host,
+    Os,
+    Env,
+}
+
+impl ConditionKind {
+    /// Name of the `[when.<kind>]` table
+    pub fn key(&self) -> &'static str {
+        match self {
+            ConditionKind::Host => "host",
+            ConditionKind::Os => "os",
+            ConditionKind::Env => "env",
+        }
+    }
+}
+
+/// A single overlay together with the condition that selects it
+#[derive(Debug, Clone, Copy)]
+pub struct Condition<'a> {
+    pub kind: ConditionKind,
+    pub name: &'a str,
+    pub overlay: &'a Overlay,
+}
+
+impl Condition<'_> {
+    /// How the condition is written in profile.toml, e.g. `when.os."linux"`
+    pub fn label(&self) -> String {
+        format!("when.{}.\"{}\"", self.kind.key(), self.name)
+    }
+
+    /// Whether this condition holds on `machine`
+    ///
+    /// Hosts match either the full hostname or its first label, ignoring case.
+    /// Environment conditions match when the variable is set and non-empty.
+    pub fn matches(&self, machine: &Machine) -> bool {
+        match self.kind {
+            ConditionKind::Host => {
+                let short = machine.host.split('.').next().unwrap_or_default();
+                self.name.eq_ignore_ascii_case(&machine.host) || self.name.eq_ignore_ascii_case(short)
+            }
+            ConditionKind::Os => self.name == machine.os,
+            ConditionKind::Env => machine.env.get(self.name).is_some_and(|value| !value.is_empty()),
+        }
+    }
+
+    /// zsh test for this condition, for use in `if ...; then`
+    ///
+    /// Names are validated before generation, so they can be embedded as-is.
+    pub fn guard(&self) -> String {
+        match self.kind {
+            ConditionKind::Host => format!(
+                "[[ ${{HOST:l}} == '{0}' || ${{${{HOST%%.*}}:l}} == '{0}' ]]",
+                self.name.to_lowercase()
+            ),
+            ConditionKind::Os => {
+                let prefix = os_type_prefix(self.name).unwrap_or(self.name);
+                format!("[[ $OSTYPE == {prefix}* ]]")
+            }
+            ConditionKind::Env => format!("[[ -n ${{{}:-}} ]]", self.name),
+        }
+    }
+}
+
+impl WhenSection {
+    /// All conditions in the order their overlays are applied: OS first,
+    /// then host, then environment, each sorted by name
+    pub fn conditions(&self) -> Vec<Condition<'_>> {
+        [
+            (ConditionKind::Os, &self.os),
+            (ConditionKind::Host, &self.host),
+            (ConditionKind::Env, &self.env),
+        ]
+        .into_iter()
+        .flat_map(|(kind, overlays)| {
+            overlays.iter().map(move |(name, overlay)| Condition {
+                kind,
+                name: name.as_str(),
+                overlay,
+            })
+        })
+        .collect()
+    }
+}
+
+/// The `$OSTYPE` prefix for an `[when.os]` name, or None if it isn't known
+pub fn os_type_prefix(name: &str) -> Option<&'static str> {
+    OS_NAMES
+        .iter()
+        .find(|(os, _)| *os == name)
+        .map(|(_, prefix)| *prefix)
+}
+
+/// Facts about a machine that conditions are matched against
+#[derive(Debug, Clone, Default)]
+pub struct Machine {
+    pub host: String,
+    /// One of the names in `OS_NAMES` (Rust's `std::env::consts::OS`)
+    pub os: String,
+    pub env: HashMap<String, String>,
+}
+
+impl Machine {
+    /// Describe the machine zprof is running on
+    pub fn current() -> Self {
+        Machine {
+            host: current_hostname(),
+            os: std::env::consts::OS.to_string(),
+            env: std::env::vars().collect(),
+        }
+    }
+}
+
+/// Hostname of the current machine, or an empty string if it can't be found
+fn current_hostname() -> String {
+    if let Ok(host) = std::env::var("HOST").or_else(|_| std::env::var("HOSTNAME")) {
+        if !host.trim().is_empty() {
+            return host.trim().to_string();
+        }
+    }
+
+    Command::new("hostname")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn machine() -> Machine {
+        Machine {
+            host: "work-laptop.corp.example.com".to_string(),
+            os: "macos".to_string(),
+            env: HashMap::from([("CI".to_string(), "true".to_string()), ("EMPTY".to_string(), String::new())]),
+        }
+    }
+
+    fn when(kind: ConditionKind, names: &[&str]) -> WhenSection {
+        let overlays: BTreeMap<String, Overlay> =
+            names.iter().map(|name| (name.to_string(), Overlay::default())).collect();
+        let mut when = WhenSection::default();
+        match kind {
+            ConditionKind::Host => when.host = overlays,
+            ConditionKind::Os => when.os = overlays,
+            ConditionKind::Env => when.env = overlays,
+        }
+        when
+    }
+
+    fn matching(when: &WhenSection) -> Vec<String> {
+        when.conditions()
+            .into_iter()
+            .filter(|c| c.matches(&machine()))
+            .map(|c| c.name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_host_matches_full_or_short_name() {
+        let when = when(ConditionKind::Host, &["Work-Laptop", "work-laptop.corp.example.com", "dev-vm"]);
+        assert_eq!(matching(&when), vec!["Work-Laptop", "work-laptop.corp.example.com"]);
+    }
+
+    #[test]
+    fn test_os_and_env_matching() {
+        assert_eq!(matching(&when(ConditionKind::Os, &["linux", "macos"])), vec!["macos"]);
+        assert_eq!(matching(&when(ConditionKind::Env, &["CI", "EMPTY", "UNSET"])), vec!["CI"]);
+    }
+
+    #[test]
+    fn test_conditions_order() {
+        let mut when = when(ConditionKind::Env, &["CI"]);
+        when.os = self::when(ConditionKind::Os, &["linux"]).os;
+        when.host = self::when(ConditionKind::Host, &["dev-vm"]).host;
+
+        let labels: Vec<String> = when.conditions().iter().map(Condition::label).collect();
+        assert_eq!(labels, vec!["when.os.\"linux\"", "when.host.\"dev-vm\"", "when.env.\"CI\""]);
+    }
+
+    #[test]
+    fn test_guards() {
+        let overlay = Overlay::default();
+        let guard = |kind, name| Condition { kind, name, overlay: &overlay }.guard();
+
+        assert_eq!(guard(ConditionKind::Os, "macos"), "[[ $OSTYPE == darwin* ]]");
+        assert_eq!(guard(ConditionKind::Env, "CI"), "[[ -n ${CI:-} ]]");
+        assert_eq!(
+            guard(ConditionKind::Host, "Dev-VM"),
+            "[[ ${HOST:l} == 'dev-vm' || ${${HOST%%.*}:l} == 'dev-vm' ]]"
+        );
+    }
+}
@@ -11,6 +11,67 @@ pub struct Config {
     /// Optional default framework preference
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default_framework: Option<String>,
+    /// Rules for picking a profile by directory
+    #[serde(default, skip_serializing_if = "AutoConfig::is_empty")]
+    pub auto: AutoConfig,
+}
+
+/// The `[auto]` section: which profile a directory tree wants
+///
+/// A `.zprof-profile` marker file wins over these rules; see `core::auto`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AutoConfig {
+    /// What the shell does on entering a directory that wants another profile
+    #[serde(default)]
+    pub mode: AutoMode,
+    /// Checked in order; the first match wins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<AutoRule>,
+}
+
+impl AutoConfig {
+    pub fn is_empty(&self) -> bool {
+        self.mode == AutoMode::default() && self.rules.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoMode {
+    /// Ask before re-starting the shell with the profile
+    #[default]
+    Ask,
+    /// Re-start the shell with the profile without asking
+    Switch,
+    /// Only print which profile the directory wants
+    Notify,
+}
+
+impl AutoMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AutoMode::Ask => "ask",
+            AutoMode::Switch => "switch",
+            AutoMode::Notify => "notify",
+        }
+    }
+}
+
+/// One `[[auto.rules]]` entry; exactly one of `path`, `glob` and `remote` is set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutoRule {
+    /// Profile to use where the rule matches
+    pub profile: String,
+    /// A directory; matches it and everything below it (`~/` allowed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// A glob on directories (`*`, `?`, `**`); matches them and everything below
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glob: Option<String>,
+    /// A glob on the URLs of the git repository's remotes, written without
+    /// scheme or `.git` (`github.com/acme/*`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
 }
 
 impl Config {
@@ -116,4 +177,30 @@ mod tests {
         assert_eq!(parsed.active_profile, Some("test-profile".to_string()));
         assert_eq!(parsed.default_framework, Some("oh-my-zsh".to_string()));
     }
+
+    #[test]
+    fn test_auto_rules_roundtrip() {
+        let content = r#"active_profile = "personal"
+
+[auto]
+mode = "notify"
+
+[[auto.rules]]
+profile = "work"
+path = "~/work"
+
+[[auto.rules]]
+profile = "acme"
+remote = "github.com/acme/*"
+"#;
+        let config: Config = toml::from_str(content).unwrap();
+        assert_eq!(config.auto.mode, AutoMode::Notify);
+        assert_eq!(config.auto.rules.len(), 2);
+        assert_eq!(config.auto.rules[1].remote.as_deref(), Some("github.com/acme/*"));
+
+        // Saving the active profile must not drop the rules
+        let parsed: Config = toml::from_str(&config.to_toml_string().unwrap()).unwrap();
+        assert_eq!(parsed.auto, config.auto);
+        assert!(!Config::new().to_toml_string().unwrap().contains("auto"));
+    }
 }
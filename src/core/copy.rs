@@ -0,0 +1,200 @@
+//! Copying and renaming profiles
+//!
+//! Shared by `zprof copy`, `zprof rename` and the GUI. A copy is a new
+//! profile: its directory is duplicated, `profile.name` and the timestamps
+//! are updated, and its shell files are regenerated. A rename moves the
+//! directory and also follows everything that refers to the old name:
+//! `extends` in child profiles and, for the active profile, config.toml and
+//! the `ZDOTDIR` line in `~/.zshenv`.
+
+use anyhow::{ensure, Context, Result};
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::document::ManifestDocument;
+use crate::core::{config, filesystem, manifest, profile};
+use crate::shell::{generator, zdotdir};
+
+/// What `rename_profile` changed besides the profile itself
+#[derive(Debug)]
+pub struct Rename {
+    /// New directory of the profile
+    pub profile_path: PathBuf,
+    /// Profiles whose `extends` now points at the new name
+    pub updated_children: Vec<String>,
+    /// True if the profile was active, so config.toml and `~/.zshenv` were updated
+    pub was_active: bool,
+}
+
+/// Duplicate `source` as a new profile called `dest`
+///
+/// Returns the new profile's directory. If anything fails after the copy
+/// started, the partial copy is removed.
+pub fn copy_profile(source: &str, dest: &str) -> Result<PathBuf> {
+    profile::validate_profile_name(dest)?;
+    let source_path = profile::get_profile_path(source)?;
+    profile::validate_profile(&source_path)?;
+    let dest_path = ensure_available(dest)?;
+
+    let result = filesystem::copy_dir_recursive(&source_path, &dest_path)
+        .context("Failed to copy profile directory")
+        .and_then(|()| {
+            let now = toml::Value::try_from(Utc::now()).context("Failed to serialize timestamp")?;
+            set_profile_keys(
+                &dest_path.join("profile.toml"),
+                &[
+                    ("name", dest.into()),
+                    ("created", now.clone()),
+                    ("modified", now),
+                ],
+            )
+        })
+        .and_then(|()| regenerate(dest));
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&dest_path);
+        return Err(e);
+    }
+
+    Ok(dest_path)
+}
+
+/// Rename profile `old` to `new`
+///
+/// If anything fails after the directory was moved, it is moved back and
+/// every file changed so far is restored.
+pub fn rename_profile(old: &str, new: &str) -> Result<Rename> {
+    profile::validate_profile_name(new)?;
+    let old_path = profile::get_profile_path(old)?;
+    profile::validate_profile(&old_path)?;
+    let new_path = ensure_available(new)?;
+
+    // Found by name, so look them up before the name changes
+    let descendants = manifest::find_descendants(old)?;
+
+    fs::rename(&old_path, &new_path)
+        .with_context(|| format!("Failed to move {} to {}", old_path.display(), new_path.display()))?;
+
+    let mut undo = Undo::default();
+    match follow_rename(old, new, &new_path, &descendants, &mut undo) {
+        Ok((updated_children, was_active)) => Ok(Rename {
+            profile_path: new_path,
+            updated_children,
+            was_active,
+        }),
+        Err(e) => {
+            undo.restore();
+            let _ = fs::rename(&new_path, &old_path);
+            // Put back what the old name generated, as far as it still can be
+            for name in std::iter::once(old).chain(descendants.iter().map(String::as_str)) {
+                let _ = regenerate(name);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Update everything that refers to a profile just moved from `old` to `new`
+///
+/// Returns the children whose `extends` was updated and whether the profile
+/// was active. Files are recorded in `undo` before they are changed.
+fn follow_rename(
+    old: &str,
+    new: &str,
+    new_path: &Path,
+    descendants: &[String],
+    undo: &mut Undo,
+) -> Result<(Vec<String>, bool)> {
+    let manifest_path = new_path.join("profile.toml");
+    undo.record(&manifest_path)?;
+    set_profile_keys(&manifest_path, &[("name", new.into())])?;
+
+    let mut updated_children = Vec::new();
+    for child in descendants {
+        let manifest_path = manifest::get_manifest_path(child);
+        let extends = manifest::load_unresolved(child)
+            .ok()
+            .and_then(|m| m.profile.extends);
+        if extends.as_deref() == Some(old) {
+            undo.record(&manifest_path)?;
+            set_profile_keys(&manifest_path, &[("extends", new.into())])?;
+            updated_children.push(child.clone());
+        }
+    }
+
+    let was_active = config::load_config()?.active_profile.as_deref() == Some(old);
+    if was_active {
+        undo.record(&profile::get_config_path()?)?;
+        config::update_active_profile(new).context("Failed to update active profile in config")?;
+        undo.record(&zdotdir::get_zshenv_path()?)?;
+        zdotdir::set_active_profile(new_path).context("Failed to update ZDOTDIR in ~/.zshenv")?;
+    }
+
+    // Generated files name the profile, and children inherit from it
+    for name in std::iter::once(new).chain(descendants.iter().map(String::as_str)) {
+        regenerate(name)?;
+    }
+
+    Ok((updated_children, was_active))
+}
+
+/// Files changed by a rename, with what they held before
+#[derive(Default)]
+struct Undo {
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl Undo {
+    /// Remember `path` as it is now, before it is changed
+    fn record(&mut self, path: &Path) -> Result<()> {
+        let contents = match fs::read(path) {
+            Ok(contents) => Some(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        self.files.push((path.to_path_buf(), contents));
+        Ok(())
+    }
+
+    /// Put every recorded file back, most recent change first
+    fn restore(self) {
+        for (path, contents) in self.files.into_iter().rev() {
+            let _ = match contents {
+                Some(contents) => fs::write(&path, contents),
+                None => fs::remove_file(&path),
+            };
+        }
+    }
+}
+
+/// Directory for a new profile, failing if one with that name exists
+fn ensure_available(name: &str) -> Result<PathBuf> {
+    let path = profile::get_profiles_dir()?.join(name);
+    ensure!(
+        !path.exists(),
+        "✗ Error: Profile '{name}' already exists\n  → Choose a different name, or delete it first with 'zprof delete {name}'"
+    );
+    Ok(path)
+}
+
+/// Set keys in the `[profile]` table, keeping the rest of the file as written
+fn set_profile_keys(manifest_path: &Path, keys: &[(&str, toml::Value)]) -> Result<()> {
+    let mut document = ManifestDocument::load(manifest_path)?;
+    let mut table = document.table()?;
+    let profile = table
+        .get_mut("profile")
+        .and_then(toml::Value::as_table_mut)
+        .with_context(|| format!("No [profile] section in {}", manifest_path.display()))?;
+    for (key, value) in keys {
+        profile.insert(key.to_string(), value.clone());
+    }
+    document.set_table(&table);
+    document.save(manifest_path)
+}
+
+fn regenerate(profile_name: &str) -> Result<()> {
+    let manifest = manifest::load_and_validate(profile_name)
+        .with_context(|| format!("Failed to load manifest for '{profile_name}'"))?;
+    generator::write_generated_files(profile_name, &manifest)
+        .with_context(|| format!("Failed to regenerate shell configuration for '{profile_name}'"))
+}
@@ -0,0 +1,561 @@
+//! Compiler-style diagnostics for profile.toml
+//!
+//! Parse errors and validation problems are collected as `Diagnostic`s that
+//! name the offending field, point at where it is written in the file, and
+//! suggest a fix for near-miss spellings. `render` prints them the way rustc
+//! does; the GUI receives them serialized so it can highlight fields.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use toml::de::{DeTable, DeValue};
+use toml::Spanned;
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The manifest can't be used until this is fixed
+    Error,
+    /// The manifest works, but probably not as intended
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// Where in profile.toml a diagnostic points
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, in characters
+    pub column: usize,
+    /// Number of characters to underline on that line
+    pub length: usize,
+    /// The full source line, for rendering
+    #[serde(skip)]
+    pub text: String,
+}
+
+impl Location {
+    /// Location of a byte range in `source`
+    ///
+    /// Ranges spanning several lines are cut off at the end of the first one.
+    pub fn from_span(source: &str, span: Range<usize>) -> Self {
+        let start = span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..].find('\n').map_or(source.len(), |i| start + i);
+        let text = source[line_start..line_end].trim_end_matches('\r');
+        let end = span.end.clamp(start, line_end);
+
+        Location {
+            line: source[..start].matches('\n').count() + 1,
+            column: source[line_start..start].chars().count() + 1,
+            length: source[start..end].chars().count().max(1),
+            text: text.to_string(),
+        }
+    }
+}
+
+/// A single problem found in a manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Lint code, e.g. `L001`, for problems `zprof lint` knows how to explain or fix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// One-line summary of the problem
+    pub message: String,
+    /// Further lines: hints and examples
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub details: String,
+    /// Path of the offending field, e.g. `plugins.enabled[2]` or `when.os."linux"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// Replacement value for a near-miss spelling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    /// Where the field is written, if it is in this file at all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    /// Parent profile the field is inherited from through `extends`, when
+    /// it is written there instead of in this file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherited_from: Option<String>,
+    /// The offending key or value as written, used to find it in the file
+    #[serde(skip)]
+    found: Option<String>,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, field: Option<String>, text: &str) -> Self {
+        let text = text.strip_prefix("Validation error: ").unwrap_or(text);
+        let (message, details) = text.split_once('\n').map_or((text, ""), |(first, rest)| (first, rest));
+
+        Diagnostic {
+            severity,
+            code: None,
+            message: message.trim_end().to_string(),
+            details: if details.is_empty() { String::new() } else { format!("\n{details}") },
+            field,
+            suggestion: None,
+            location: None,
+            inherited_from: None,
+            found: None,
+        }
+    }
+
+    /// An error about `field`; the first line of `text` is the summary
+    pub fn error(field: impl Into<String>, text: impl AsRef<str>) -> Self {
+        Self::new(Severity::Error, Some(field.into()), text.as_ref())
+    }
+
+    /// A warning about `field`; the first line of `text` is the summary
+    pub fn warning(field: impl Into<String>, text: impl AsRef<str>) -> Self {
+        Self::new(Severity::Warning, Some(field.into()), text.as_ref())
+    }
+
+    /// Tag the diagnostic with a lint code
+    pub fn with_code(mut self, code: &str) -> Self {
+        self.code = Some(code.to_string());
+        self
+    }
+
+    /// Record the offending key or value, so the exact spot can be underlined
+    pub fn with_found(mut self, found: impl Into<String>) -> Self {
+        self.found = Some(found.into());
+        self
+    }
+
+    /// Suggest a replacement value
+    pub fn with_suggestion(mut self, suggestion: Option<&str>) -> Self {
+        self.suggestion = suggestion.map(str::to_string);
+        self
+    }
+
+    /// Nest this diagnostic under `prefix`, e.g. for a `[when]` overlay
+    pub fn within(mut self, prefix: &str, note: &str) -> Self {
+        self.field = Some(match self.field {
+            Some(field) => format!("{prefix}.{field}"),
+            None => prefix.to_string(),
+        });
+        self.details.push_str(note);
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// The offending key or value, if known
+    pub fn found(&self) -> Option<&str> {
+        self.found.as_deref()
+    }
+
+    /// Turn a TOML syntax or deserialization error into a diagnostic
+    ///
+    /// Syntax errors carry their own span. Deserialization errors only name
+    /// the table they happened in, so the offending key or value is picked
+    /// out of the message for `locate` to search for.
+    pub fn from_toml_error(error: &toml::de::Error, source: &str) -> Self {
+        let mut diagnostic = Self::new(Severity::Error, None, error.message().trim_end());
+        if let Some(span) = error.span() {
+            diagnostic.location = Some(Location::from_span(source, span));
+            return diagnostic;
+        }
+
+        let rendered = error.to_string();
+        diagnostic.field = rendered
+            .trim_end()
+            .lines()
+            .last()
+            .and_then(|line| line.strip_prefix("in `")?.strip_suffix('`'))
+            .map(str::to_string);
+
+        let message = diagnostic.message.clone();
+        if ["unknown variant", "unknown field", "invalid type", "invalid value"]
+            .iter()
+            .any(|prefix| message.starts_with(prefix))
+        {
+            let (found, expected) = message.split_once(", expected").unwrap_or((&message, ""));
+            let candidates = backticked(expected);
+            diagnostic.found = backticked(found).first().map(|s| s.to_string());
+            diagnostic.suggestion = diagnostic
+                .found
+                .as_deref()
+                .and_then(|found| did_you_mean(found, candidates))
+                .map(str::to_string);
+        }
+        diagnostic
+    }
+
+    /// Render the diagnostic with a caret-underlined source snippet
+    pub fn render(&self, file: &Path) -> String {
+        let mut out = match &self.code {
+            Some(code) => format!("{}[{code}]: {}\n", self.severity, self.message),
+            None => format!("{}: {}\n", self.severity, self.message),
+        };
+        let gutter = match &self.location {
+            Some(location) => {
+                let gutter = " ".repeat(location.line.to_string().len());
+                let indent: String = location
+                    .text
+                    .chars()
+                    .take(location.column - 1)
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+                out.push_str(&format!("{gutter}--> {}:{}:{}\n", file.display(), location.line, location.column));
+                out.push_str(&format!("{gutter} |\n"));
+                out.push_str(&format!("{} | {}\n", location.line, location.text));
+                out.push_str(&format!("{gutter} | {indent}{}\n", "^".repeat(location.length)));
+                gutter
+            }
+            None => {
+                out.push_str(&format!(" --> {}\n", file.display()));
+                String::new()
+            }
+        };
+        if let Some(parent) = &self.inherited_from {
+            out.push_str(&format!("{gutter} = note: inherited from profile '{parent}'; fix it there\n"));
+        }
+        if let Some(suggestion) = &self.suggestion {
+            out.push_str(&format!("{gutter} = help: did you mean '{suggestion}'?\n"));
+        }
+        if !self.details.is_empty() {
+            out.push_str(self.details.trim_start_matches('\n'));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// The summary and details, with the suggestion placed before any example
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (notes, example) = match self.details.find("\n\nExample:") {
+            Some(idx) => self.details.split_at(idx),
+            None => (self.details.as_str(), ""),
+        };
+        write!(f, "{}{notes}", self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n  → Did you mean '{suggestion}'?")?;
+        }
+        write!(f, "{example}")
+    }
+}
+
+/// Fill in the location of each diagnostic that names a field in `source`
+///
+/// Diagnostics about fields that aren't written in this file (for example
+/// ones inherited through `extends`) are left without a location.
+pub fn locate(diagnostics: &mut [Diagnostic], source: &str) {
+    let (root, _) = DeTable::parse_recoverable(source);
+    let span = root.span();
+    let root = DeValue::Table(root.into_inner());
+
+    for diagnostic in diagnostics.iter_mut().filter(|d| d.location.is_none()) {
+        let Some(field) = &diagnostic.field else { continue };
+        let segments = parse_field(field);
+        let found = diagnostic.found.as_deref();
+
+        let mut hit = find(&root, span.clone(), None, &segments, found, source);
+        // Problems inside a [when] overlay may come from the base manifest;
+        // point at the overlay's header instead
+        if hit.is_none() && segments.len() > 3 && field.starts_with("when.") {
+            hit = find(&root, span.clone(), None, &segments[..3], None, source);
+        }
+        // A value that isn't written here (e.g. one inherited through
+        // `extends`) has no location in this file
+        if let Some((span, true)) = hit {
+            diagnostic.location = Some(Location::from_span(source, span));
+        }
+    }
+}
+
+/// Name `profile` as the origin of each diagnostic that is written in its `source`
+///
+/// Used for the parents of a profile that uses `extends`, on the diagnostics
+/// `locate` found no place for in the profile's own file.
+pub fn attribute(diagnostics: &mut [Diagnostic], source: &str, profile: &str) {
+    for diagnostic in diagnostics
+        .iter_mut()
+        .filter(|d| d.location.is_none() && d.inherited_from.is_none() && d.field.is_some())
+    {
+        let mut probe = [diagnostic.clone()];
+        locate(&mut probe, source);
+        if probe[0].location.is_some() {
+            diagnostic.inherited_from = Some(profile.to_string());
+        }
+    }
+}
+
+/// The candidate closest to `found`, if any is close enough to be a typo
+pub fn did_you_mean<'a>(found: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let found = found.to_lowercase();
+    let limit = (found.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&found, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance counting insertions, deletions, substitutions and
+/// transpositions of adjacent characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>(); a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Quote a table key for use in a field path, if it isn't a bare key
+pub fn field_key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        key.to_string()
+    } else {
+        format!("{key:?}")
+    }
+}
+
+/// Every `backticked` token in a serde error message
+fn backticked(text: &str) -> Vec<&str> {
+    text.split('`').skip(1).step_by(2).collect()
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Split a field path like `when.host."a.b".plugins.enabled[0]` into segments
+fn parse_field(field: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut chars = field.chars().peekable();
+    let mut key = String::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        break;
+                    }
+                    key.push(c);
+                }
+                segments.push(Segment::Key(std::mem::take(&mut key)));
+            }
+            '[' => {
+                if !key.is_empty() {
+                    segments.push(Segment::Key(std::mem::take(&mut key)));
+                }
+                let index: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                if let Ok(index) = index.parse() {
+                    segments.push(Segment::Index(index));
+                }
+            }
+            '.' => {
+                if !key.is_empty() {
+                    segments.push(Segment::Key(std::mem::take(&mut key)));
+                }
+            }
+            c => key.push(c),
+        }
+    }
+    if !key.is_empty() {
+        segments.push(Segment::Key(key));
+    }
+    segments
+}
+
+/// Span of the field at `segments` below `value`, and whether `found` was
+/// matched exactly
+///
+/// Keys applied to an array of tables are tried on every element. Once the
+/// field is reached, `found` is searched for among its keys and values.
+fn find(
+    value: &DeValue,
+    span: Range<usize>,
+    key: Option<&Spanned<std::borrow::Cow<'_, str>>>,
+    segments: &[Segment],
+    found: Option<&str>,
+    source: &str,
+) -> Option<(Range<usize>, bool)> {
+    let Some((first, rest)) = segments.split_first() else {
+        let Some(found) = found else { return Some((span, true)) };
+        if key.is_some_and(|key| key.get_ref() == found) {
+            return key.map(|key| (key.span(), true));
+        }
+        return Some(search(value, span.clone(), found, source).map_or((span, false), |span| (span, true)));
+    };
+
+    match (first, value) {
+        (Segment::Key(name), DeValue::Table(table)) => {
+            let (key, value) = table.iter().find(|(key, _)| key.get_ref() == name)?;
+            find(value.get_ref(), value.span(), Some(key), rest, found, source)
+        }
+        (Segment::Index(index), DeValue::Array(items)) => {
+            // Indices of a resolved manifest may not match the file; trust the value
+            if let (Some(found), true) = (found, rest.is_empty()) {
+                if let Some(span) = search(value, span.clone(), found, source) {
+                    return Some((span, true));
+                }
+            }
+            let item = items.get(*index)?;
+            find(item.get_ref(), item.span(), None, rest, found, source)
+        }
+        (Segment::Key(_), DeValue::Array(items)) => {
+            let hits: Vec<_> = items
+                .iter()
+                .filter_map(|item| find(item.get_ref(), item.span(), None, segments, found, source))
+                .collect();
+            hits.iter().find(|(_, exact)| *exact).or(hits.first()).cloned()
+        }
+        _ => None,
+    }
+}
+
+/// Span of the first key or value below `value` that is written as `found`
+fn search(value: &DeValue, span: Range<usize>, found: &str, source: &str) -> Option<Range<usize>> {
+    match value {
+        DeValue::Table(table) => table.iter().find_map(|(key, value)| {
+            if key.get_ref() == found {
+                Some(key.span())
+            } else {
+                search(value.get_ref(), value.span(), found, source)
+            }
+        }),
+        DeValue::Array(items) => items
+            .iter()
+            .find_map(|item| search(item.get_ref(), item.span(), found, source)),
+        DeValue::String(text) if text == found => Some(span),
+        _ => source.get(span.clone()).filter(|raw| raw.trim() == found).map(|_| span),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "[profile]\nname = \"work\"\nframework = \"oh-my-zhs\"\n\n[plugins]\nenabled = [\"git\", \"zsh-autosugestions\"]\n";
+
+    #[test]
+    fn test_did_you_mean() {
+        let frameworks = ["oh-my-zsh", "zimfw", "prezto", "zinit", "zap"];
+        assert_eq!(did_you_mean("oh-my-zhs", frameworks), Some("oh-my-zsh"));
+        assert_eq!(did_you_mean("Zimfw", frameworks), Some("zimfw"));
+        assert_eq!(did_you_mean("antigen", frameworks), None);
+    }
+
+    #[test]
+    fn test_parse_field() {
+        assert_eq!(
+            parse_field("when.host.\"a.b\".plugins.enabled[1]"),
+            vec![
+                Segment::Key("when".into()),
+                Segment::Key("host".into()),
+                Segment::Key("a.b".into()),
+                Segment::Key("plugins".into()),
+                Segment::Key("enabled".into()),
+                Segment::Index(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_locate_value_and_array_element() {
+        let mut diagnostics = vec![
+            Diagnostic::error("profile.framework", "bad framework").with_found("oh-my-zhs"),
+            Diagnostic::warning("plugins.enabled[3]", "unknown plugin").with_found("zsh-autosugestions"),
+            Diagnostic::error("aliases.ll", "not in this file"),
+        ];
+        locate(&mut diagnostics, SOURCE);
+
+        let framework = diagnostics[0].location.as_ref().unwrap();
+        assert_eq!((framework.line, framework.column, framework.length), (3, 13, 11));
+        let plugin = diagnostics[1].location.as_ref().unwrap();
+        assert_eq!((plugin.line, plugin.column), (6, 19));
+        assert!(diagnostics[2].location.is_none());
+    }
+
+    #[test]
+    fn test_inherited_value_is_attributed_to_parent() {
+        let child = "[profile]\nname = \"child\"\nextends = \"work\"\n\n[plugins]\nenabled = [\"docker\", \"fzf\"]\n";
+        let mut diagnostics =
+            vec![Diagnostic::warning("plugins.enabled[1]", "unknown plugin").with_found("zsh-autosugestions")];
+
+        // The merged index points at 'fzf' in the child, which isn't the value
+        locate(&mut diagnostics, child);
+        assert!(diagnostics[0].location.is_none());
+
+        attribute(&mut diagnostics, SOURCE, "work");
+        assert_eq!(diagnostics[0].inherited_from.as_deref(), Some("work"));
+        assert!(diagnostics[0].location.is_none());
+    }
+
+    #[test]
+    fn test_from_deserialize_error_finds_value() {
+        let source = "[profile]\nname = \"work\"\nframework = \"zap\"\nprompt_mode = \"prompt_engin\"\n";
+        // Deserializing from a table (as the loader does) loses the span
+        let table: toml::Table = toml::from_str(source).unwrap();
+        let error = <crate::core::manifest::Manifest as serde::Deserialize>::deserialize(toml::Value::Table(table))
+            .map_err(|e| Diagnostic::from_toml_error(&e, source))
+            .unwrap_err();
+
+        let mut diagnostics = vec![error];
+        locate(&mut diagnostics, source);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.field.as_deref(), Some("profile"));
+        assert_eq!(diagnostic.suggestion.as_deref(), Some("prompt_engine"));
+        assert_eq!(diagnostic.location.as_ref().unwrap().line, 4);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut diagnostics = vec![Diagnostic::error(
+            "profile.framework",
+            "Validation error: profile.framework must be one of: oh-my-zsh, zap\n  Found: 'oh-my-zhs'",
+        )
+        .with_found("oh-my-zhs")
+        .with_suggestion(Some("oh-my-zsh"))];
+        locate(&mut diagnostics, SOURCE);
+
+        let rendered = diagnostics[0].render(Path::new("profile.toml"));
+        assert_eq!(
+            rendered,
+            "error: profile.framework must be one of: oh-my-zsh, zap\n \
+             --> profile.toml:3:13\n  |\n\
+             3 | framework = \"oh-my-zhs\"\n  |             ^^^^^^^^^^^\n  \
+             = help: did you mean 'oh-my-zsh'?\n  Found: 'oh-my-zhs'\n"
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "profile.framework must be one of: oh-my-zsh, zap\n  Found: 'oh-my-zhs'\n  → Did you mean 'oh-my-zsh'?"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Semantic differences between two manifests
+//!
+//! Compares what a profile does rather than how profile.toml is written:
+//! framework, prompt, plugins and environment. Used by `zprof diff` to show
+//! what switching to or importing a profile would change.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::core::manifest::{Manifest, PromptMode};
+
+/// One difference between two manifests, from the first to the second
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Framework { from: String, to: String },
+    Prompt { from: String, to: String },
+    PluginAdded(String),
+    PluginRemoved(String),
+    EnvAdded { key: String, value: String },
+    EnvRemoved { key: String, value: String },
+    EnvChanged { key: String, from: String, to: String },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Framework { from, to } => write!(f, "framework: {from} → {to}"),
+            Change::Prompt { from, to } => write!(f, "prompt: {from} → {to}"),
+            Change::PluginAdded(name) => write!(f, "+ {name}"),
+            Change::PluginRemoved(name) => write!(f, "- {name}"),
+            Change::EnvAdded { key, value } => write!(f, "+ {key}={value}"),
+            Change::EnvRemoved { key, value } => write!(f, "- {key}={value}"),
+            Change::EnvChanged { key, from, to } => write!(f, "~ {key}: {from} → {to}"),
+        }
+    }
+}
+
+impl Change {
+    /// True for plugin additions and removals
+    pub fn is_plugin(&self) -> bool {
+        matches!(self, Change::PluginAdded(_) | Change::PluginRemoved(_))
+    }
+
+    /// True for environment variable changes
+    pub fn is_env(&self) -> bool {
+        matches!(
+            self,
+            Change::EnvAdded { .. } | Change::EnvRemoved { .. } | Change::EnvChanged { .. }
+        )
+    }
+}
+
+/// Describe a prompt setup, e.g. "prompt engine 'starship'"
+pub fn describe_prompt(prompt_mode: &PromptMode) -> String {
+    match prompt_mode {
+        PromptMode::PromptEngine { engine } => format!("prompt engine '{engine}'"),
+        PromptMode::FrameworkTheme { theme } if theme.is_empty() => "framework default theme".to_string(),
+        PromptMode::FrameworkTheme { theme } => format!("framework theme '{theme}'"),
+    }
+}
+
+/// Everything that differs from `from` to `to`
+///
+/// Plugins are compared by name, in `to`'s load order for additions and
+/// `from`'s for removals; environment variables are sorted by name. Secret
+/// variables are compared by source and never resolved.
+pub fn diff_manifests(from: &Manifest, to: &Manifest) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    if from.profile.framework != to.profile.framework {
+        changes.push(Change::Framework {
+            from: from.profile.framework.clone(),
+            to: to.profile.framework.clone(),
+        });
+    }
+
+    if from.profile.prompt_mode != to.profile.prompt_mode {
+        changes.push(Change::Prompt {
+            from: describe_prompt(&from.profile.prompt_mode),
+            to: describe_prompt(&to.profile.prompt_mode),
+        });
+    }
+
+    let from_plugins = from.plugins.names();
+    let to_plugins = to.plugins.names();
+    changes.extend(
+        to_plugins
+            .iter()
+            .filter(|name| !from_plugins.contains(name))
+            .map(|name| Change::PluginAdded(name.clone())),
+    );
+    changes.extend(
+        from_plugins
+            .iter()
+            .filter(|name| !to_plugins.contains(name))
+            .map(|name| Change::PluginRemoved(name.clone())),
+    );
+
+    let keys: BTreeSet<&String> = from.env.keys().chain(to.env.keys()).collect();
+    for key in keys {
+        let change = match (from.env.get(key), to.env.get(key)) {
+            (None, Some(value)) => Change::EnvAdded {
+                key: key.clone(),
+                value: value.to_string(),
+            },
+            (Some(value), None) => Change::EnvRemoved {
+                key: key.clone(),
+                value: value.to_string(),
+            },
+            (Some(old), Some(new)) if old != new => Change::EnvChanged {
+                key: key.clone(),
+                from: old.to_string(),
+                to: new.to_string(),
+            },
+            _ => continue,
+        };
+        changes.push(change);
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::manifest::parse_manifest;
+
+    fn manifest(framework: &str, prompt: &str, plugins: &str, env: &str) -> Manifest {
+        parse_manifest(&format!(
+            "[profile]\nname = \"p\"\nframework = \"{framework}\"\n{prompt}\n\n[plugins]\nenabled = [{plugins}]\n\n[env]\n{env}\n"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_identical_manifests_have_no_changes() {
+        let a = manifest("zap", "prompt_mode = \"framework_theme\"\nframework_theme = \"\"", "\"git\"", "A = \"1\"");
+        assert!(diff_manifests(&a, &a.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_each_kind_of_change() {
+        let a = manifest(
+            "oh-my-zsh",
+            "prompt_mode = \"framework_theme\"\nframework_theme = \"robbyrussell\"",
+            "\"git\", \"docker\"",
+            "EDITOR = \"vim\"\nOLD = \"1\"",
+        );
+        let b = manifest(
+            "zimfw",
+            "prompt_mode = \"prompt_engine\"\nprompt_engine = \"starship\"",
+            "\"git\", \"fzf\"",
+            "EDITOR = \"nvim\"\nNEW = \"2\"",
+        );
+
+        let changes = diff_manifests(&a, &b);
+        assert_eq!(
+            changes,
+            vec![
+                Change::Framework {
+                    from: "oh-my-zsh".to_string(),
+                    to: "zimfw".to_string()
+                },
+                Change::Prompt {
+                    from: "framework theme 'robbyrussell'".to_string(),
+                    to: "prompt engine 'starship'".to_string()
+                },
+                Change::PluginAdded("fzf".to_string()),
+                Change::PluginRemoved("docker".to_string()),
+                Change::EnvChanged {
+                    key: "EDITOR".to_string(),
+                    from: "vim".to_string(),
+                    to: "nvim".to_string()
+                },
+                Change::EnvAdded {
+                    key: "NEW".to_string(),
+                    value: "2".to_string()
+                },
+                Change::EnvRemoved {
+                    key: "OLD".to_string(),
+                    value: "1".to_string()
+                },
+            ]
+        );
+        assert_eq!(changes[4].to_string(), "~ EDITOR: vim → nvim");
+    }
+}
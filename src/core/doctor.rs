@@ -0,0 +1,428 @@
+//! End-to-end health checks behind `zprof doctor`
+//!
+//! Each check looks at one thing a working setup depends on: the managed
+//! section of `~/.zshenv`, config.toml, every profile's manifest and
+//! framework checkout, the shared history file and Nerd Fonts. A check
+//! passes, warns or fails; the ones that found something zprof can put right
+//! without touching user data carry a `Repair`, which `--fix` applies.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::manifest::{self, PromptMode};
+use crate::core::{config, filesystem, profile};
+use crate::fonts::{detect_nerd_fonts, nerd_fonts};
+use crate::frameworks::{installer, FrameworkType};
+use crate::prompts::engine::PromptEngine;
+use crate::shell::{generator, zdotdir};
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// A repair that can't lose user data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Repair {
+    /// Rewrite the managed section of `~/.zshenv` to activate a profile
+    WriteZshenv { profile: String },
+    /// Set `active_profile` in config.toml
+    SetActiveProfile { profile: String },
+    /// Regenerate a profile's shell files from its manifest
+    Regenerate { profile: String },
+    /// Clone a profile's framework where it is missing or only a placeholder
+    InstallFramework { profile: String, framework: String },
+    /// Remove empty oh-my-zsh custom plugin directories
+    RemoveEmptyDirs { paths: Vec<PathBuf> },
+    /// Create the shared history file and its directory
+    CreateHistory,
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Repair::WriteZshenv { profile } => write!(f, "rewrite ~/.zshenv to activate '{profile}'"),
+            Repair::SetActiveProfile { profile } => write!(f, "set the active profile in config.toml to '{profile}'"),
+            Repair::Regenerate { profile } => write!(f, "regenerate shell files for '{profile}'"),
+            Repair::InstallFramework { profile, framework } => write!(f, "install {framework} for '{profile}'"),
+            Repair::RemoveEmptyDirs { paths } => write!(f, "remove {} empty plugin director(ies)", paths.len()),
+            Repair::CreateHistory => write!(f, "create the shared history file"),
+        }
+    }
+}
+
+impl Repair {
+    pub fn apply(&self) -> Result<()> {
+        match self {
+            Repair::WriteZshenv { profile } => {
+                zdotdir::set_active_profile(&profile::get_profiles_dir()?.join(profile))
+            }
+            Repair::SetActiveProfile { profile } => config::update_active_profile(profile),
+            Repair::Regenerate { profile } => {
+                let manifest = manifest::load_and_validate(profile)?;
+                generator::write_generated_files(profile, &manifest)
+            }
+            Repair::InstallFramework { profile, framework } => {
+                let framework = FrameworkType::from_name(framework)
+                    .with_context(|| format!("Unsupported framework: {framework}"))?;
+                let profile_path = profile::get_profiles_dir()?.join(profile);
+                let dir = installer::framework_dir(&framework, &profile_path);
+                if dir.exists() {
+                    fs::remove_dir_all(&dir)
+                        .with_context(|| format!("Failed to remove placeholder {}", dir.display()))?;
+                }
+                installer::install_framework(&framework, &profile_path)
+            }
+            Repair::RemoveEmptyDirs { paths } => {
+                for path in paths {
+                    fs::remove_dir(path).with_context(|| format!("Failed to remove {}", path.display()))?;
+                }
+                Ok(())
+            }
+            Repair::CreateHistory => {
+                let history = history_path()?;
+                if let Some(parent) = history.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("Failed to create {}", parent.display()))?;
+                }
+                fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&history)
+                    .with_context(|| format!("Failed to create {}", history.display()))?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The result of one check
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// What was checked, e.g. `~/.zshenv` or `profile 'work': framework`
+    pub name: String,
+    pub status: Status,
+    pub message: String,
+    /// What to do about a warning or failure `--fix` can't repair
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repair: Option<Repair>,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, status: Status, message: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            status,
+            message: message.into(),
+            hint: None,
+            repair: None,
+        }
+    }
+
+    fn pass(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(name, Status::Pass, message)
+    }
+
+    fn warn(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(name, Status::Warn, message)
+    }
+
+    fn fail(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(name, Status::Fail, message)
+    }
+
+    fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    fn with_repair(mut self, repair: Option<Repair>) -> Self {
+        self.repair = repair;
+        self
+    }
+}
+
+/// Run every check, in the order they are reported
+pub fn run_checks() -> Result<Vec<Check>> {
+    let profiles_dir = profile::get_profiles_dir()?;
+    if !profiles_dir.exists() {
+        return Ok(vec![Check::fail("zprof", "~/.zsh-profiles/profiles does not exist")
+            .with_hint("Run 'zprof init' to set up zprof")]);
+    }
+    let profiles = profile::list_available_profiles()?;
+
+    // The profile ~/.zshenv activates, if it points at one
+    let zdotdir = zdotdir::managed_zdotdir()?;
+    let zshenv_profile = zdotdir
+        .as_deref()
+        .filter(|path| path.parent() == Some(profiles_dir.as_path()) && path.is_dir())
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().to_string());
+
+    let mut checks = Vec::new();
+    let active = match config::load_config() {
+        Ok(config) => {
+            checks.push(check_config(config.active_profile.as_deref(), zshenv_profile.as_deref(), &profiles));
+            config.active_profile.filter(|name| profiles.contains(name))
+        }
+        Err(e) => {
+            checks.push(
+                Check::fail("config.toml", format!("{e:#}"))
+                    .with_hint("Fix or remove ~/.zsh-profiles/config.toml, then run 'zprof use <profile>'"),
+            );
+            None
+        }
+    };
+    checks.push(check_zshenv(zdotdir.as_deref(), zshenv_profile.as_deref(), active.as_deref()));
+
+    let mut engines: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for name in &profiles {
+        let profile_path = profiles_dir.join(name);
+        let manifest = match manifest::load_and_validate(name) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                let summary = format!("{e:#}");
+                let summary = summary.lines().next().unwrap_or_default().to_string();
+                checks.push(
+                    Check::fail(format!("profile '{name}'"), format!("manifest is invalid: {summary}"))
+                        .with_hint(format!("Run 'zprof lint {name}' for details")),
+                );
+                continue;
+            }
+        };
+
+        checks.push(if profile_path.join(".zshrc").exists() {
+            Check::pass(format!("profile '{name}'"), "manifest is valid")
+        } else {
+            Check::warn(format!("profile '{name}'"), "manifest is valid but .zshrc has not been generated")
+                .with_repair(Some(Repair::Regenerate { profile: name.clone() }))
+        });
+
+        if let Some(framework) = FrameworkType::from_name(&manifest.profile.framework) {
+            checks.push(check_framework(name, &framework, &profile_path));
+            if framework == FrameworkType::OhMyZsh {
+                checks.push(check_plugin_dirs(name, &profile_path)?);
+            }
+        }
+
+        if let PromptMode::PromptEngine { engine } = &manifest.profile.prompt_mode {
+            engines.entry(engine.to_lowercase()).or_default().push(name.clone());
+        }
+    }
+
+    checks.push(check_history()?);
+    checks.push(check_fonts(&engines));
+
+    Ok(checks)
+}
+
+/// config.toml names an existing profile, the same one ~/.zshenv activates
+fn check_config(active: Option<&str>, zshenv_profile: Option<&str>, profiles: &[String]) -> Check {
+    let switch_to = zshenv_profile.map(|profile| Repair::SetActiveProfile { profile: profile.to_string() });
+    match active {
+        None => Check::warn("config.toml", "no active profile")
+            .with_hint("Run 'zprof use <profile>' to activate one")
+            .with_repair(switch_to),
+        Some(name) if !profiles.iter().any(|p| p == name) => {
+            Check::fail("config.toml", format!("active profile '{name}' does not exist"))
+                .with_hint("Run 'zprof use <profile>' to activate another profile")
+                .with_repair(switch_to)
+        }
+        Some(name) => match zshenv_profile {
+            Some(other) if other != name => Check::fail(
+                "config.toml",
+                format!("active profile is '{name}', but ~/.zshenv activates '{other}'"),
+            )
+            .with_repair(Some(Repair::WriteZshenv { profile: name.to_string() })),
+            _ => Check::pass("config.toml", format!("active profile is '{name}'")),
+        },
+    }
+}
+
+/// ~/.zshenv has the managed section, pointing at the active profile
+fn check_zshenv(zdotdir: Option<&Path>, zshenv_profile: Option<&str>, active: Option<&str>) -> Check {
+    let rewrite = active.map(|profile| Repair::WriteZshenv { profile: profile.to_string() });
+    match (zdotdir, zshenv_profile) {
+        (None, _) => Check::fail("~/.zshenv", "no zprof-managed ZDOTDIR export")
+            .with_hint("Run 'zprof use <profile>' to activate a profile")
+            .with_repair(rewrite),
+        (Some(path), None) => Check::fail(
+            "~/.zshenv",
+            format!("ZDOTDIR points at {}, which is not a profile", path.display()),
+        )
+        .with_hint("Run 'zprof use <profile>' to activate a profile")
+        .with_repair(rewrite),
+        (Some(_), Some(name)) if active.is_some_and(|active| active != name) => {
+            // Reported, with its repair, by the config.toml check
+            Check::warn("~/.zshenv", format!("activates '{name}', not the active profile"))
+        }
+        (Some(_), Some(name)) => Check::pass("~/.zshenv", format!("activates '{name}'")),
+    }
+}
+
+/// The framework is a real git checkout, not missing or an empty placeholder
+fn check_framework(profile_name: &str, framework: &FrameworkType, profile_path: &Path) -> Check {
+    let name = format!("profile '{profile_name}': framework");
+    let framework_name = framework.name();
+    let dir = installer::framework_dir(framework, profile_path);
+    let reinstall = Some(Repair::InstallFramework {
+        profile: profile_name.to_string(),
+        framework: framework_name.to_string(),
+    });
+
+    if dir.join(".git").exists() {
+        Check::pass(name, format!("{framework_name} is installed"))
+    } else if !dir.exists() {
+        Check::fail(name, format!("{framework_name} is not installed at {}", dir.display())).with_repair(reinstall)
+    } else if is_placeholder(&dir) {
+        Check::fail(name, format!("{} is an empty placeholder, not a git checkout of {framework_name}", dir.display()))
+            .with_repair(reinstall)
+    } else {
+        Check::fail(name, format!("{} is not a git checkout of {framework_name}", dir.display()))
+            .with_hint(format!("Move it aside and run 'zprof doctor --fix' to reinstall {framework_name}"))
+    }
+}
+
+/// No oh-my-zsh custom plugin directory is empty
+///
+/// oh-my-zsh prefers `custom/plugins/<name>` over its bundled plugin of the
+/// same name, so an empty directory there stops the plugin from loading.
+fn check_plugin_dirs(profile_name: &str, profile_path: &Path) -> Result<Check> {
+    let name = format!("profile '{profile_name}': plugins");
+    let plugins_dir = profile_path.join(".oh-my-zsh").join("custom").join("plugins");
+    if !plugins_dir.is_dir() {
+        return Ok(Check::pass(name, "no custom plugin directories"));
+    }
+
+    let mut empty = Vec::new();
+    for entry in fs::read_dir(&plugins_dir).with_context(|| format!("Failed to read {}", plugins_dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() && fs::read_dir(&path)?.next().is_none() {
+            empty.push(path);
+        }
+    }
+    empty.sort();
+
+    if empty.is_empty() {
+        return Ok(Check::pass(name, "custom plugin directories are not empty"));
+    }
+    let names: Vec<String> = empty
+        .iter()
+        .filter_map(|path| path.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .collect();
+    Ok(Check::fail(
+        name,
+        format!("empty custom plugin directories stop these plugins from loading: {}", names.join(", ")),
+    )
+    .with_repair(Some(Repair::RemoveEmptyDirs { paths: empty })))
+}
+
+/// The shared history file can be appended to
+fn check_history() -> Result<Check> {
+    let history = history_path()?;
+    let name = "shared history";
+
+    if !history.exists() {
+        let parent_exists = history.parent().is_some_and(Path::is_dir);
+        let check = if parent_exists {
+            Check::warn(name, format!("{} does not exist yet", history.display()))
+        } else {
+            Check::fail(name, format!("{} does not exist", history.parent().unwrap_or(&history).display()))
+        };
+        return Ok(check.with_repair(Some(Repair::CreateHistory)));
+    }
+
+    Ok(match fs::OpenOptions::new().append(true).open(&history) {
+        Ok(_) => Check::pass(name, format!("{} is writable", history.display())),
+        Err(e) => Check::fail(name, format!("{} is not writable: {e}", history.display()))
+            .with_hint(format!("Check the owner and permissions of {}", history.display())),
+    })
+}
+
+/// A Nerd Font is installed if any profile's prompt engine needs one
+fn check_fonts(engines: &BTreeMap<String, Vec<String>>) -> Check {
+    let name = "nerd fonts";
+    let needing: Vec<(PromptEngine, &Vec<String>)> = engines
+        .iter()
+        .filter_map(|(engine, profiles)| Some((PromptEngine::from_name(engine)?, profiles)))
+        .filter(|(engine, _)| engine.requires_nerd_font())
+        .collect();
+    let Some((engine, profiles)) = needing.first() else {
+        return Check::pass(name, "no profile uses a prompt engine that needs a Nerd Font");
+    };
+
+    let detected = detect_nerd_fonts();
+    if detected.is_installed() {
+        return Check::pass(name, format!("{} Nerd Font file(s) installed", detected.count()));
+    }
+
+    let font = nerd_fonts::get_fonts_for_engine(engine)
+        .first()
+        .map_or("a Nerd Font".to_string(), |font| font.name.to_string());
+    Check::warn(
+        name,
+        format!(
+            "'{}' uses {}, which needs a Nerd Font, but none is installed",
+            profiles.join("', '"),
+            engine.name()
+        ),
+    )
+    .with_hint(format!("Install {font} from https://www.nerdfonts.com/ and select it in your terminal"))
+}
+
+fn history_path() -> Result<PathBuf> {
+    Ok(filesystem::get_zprof_dir()?.join("shared").join(".zsh_history"))
+}
+
+/// True if `dir` holds nothing but (possibly nested) empty directories
+fn is_placeholder(dir: &Path) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else { return false };
+    entries.flatten().all(|entry| {
+        let path = entry.path();
+        path.is_dir() && !path.is_symlink() && is_placeholder(&path)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_placeholder() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join(".zim");
+        fs::create_dir_all(dir.join("modules").join("git")).unwrap();
+        assert!(is_placeholder(&dir));
+
+        fs::write(dir.join("modules").join("git").join("init.zsh"), "").unwrap();
+        assert!(!is_placeholder(&dir));
+    }
+
+    #[test]
+    fn test_check_config_reports_disagreement_with_zshenv() {
+        let profiles = vec!["work".to_string(), "home".to_string()];
+
+        let check = check_config(Some("work"), Some("home"), &profiles);
+        assert_eq!(check.status, Status::Fail);
+        assert_eq!(check.repair, Some(Repair::WriteZshenv { profile: "work".to_string() }));
+
+        let check = check_config(Some("gone"), Some("home"), &profiles);
+        assert_eq!(check.status, Status::Fail);
+        assert_eq!(check.repair, Some(Repair::SetActiveProfile { profile: "home".to_string() }));
+
+        assert_eq!(check_config(Some("work"), Some("work"), &profiles).status, Status::Pass);
+    }
+}
@@ -0,0 +1,512 @@
+//! Format-preserving edits of profile.toml
+//!
+//! profile.toml is meant to be hand-edited, so a rewrite must not throw away
+//! the user's comments, key order or blank lines. `ManifestDocument` keeps the
+//! parsed `toml_edit` document and applies changes by syncing it against the
+//! table the caller wants: keys and array items that didn't change keep their
+//! formatting, new keys are appended, and removed ones take only their own
+//! lines with them.
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::path::Path;
+use toml_edit::{Array, ArrayOfTables, DocumentMut, InlineTable, Item, Table, Value};
+
+use crate::core::manifest::{self, Manifest};
+
+/// A profile.toml with its original formatting
+#[derive(Debug, Clone)]
+pub struct ManifestDocument {
+    /// `#:schema` directive and the blank lines after it, kept verbatim
+    preamble: String,
+    doc: DocumentMut,
+}
+
+impl ManifestDocument {
+    /// Parse profile.toml content
+    pub fn parse(content: &str) -> Result<Self> {
+        let (preamble, body) = split_preamble(content);
+        let doc = body
+            .parse::<DocumentMut>()
+            .map_err(|e| anyhow::anyhow!("Failed to parse TOML: {}", e.message()))?;
+        Ok(Self {
+            preamble: preamble.to_string(),
+            doc,
+        })
+    }
+
+    /// Read and parse a profile.toml
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read profile.toml at {path:?}"))?;
+        Self::parse(&content)
+    }
+
+    /// Write the document back, refusing anything that wouldn't load
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = self.to_string();
+        manifest::parse_manifest(&content).context("Edited manifest does not parse")?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write manifest to {}", path.display()))
+    }
+
+    /// The document's values as a plain table, for `migrate_table`, lint fixes
+    /// and the like
+    pub fn table(&self) -> Result<toml::Table> {
+        manifest::parse_manifest_table(&self.doc.to_string())
+    }
+
+    /// Make the document hold exactly the values in `table`
+    ///
+    /// Only keys whose values differ are touched, so comments and layout
+    /// around everything else survive.
+    pub fn set_table(&mut self, table: &toml::Table) {
+        let root = self.doc.as_table_mut();
+        let had_values = root.iter().any(|(_, item)| item.is_value());
+        sync_table(root, table);
+
+        if !had_values && root.iter().any(|(_, item)| item.is_value()) {
+            // New top-level keys go above the first section; keep a blank
+            // line between them
+            let first = root
+                .iter_mut()
+                .filter_map(|(_, item)| item.as_table_mut())
+                .filter(|t| !t.is_implicit())
+                .min_by_key(|t| t.position());
+            if let Some(first) = first {
+                let prefix = first.decor().prefix().and_then(|p| p.as_str()).unwrap_or_default();
+                if !prefix.starts_with('\n') {
+                    let prefix = format!("\n{prefix}");
+                    first.decor_mut().set_prefix(prefix);
+                }
+            }
+        }
+    }
+
+    /// Make the document hold `manifest`, as `Manifest::to_toml_string` would
+    /// write it
+    pub fn set_manifest(&mut self, manifest: &Manifest) -> Result<()> {
+        let value = toml::Value::try_from(manifest).context("Failed to serialize manifest to TOML format")?;
+        let toml::Value::Table(table) = value else {
+            anyhow::bail!("Manifest did not serialize to a TOML table");
+        };
+        self.set_table(&table);
+        Ok(())
+    }
+}
+
+impl fmt::Display for ManifestDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.preamble, self.doc)
+    }
+}
+
+/// Split off a leading `#:schema` directive and the blank lines after it
+///
+/// toml_edit would attach the directive to whichever key or section comes
+/// first, and lose it if that one is removed or a top-level key is added
+/// above it.
+fn split_preamble(content: &str) -> (&str, &str) {
+    if !content.starts_with("#:schema") {
+        return ("", content);
+    }
+    let mut end = content.find('\n').map_or(content.len(), |i| i + 1);
+    while content[end..].starts_with('\n') || content[end..].starts_with("\r\n") {
+        end += if content[end..].starts_with('\n') { 1 } else { 2 };
+    }
+    content.split_at(end)
+}
+
+fn sync_table(table: &mut Table, target: &toml::Table) {
+    table.retain(|key, _| target.contains_key(key));
+    for (key, value) in target {
+        match table.get_mut(key) {
+            Some(item) => sync_item(item, value),
+            // An empty section means the same as a missing one
+            None if is_empty_table(value) => {}
+            None => {
+                table.insert(key, new_item(value));
+            }
+        }
+    }
+}
+
+fn sync_item(item: &mut Item, target: &toml::Value) {
+    if item_to_plain(item).as_ref() == Some(target) {
+        return;
+    }
+    match (item, target) {
+        (Item::Table(table), toml::Value::Table(target)) => sync_table(table, target),
+        (Item::ArrayOfTables(tables), toml::Value::Array(target)) if is_array_of_tables(target) => {
+            sync_array_of_tables(tables, target)
+        }
+        (Item::Value(value), _) => sync_value(value, target),
+        (item, _) => *item = new_item(target),
+    }
+}
+
+/// Sync `[[x]]` tables by position: edits land in place, extra tables are
+/// dropped from the end and new ones appended
+fn sync_array_of_tables(tables: &mut ArrayOfTables, target: &[toml::Value]) {
+    while tables.len() > target.len() {
+        tables.remove(tables.len() - 1);
+    }
+    for (index, value) in target.iter().enumerate() {
+        let toml::Value::Table(value) = value else {
+            continue;
+        };
+        match tables.get_mut(index) {
+            Some(table) => sync_table(table, value),
+            None => tables.push(new_table(value)),
+        }
+    }
+}
+
+fn sync_value(value: &mut Value, target: &toml::Value) {
+    if value_to_plain(value) == *target {
+        return;
+    }
+    match (&mut *value, target) {
+        (Value::InlineTable(table), toml::Value::Table(target)) => {
+            table.retain(|key, _| target.contains_key(key));
+            for (key, item) in target {
+                match table.get_mut(key) {
+                    Some(existing) => sync_value(existing, item),
+                    None => {
+                        table.insert(key, new_value(item));
+                    }
+                }
+            }
+        }
+        (Value::Array(array), toml::Value::Array(target)) => sync_array(array, target),
+        _ => {
+            // Keep the spacing and any trailing comment around the old value
+            let decor = value.decor().clone();
+            *value = new_value(target);
+            *value.decor_mut() = decor;
+        }
+    }
+}
+
+/// One array element, with the comment that follows it on its line
+struct Element {
+    value: Value,
+    comment: String,
+}
+
+/// Sync an inline array, keeping unchanged elements with their comments
+///
+/// A comment written after an element's comma is stored by toml_edit as the
+/// start of the next element's prefix (or of the array's trailing text), so
+/// each element is paired with that comment before the array is rebuilt.
+fn sync_array(array: &mut Array, target: &[toml::Value]) {
+    if array.is_empty() {
+        *array = target.iter().map(new_value).collect();
+        return;
+    }
+
+    let values: Vec<Value> = array.iter().cloned().collect();
+    let trailing = array.trailing().as_str().unwrap_or_default().to_string();
+    let (open_comment, _) = split_line_comment(prefix_of(&values[0]));
+    let open_comment = open_comment.to_string();
+
+    let mut old: Vec<Option<Element>> = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let next = values.get(i + 1).map_or(trailing.as_str(), prefix_of);
+            let mut value = value.clone();
+            let own = split_line_comment(prefix_of(&value)).1.to_string();
+            value.decor_mut().set_prefix(own);
+            Some(Element {
+                value,
+                comment: split_line_comment(next).0.to_string(),
+            })
+        })
+        .collect();
+    let mut template = old.last().and_then(Option::as_ref).map(|e| e.value.decor().clone());
+    if let (1, Some(decor)) = (old.len(), template.as_mut()) {
+        // Nothing separates the first element from `[` on a one-line array,
+        // but a new one follows a comma
+        if !prefix_of(&values[0]).contains('\n') {
+            decor.set_prefix(" ");
+        }
+    }
+
+    let mut elements = Vec::new();
+    let mut cursor = 0;
+    for (i, item) in target.iter().enumerate() {
+        let found = (cursor..old.len()).find(|&j| {
+            old[j].as_ref().is_some_and(|e| value_to_plain(&e.value) == *item)
+        });
+        if let Some(j) = found {
+            elements.push(old[j].take().expect("unused element"));
+            cursor = j + 1;
+            continue;
+        }
+
+        // An element that isn't wanted any more, in the spot a new one takes,
+        // was changed rather than removed
+        let replaced = old.get(cursor).and_then(Option::as_ref).is_some_and(|e| {
+            let plain = value_to_plain(&e.value);
+            !target[i..].contains(&plain)
+        });
+        if replaced {
+            let mut element = old[cursor].take().expect("unused element");
+            sync_value(&mut element.value, item);
+            elements.push(element);
+            cursor += 1;
+            continue;
+        }
+
+        let mut value = new_value(item);
+        if let Some(decor) = &template {
+            *value.decor_mut() = decor.clone();
+        }
+        elements.push(Element {
+            value,
+            comment: String::new(),
+        });
+    }
+
+    let trailing_comma = array.trailing_comma();
+    let (_, trailing_rest) = split_line_comment(&trailing);
+    let mut rebuilt = Array::new();
+    let mut comment = open_comment;
+    for mut element in elements {
+        let own = prefix_of(&element.value).to_owned();
+        element.value.decor_mut().set_prefix(format!("{comment}{own}"));
+        rebuilt.push_formatted(element.value);
+        comment = element.comment;
+    }
+    rebuilt.set_trailing(format!("{comment}{trailing_rest}"));
+    rebuilt.set_trailing_comma(trailing_comma && !rebuilt.is_empty());
+    *rebuilt.decor_mut() = array.decor().clone();
+    *array = rebuilt;
+}
+
+fn prefix_of(value: &Value) -> &str {
+    value.decor().prefix().and_then(|p| p.as_str()).unwrap_or_default()
+}
+
+/// Split whitespace before an array element into the part still on the
+/// previous element's line and the element's own leading lines
+fn split_line_comment(prefix: &str) -> (&str, &str) {
+    match prefix.find('\n') {
+        Some(i) => prefix.split_at(i),
+        None => ("", prefix),
+    }
+}
+
+fn is_empty_table(value: &toml::Value) -> bool {
+    value.as_table().is_some_and(toml::Table::is_empty)
+}
+
+fn is_array_of_tables(values: &[toml::Value]) -> bool {
+    !values.is_empty() && values.iter().all(toml::Value::is_table)
+}
+
+/// A new item for a key that isn't in the document yet
+fn new_item(value: &toml::Value) -> Item {
+    match value {
+        toml::Value::Table(table) => Item::Table(new_table(table)),
+        toml::Value::Array(values) if is_array_of_tables(values) => Item::ArrayOfTables(
+            values
+                .iter()
+                .filter_map(toml::Value::as_table)
+                .map(new_table)
+                .collect(),
+        ),
+        _ => Item::Value(new_value(value)),
+    }
+}
+
+/// A new `[section]`, laid out the way `toml::to_string_pretty` would
+fn new_table(table: &toml::Table) -> Table {
+    let mut section = Table::new();
+    for (key, value) in table {
+        if !is_empty_table(value) {
+            section.insert(key, new_item(value));
+        }
+    }
+    // Sections that only hold other sections don't need a header of their own
+    section.set_implicit(!section.is_empty() && section.iter().all(|(_, item)| !item.is_value()));
+    section
+}
+
+fn new_value(value: &toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => s.as_str().into(),
+        toml::Value::Integer(i) => (*i).into(),
+        toml::Value::Float(f) => (*f).into(),
+        toml::Value::Boolean(b) => (*b).into(),
+        toml::Value::Datetime(dt) => dt
+            .to_string()
+            .parse::<toml_edit::Datetime>()
+            .map(Value::from)
+            .unwrap_or_else(|_| dt.to_string().into()),
+        toml::Value::Array(values) => Value::Array(values.iter().map(new_value).collect()),
+        toml::Value::Table(table) => Value::InlineTable(
+            table
+                .iter()
+                .map(|(key, value)| (key.as_str(), new_value(value)))
+                .collect::<InlineTable>(),
+        ),
+    }
+}
+
+fn item_to_plain(item: &Item) -> Option<toml::Value> {
+    match item {
+        Item::None => None,
+        Item::Value(value) => Some(value_to_plain(value)),
+        Item::Table(table) => Some(toml::Value::Table(
+            table
+                .iter()
+                .filter_map(|(key, item)| Some((key.to_string(), item_to_plain(item)?)))
+                .collect(),
+        )),
+        Item::ArrayOfTables(tables) => Some(toml::Value::Array(
+            tables
+                .iter()
+                .map(|table| item_to_plain(&Item::Table(table.clone())).expect("a table"))
+                .collect(),
+        )),
+    }
+}
+
+fn value_to_plain(value: &Value) -> toml::Value {
+    match value {
+        Value::String(s) => toml::Value::String(s.value().clone()),
+        Value::Integer(i) => toml::Value::Integer(*i.value()),
+        Value::Float(f) => toml::Value::Float(*f.value()),
+        Value::Boolean(b) => toml::Value::Boolean(*b.value()),
+        Value::Datetime(dt) => dt
+            .value()
+            .to_string()
+            .parse()
+            .map(toml::Value::Datetime)
+            .unwrap_or_else(|_| toml::Value::String(dt.value().to_string())),
+        Value::Array(array) => toml::Value::Array(array.iter().map(value_to_plain).collect()),
+        Value::InlineTable(table) => toml::Value::Table(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), value_to_plain(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMENTED: &str = r#"#:schema ../../schema.json
+
+# Work laptop setup
+[profile]
+name = "work"   # keep in sync with the directory
+framework = "oh-my-zsh"
+prompt_mode = "framework_theme"
+framework_theme = "robbyrussell"
+created = "2025-01-01T00:00:00Z"
+modified = "2025-01-01T00:00:00Z"
+
+[plugins]
+enabled = [
+    "git",      # always
+    "docker",   # for the day job
+    "kubectl",
+]
+
+# Things every shell needs
+[env]
+EDITOR = "vim"
+"#;
+
+    #[test]
+    fn test_unchanged_table_keeps_content_verbatim() {
+        let mut doc = ManifestDocument::parse(COMMENTED).unwrap();
+        let table = doc.table().unwrap();
+        doc.set_table(&table);
+        assert_eq!(doc.to_string(), COMMENTED);
+    }
+
+    fn set_plugins(doc: &mut ManifestDocument, plugins: &[&str]) {
+        let mut table = doc.table().unwrap();
+        table["plugins"]["enabled"] = toml::Value::Array(plugins.iter().map(|p| (*p).into()).collect());
+        doc.set_table(&table);
+    }
+
+    #[test]
+    fn test_removed_plugin_keeps_other_comments() {
+        let mut doc = ManifestDocument::parse(COMMENTED).unwrap();
+        set_plugins(&mut doc, &["git", "kubectl"]);
+
+        let content = doc.to_string();
+        assert!(content.contains("    \"git\",      # always\n    \"kubectl\",\n]"));
+        assert!(!content.contains("day job"));
+        assert!(content.contains("# Work laptop setup\n[profile]"));
+        assert!(content.contains("# Things every shell needs\n[env]"));
+        assert!(content.starts_with("#:schema ../../schema.json\n\n"));
+    }
+
+    #[test]
+    fn test_added_plugin_and_env_follow_existing_layout() {
+        let mut doc = ManifestDocument::parse(COMMENTED).unwrap();
+        set_plugins(&mut doc, &["git", "docker", "kubectl", "fzf"]);
+        let mut table = doc.table().unwrap();
+        let env = table["env"].as_table_mut().unwrap();
+        env.insert("EDITOR".to_string(), "nvim".into());
+        env.insert("PAGER".to_string(), "less".into());
+        doc.set_table(&table);
+
+        let content = doc.to_string();
+        assert!(content.contains("    \"kubectl\",\n    \"fzf\",\n]"));
+        assert!(content.contains("[env]\nEDITOR = \"nvim\"\nPAGER = \"less\"\n"));
+        assert!(content.contains("name = \"work\"   # keep in sync with the directory"));
+
+        let manifest = manifest::parse_manifest(&content).unwrap();
+        assert_eq!(manifest.plugins.enabled, vec!["git", "docker", "kubectl", "fzf"]);
+    }
+
+    #[test]
+    fn test_added_plugin_on_one_line_array_is_spaced() {
+        let mut doc = ManifestDocument::parse("[plugins]\nenabled = [\"git\"]\n").unwrap();
+        set_plugins(&mut doc, &["git", "docker", "fzf"]);
+
+        assert_eq!(doc.to_string(), "[plugins]\nenabled = [\"git\", \"docker\", \"fzf\"]\n");
+    }
+
+    #[test]
+    fn test_changed_element_keeps_its_comment() {
+        let mut doc = ManifestDocument::parse(COMMENTED).unwrap();
+        set_plugins(&mut doc, &["git", "podman", "kubectl"]);
+
+        assert!(doc.to_string().contains("    \"podman\",   # for the day job\n"));
+    }
+
+    #[test]
+    fn test_new_top_level_key_goes_below_directive() {
+        let mut doc = ManifestDocument::parse(COMMENTED).unwrap();
+        let mut table = doc.table().unwrap();
+        table.insert("schema_version".to_string(), toml::Value::Integer(2));
+        doc.set_table(&table);
+
+        assert!(doc
+            .to_string()
+            .starts_with("#:schema ../../schema.json\n\nschema_version = 2\n\n# Work laptop setup\n[profile]"));
+    }
+
+    #[test]
+    fn test_set_manifest_only_touches_changed_values() {
+        let mut doc = ManifestDocument::parse(COMMENTED).unwrap();
+        let mut manifest = manifest::parse_manifest(COMMENTED).unwrap();
+        manifest.profile.framework = "zimfw".to_string();
+        doc.set_manifest(&manifest).unwrap();
+
+        let content = doc.to_string();
+        assert!(content.contains("framework = \"zimfw\"\n"));
+        assert!(content.contains("\"docker\",   # for the day job"));
+        assert_eq!(manifest::parse_manifest(&content).unwrap(), manifest);
+    }
+}
@@ -0,0 +1,267 @@
+//! Serializable descriptions of profiles and the framework registries
+//!
+//! The GUI receives these over IPC and `--format json|toml` prints them, so
+//! both front ends describe a profile the same way. Fields are only ever
+//! added, never renamed or removed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::manifest::{self, Manifest, PromptMode};
+use crate::core::profile;
+use crate::frameworks::{plugin, theme, FrameworkType, PluginCategory};
+
+/// Profile information for display in profile list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileInfo {
+    /// Profile name (unique identifier)
+    pub name: String,
+    /// Framework name (oh-my-zsh, zimfw, etc.)
+    pub framework: String,
+    /// Prompt mode: "prompt_engine" or "framework_theme"
+    pub prompt_mode: String,
+    /// Prompt engine name (if using prompt_engine mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_engine: Option<String>,
+    /// Framework theme name (if using framework_theme mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub framework_theme: Option<String>,
+    /// Whether this profile is currently active
+    pub active: bool,
+    /// ISO 8601 timestamp when profile was created
+    pub created_at: String,
+    /// Number of enabled plugins
+    pub plugin_count: usize,
+}
+
+impl ProfileInfo {
+    /// Fill in a scanned profile from its manifest
+    ///
+    /// The framework comes from the resolved manifest, so profiles that
+    /// inherit it through `extends` show it too. A manifest that doesn't load
+    /// still gets an entry, with no plugins and the current time as its
+    /// creation date.
+    pub fn load(scanned: profile::ProfileInfo) -> Self {
+        let (framework, created_at, plugin_count, prompt_mode, prompt_engine, framework_theme) =
+            match manifest::load_and_validate(&scanned.name) {
+                Ok(m) => {
+                    let (prompt_mode, engine, theme) = match &m.profile.prompt_mode {
+                        PromptMode::PromptEngine { engine } => ("prompt_engine", Some(engine.clone()), None),
+                        PromptMode::FrameworkTheme { theme } => ("framework_theme", None, Some(theme.clone())),
+                    };
+                    (
+                        m.profile.framework.clone(),
+                        m.profile.created.to_rfc3339(),
+                        m.plugins.names().len(),
+                        prompt_mode.to_string(),
+                        engine,
+                        theme,
+                    )
+                }
+                Err(_) => (
+                    scanned.framework,
+                    chrono::Utc::now().to_rfc3339(),
+                    0,
+                    "framework_theme".to_string(),
+                    None,
+                    None,
+                ),
+            };
+
+        ProfileInfo {
+            name: scanned.name,
+            framework,
+            prompt_mode,
+            prompt_engine,
+            framework_theme,
+            active: scanned.is_active,
+            created_at,
+            plugin_count,
+        }
+    }
+}
+
+/// Full profile details including configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileDetails {
+    /// Profile name
+    pub name: String,
+    /// Profile this one inherits from, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Framework name
+    pub framework: String,
+    /// Prompt mode discriminator
+    pub prompt_mode: PromptModeInfo,
+    /// List of enabled plugins
+    pub plugins: Vec<String>,
+    /// Environment variables
+    pub env_vars: HashMap<String, String>,
+    /// Shell aliases
+    #[serde(default)]
+    pub aliases: AliasesInfo,
+    /// ISO 8601 timestamp when created
+    pub created_at: String,
+    /// ISO 8601 timestamp when last modified
+    pub modified_at: String,
+}
+
+impl From<Manifest> for ProfileDetails {
+    fn from(manifest: Manifest) -> Self {
+        ProfileDetails {
+            name: manifest.profile.name,
+            extends: manifest.profile.extends,
+            framework: manifest.profile.framework,
+            prompt_mode: manifest.profile.prompt_mode.into(),
+            plugins: manifest.plugins.names(),
+            // Secrets are shown by source, never resolved
+            env_vars: manifest.env.into_iter().map(|(key, value)| (key, value.to_string())).collect(),
+            aliases: AliasesInfo {
+                plain: manifest.aliases.plain,
+                global: manifest.aliases.global,
+                suffix: manifest.aliases.suffix,
+            },
+            created_at: manifest.profile.created.to_rfc3339(),
+            modified_at: manifest.profile.modified.to_rfc3339(),
+        }
+    }
+}
+
+/// Prompt mode information for profile details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PromptModeInfo {
+    /// Using a standalone prompt engine
+    PromptEngine {
+        /// Engine name (starship, powerlevel10k, etc.)
+        engine: String,
+    },
+    /// Using framework's built-in theme system
+    FrameworkTheme {
+        /// Theme name (robbyrussell, agnoster, etc.)
+        theme: String,
+    },
+}
+
+impl From<PromptMode> for PromptModeInfo {
+    fn from(mode: PromptMode) -> Self {
+        match mode {
+            PromptMode::PromptEngine { engine } => PromptModeInfo::PromptEngine { engine },
+            PromptMode::FrameworkTheme { theme } => PromptModeInfo::FrameworkTheme { theme },
+        }
+    }
+}
+
+/// Shell aliases for profile details
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AliasesInfo {
+    /// Plain aliases (`alias name=...`)
+    pub plain: HashMap<String, String>,
+    /// Global aliases (`alias -g name=...`)
+    pub global: HashMap<String, String>,
+    /// Suffix aliases keyed by file extension (`alias -s ext=...`)
+    pub suffix: HashMap<String, String>,
+}
+
+/// Framework information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameworkInfo {
+    /// Framework name
+    pub name: String,
+    /// Human-readable description
+    pub description: String,
+    /// Whether this framework supports themes
+    pub supports_themes: bool,
+    /// Whether this framework supports plugins
+    pub supports_plugins: bool,
+}
+
+impl FrameworkInfo {
+    /// Every supported framework
+    pub fn all() -> Vec<Self> {
+        FrameworkType::ALL
+            .iter()
+            .map(|framework| {
+                let (description, supports_themes) = match framework {
+                    FrameworkType::OhMyZsh => ("Community-driven zsh framework with 300+ plugins and 140+ themes", true),
+                    FrameworkType::Zimfw => ("Blazing fast zsh framework focused on speed and simplicity", true),
+                    FrameworkType::Prezto => ("Configuration framework for zsh with sane defaults and modules", true),
+                    FrameworkType::Zinit => ("Flexible and fast zsh plugin manager with turbo mode", false),
+                    FrameworkType::Zap => ("Minimal zsh plugin manager, fast and simple", false),
+                };
+                FrameworkInfo {
+                    name: framework.name().to_string(),
+                    description: description.to_string(),
+                    supports_themes,
+                    supports_plugins: true,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Plugin information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    /// Plugin name
+    pub name: String,
+    /// Description of what the plugin does
+    pub description: String,
+    /// Category (git, docker, utility, etc.)
+    pub category: String,
+    /// Framework this plugin is for
+    pub framework: String,
+}
+
+impl PluginInfo {
+    /// Registry plugins that `framework` can load
+    pub fn for_framework(framework: &FrameworkType) -> Vec<Self> {
+        plugin::get_plugins_for_framework(framework)
+            .into_iter()
+            .map(|p| {
+                let category = match p.category {
+                    PluginCategory::Git => "git",
+                    PluginCategory::Docker => "docker",
+                    PluginCategory::Kubernetes => "kubernetes",
+                    PluginCategory::Language => "language",
+                    PluginCategory::Utility => "utility",
+                };
+                PluginInfo {
+                    name: p.name.to_string(),
+                    description: p.description.to_string(),
+                    category: category.to_string(),
+                    framework: framework.name().to_string(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Theme information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeInfo {
+    /// Theme name
+    pub name: String,
+    /// Description of the theme
+    pub description: String,
+    /// Framework this theme is for
+    pub framework: String,
+    /// Optional URL to preview image
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview_url: Option<String>,
+}
+
+impl ThemeInfo {
+    /// Registry themes that `framework` can load
+    pub fn for_framework(framework: &FrameworkType) -> Vec<Self> {
+        theme::get_themes_for_framework(framework)
+            .into_iter()
+            .map(|t| ThemeInfo {
+                name: t.name.to_string(),
+                description: t.description.to_string(),
+                framework: framework.name().to_string(),
+                preview_url: Some(t.preview.to_string()),
+            })
+            .collect()
+    }
+}
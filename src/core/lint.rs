@@ -0,0 +1,411 @@
+//! Semantic lint of manifests against the plugin, theme and engine registries
+//!
+//! `Manifest::check` makes sure a manifest is well-formed. The rules here go
+//! further and ask whether it will actually work: whether each plugin and
+//! theme exists for the profile's framework, whether plugin dependencies are
+//! enabled, and whether the prompt engine is spelled the way zprof expects.
+//! Every rule has a stable code, and the ones with an unambiguous correction
+//! can be fixed in place by `zprof lint --fix`.
+
+use std::fmt;
+
+use crate::core::diagnostics::{self, Diagnostic};
+use crate::core::manifest::{Manifest, PromptMode};
+use crate::frameworks::{plugin, theme, FrameworkType};
+
+/// Plugin name that looks like a misspelling of a registry plugin
+pub const MISSPELLED_PLUGIN: &str = "L001";
+/// Plugin that can't be loaded by the profile's framework
+pub const UNSUPPORTED_PLUGIN: &str = "L002";
+/// Plugin whose registry dependencies aren't enabled
+pub const MISSING_DEPENDENCY: &str = "L003";
+/// Plugin listed more than once in `plugins.enabled`
+pub const DUPLICATE_PLUGIN: &str = "L004";
+/// Theme name that looks like a misspelling of a registry theme
+pub const MISSPELLED_THEME: &str = "L010";
+/// Theme that the profile's framework can't load
+pub const UNSUPPORTED_THEME: &str = "L011";
+/// Prompt engine zprof doesn't know how to set up
+pub const UNKNOWN_ENGINE: &str = "L020";
+/// Prompt engine written with a short alias instead of its name
+pub const ENGINE_ALIAS: &str = "L021";
+
+/// Short names the generator also accepts for prompt engines, with the
+/// name they stand for
+pub const PROMPT_ENGINE_ALIASES: &[(&str, &str)] = &[("p10k", "powerlevel10k"), ("ohmyposh", "oh-my-posh")];
+
+/// Frameworks that install every plugin from a repository, so a plugin
+/// without one can't be loaded
+pub const REPO_FRAMEWORKS: &[FrameworkType] = &[FrameworkType::Zinit, FrameworkType::Zap];
+
+/// Warning for a plugin name that isn't in the registry but is close to one
+///
+/// Frameworks ship many plugins the registry doesn't list, so names are only
+/// flagged when they look like a typo.
+pub fn misspelled_plugin(field: &str, name: &str) -> Option<Diagnostic> {
+    if plugin::find_plugin(name).is_some() {
+        return None;
+    }
+    let suggestion = diagnostics::did_you_mean(name, plugin::PLUGIN_REGISTRY.iter().map(|p| p.name))?;
+    Some(
+        Diagnostic::warning(field, format!("plugin '{name}' is not in the plugin registry"))
+            .with_code(MISSPELLED_PLUGIN)
+            .with_found(name)
+            .with_suggestion(Some(suggestion)),
+    )
+}
+
+/// Check a resolved manifest against the registries
+///
+/// Misspelled plugins and unknown engines are reported by `Manifest::check`
+/// already, with the codes above; this returns the remaining rules.
+pub fn lint(manifest: &Manifest) -> Vec<Diagnostic> {
+    let mut problems = Vec::new();
+    let Some(framework) = FrameworkType::from_name(&manifest.profile.framework) else {
+        // An unknown framework is a validation error; nothing to check against
+        return problems;
+    };
+    let framework_name = framework.name();
+
+    let plugins = manifest.plugins.resolved();
+    let field_for = |name: &str| match manifest.plugins.enabled.iter().position(|p| p == name) {
+        Some(idx) => format!("plugins.enabled[{idx}]"),
+        None => {
+            let idx = manifest.plugins.entry.iter().position(|e| e.name == name).unwrap_or_default();
+            format!("plugins.entry[{idx}].name")
+        }
+    };
+
+    for (idx, name) in manifest.plugins.enabled.iter().enumerate() {
+        if manifest.plugins.enabled[..idx].contains(name) {
+            problems.push(
+                Diagnostic::warning(
+                    format!("plugins.enabled[{idx}]"),
+                    format!("plugin '{name}' is enabled more than once"),
+                )
+                .with_code(DUPLICATE_PLUGIN)
+                .with_found(name),
+            );
+        }
+    }
+
+    for entry in &plugins {
+        let name = entry.name.as_str();
+        if entry.source.is_some() {
+            // Plugins with their own source are outside the registry's knowledge
+            continue;
+        }
+        let registered = plugin::find_plugin(name);
+
+        if let Some(registered) = registered {
+            if !registered.compatibility.supports_framework(&framework) {
+                problems.push(
+                    Diagnostic::error(
+                        field_for(name),
+                        format!("plugin '{name}' is not available for {framework_name}\n  → Remove it, or add a [[plugins.entry]] with a source to install it from"),
+                    )
+                    .with_code(UNSUPPORTED_PLUGIN)
+                    .with_found(name),
+                );
+                continue;
+            }
+        }
+        if REPO_FRAMEWORKS.contains(&framework) && entry.repo_for(&framework).is_none() {
+            // Misspellings get their own warning; don't pile on
+            if registered.is_none() && misspelled_plugin("", name).is_some() {
+                continue;
+            }
+            problems.push(
+                Diagnostic::error(
+                    field_for(name),
+                    format!("{framework_name} has no repository to install plugin '{name}' from\n  → Add a source for it\n\nExample:\n  [[plugins.entry]]\n  name = \"{name}\"\n  source = \"user/{name}\""),
+                )
+                .with_code(UNSUPPORTED_PLUGIN)
+                .with_found(name),
+            );
+        }
+
+        for dependency in registered.map(|p| p.compatibility.dependencies).unwrap_or_default() {
+            if !plugins.iter().any(|p| p.name == *dependency) {
+                problems.push(
+                    Diagnostic::warning(
+                        field_for(name),
+                        format!("plugin '{name}' depends on '{dependency}', which is not enabled"),
+                    )
+                    .with_code(MISSING_DEPENDENCY)
+                    .with_found(name)
+                    .with_suggestion(Some(dependency)),
+                );
+            }
+        }
+    }
+
+    match &manifest.profile.prompt_mode {
+        PromptMode::FrameworkTheme { theme: name } if !name.is_empty() => {
+            match theme::THEME_REGISTRY.iter().find(|t| t.name == name.as_str()) {
+                Some(registered) if !registered.compatibility.supports_framework(&framework) => {
+                    problems.push(
+                        Diagnostic::error(
+                            "profile.framework_theme",
+                            format!("theme '{name}' is not available for {framework_name}\n  → Run 'zprof available themes' to see themes for each framework"),
+                        )
+                        .with_code(UNSUPPORTED_THEME)
+                        .with_found(name),
+                    );
+                }
+                Some(_) => {}
+                None => {
+                    let candidates = theme::get_themes_for_framework(&framework);
+                    let suggestion = diagnostics::did_you_mean(name, candidates.iter().map(|t| t.name));
+                    if suggestion.is_some() {
+                        problems.push(
+                            Diagnostic::warning(
+                                "profile.framework_theme",
+                                format!("theme '{name}' is not in the theme registry"),
+                            )
+                            .with_code(MISSPELLED_THEME)
+                            .with_found(name)
+                            .with_suggestion(suggestion),
+                        );
+                    } else if framework == FrameworkType::Zap {
+                        problems.push(
+                            Diagnostic::warning(
+                                "profile.framework_theme",
+                                format!("zap has no repository to install theme '{name}' from; it will not load\n  → Use a prompt engine, or a theme from 'zprof available themes'"),
+                            )
+                            .with_code(UNSUPPORTED_THEME)
+                            .with_found(name),
+                        );
+                    }
+                }
+            }
+        }
+        PromptMode::PromptEngine { engine } => {
+            let lower = engine.to_lowercase();
+            if let Some((_, name)) = PROMPT_ENGINE_ALIASES.iter().find(|(alias, _)| *alias == lower) {
+                problems.push(
+                    Diagnostic::warning(
+                        "profile.prompt_engine",
+                        format!("prompt_engine '{engine}' is a short name for '{name}'"),
+                    )
+                    .with_code(ENGINE_ALIAS)
+                    .with_found(engine)
+                    .with_suggestion(Some(name)),
+                );
+            }
+        }
+        PromptMode::FrameworkTheme { .. } => {}
+    }
+
+    problems
+}
+
+/// A safe, mechanical correction for a lint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fix {
+    /// Replace a misspelled plugin name everywhere it is listed
+    RenamePlugin { from: String, to: String },
+    /// Keep only the first occurrence of a plugin in each `enabled` list
+    RemoveDuplicatePlugin(String),
+    /// Add a plugin to `plugins.enabled`
+    EnablePlugin(String),
+    /// Set `framework_theme`
+    SetTheme(String),
+    /// Set `prompt_engine`
+    SetEngine(String),
+}
+
+impl Fix {
+    /// The fix for a diagnostic, if it has one
+    pub fn for_diagnostic(diagnostic: &Diagnostic) -> Option<Fix> {
+        let suggestion = diagnostic.suggestion.clone();
+        let found = diagnostic.found().map(str::to_string);
+
+        match diagnostic.code.as_deref()? {
+            MISSPELLED_PLUGIN => Some(Fix::RenamePlugin { from: found?, to: suggestion? }),
+            DUPLICATE_PLUGIN => Some(Fix::RemoveDuplicatePlugin(found?)),
+            MISSING_DEPENDENCY => Some(Fix::EnablePlugin(suggestion?)),
+            MISSPELLED_THEME => Some(Fix::SetTheme(suggestion?)),
+            UNKNOWN_ENGINE | ENGINE_ALIAS => Some(Fix::SetEngine(suggestion?)),
+            _ => None,
+        }
+    }
+
+    /// Apply the fix to a profile.toml table, returning whether it changed anything
+    ///
+    /// Only settings written in this file are touched; a value inherited
+    /// through `extends` has to be fixed in the profile that sets it.
+    pub fn apply(&self, table: &mut toml::Table) -> bool {
+        match self {
+            Fix::RenamePlugin { from, to } => {
+                let mut changed = false;
+                for plugins in plugins_tables(table) {
+                    if let Some(toml::Value::Array(enabled)) = plugins.get_mut("enabled") {
+                        for value in enabled.iter_mut().filter(|v| v.as_str() == Some(from)) {
+                            *value = to.as_str().into();
+                            changed = true;
+                        }
+                    }
+                    if let Some(toml::Value::Array(entries)) = plugins.get_mut("entry") {
+                        for entry in entries.iter_mut().filter_map(toml::Value::as_table_mut) {
+                            if entry.get("name").and_then(toml::Value::as_str) == Some(from) {
+                                entry.insert("name".to_string(), to.as_str().into());
+                                changed = true;
+                            }
+                        }
+                    }
+                }
+                changed
+            }
+            Fix::RemoveDuplicatePlugin(name) => {
+                let mut changed = false;
+                for plugins in plugins_tables(table) {
+                    if let Some(toml::Value::Array(enabled)) = plugins.get_mut("enabled") {
+                        let mut seen = false;
+                        let before = enabled.len();
+                        enabled.retain(|v| {
+                            if v.as_str() != Some(name) {
+                                return true;
+                            }
+                            !std::mem::replace(&mut seen, true)
+                        });
+                        changed |= enabled.len() != before;
+                    }
+                }
+                changed
+            }
+            Fix::EnablePlugin(name) => {
+                let plugins = table
+                    .entry("plugins")
+                    .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+                let Some(plugins) = plugins.as_table_mut() else { return false };
+                let enabled = plugins
+                    .entry("enabled")
+                    .or_insert_with(|| toml::Value::Array(Vec::new()));
+                let Some(enabled) = enabled.as_array_mut() else { return false };
+                if enabled.iter().any(|v| v.as_str() == Some(name)) {
+                    return false;
+                }
+                enabled.push(name.as_str().into());
+                true
+            }
+            Fix::SetTheme(name) => set_profile_key(table, &["framework_theme", "theme"], name),
+            Fix::SetEngine(name) => set_profile_key(table, &["prompt_engine"], name),
+        }
+    }
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fix::RenamePlugin { from, to } => write!(f, "renamed plugin '{from}' to '{to}'"),
+            Fix::RemoveDuplicatePlugin(name) => write!(f, "removed duplicate plugin '{name}'"),
+            Fix::EnablePlugin(name) => write!(f, "enabled plugin '{name}'"),
+            Fix::SetTheme(name) => write!(f, "set framework_theme to '{name}'"),
+            Fix::SetEngine(name) => write!(f, "set prompt_engine to '{name}'"),
+        }
+    }
+}
+
+/// The `[plugins]` table and those of every `[when]` overlay
+fn plugins_tables(table: &mut toml::Table) -> Vec<&mut toml::Table> {
+    let mut tables = Vec::new();
+    for (key, value) in table.iter_mut() {
+        match (key.as_str(), value) {
+            ("plugins", toml::Value::Table(plugins)) => tables.push(plugins),
+            ("when", toml::Value::Table(when)) => {
+                for (_, kind) in when.iter_mut() {
+                    let Some(kind) = kind.as_table_mut() else { continue };
+                    for (_, overlay) in kind.iter_mut() {
+                        let Some(overlay) = overlay.as_table_mut() else { continue };
+                        if let Some(toml::Value::Table(plugins)) = overlay.get_mut("plugins") {
+                            tables.push(plugins);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    tables
+}
+
+/// Set the first of `keys` that `[profile]` already has to `value`
+fn set_profile_key(table: &mut toml::Table, keys: &[&str], value: &str) -> bool {
+    let Some(toml::Value::Table(profile)) = table.get_mut("profile") else { return false };
+    match keys.iter().find(|key| profile.contains_key(**key)) {
+        Some(key) => {
+            profile.insert(key.to_string(), value.into());
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::manifest::parse_manifest;
+
+    fn codes(source: &str) -> Vec<(String, Option<String>)> {
+        let manifest = parse_manifest(source).unwrap();
+        manifest
+            .check()
+            .into_iter()
+            .chain(lint(&manifest))
+            .filter_map(|d| Some((d.code.clone()?, d.suggestion.clone())))
+            .collect()
+    }
+
+    #[test]
+    fn test_plugin_rules() {
+        let found = codes(
+            "[profile]\nname = \"w\"\nframework = \"zap\"\n\n[plugins]\nenabled = [\"zsh-autosugestions\", \"fzf\", \"fzf\", \"git\", \"my-plugin\"]\n",
+        );
+        assert_eq!(
+            found,
+            vec![
+                (MISSPELLED_PLUGIN.to_string(), Some("zsh-autosuggestions".to_string())),
+                (DUPLICATE_PLUGIN.to_string(), None),
+                // zap can't install 'my-plugin' without a source
+                (UNSUPPORTED_PLUGIN.to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_theme_and_engine_rules() {
+        let theme = codes("[profile]\nname = \"w\"\nframework = \"oh-my-zsh\"\nframework_theme = \"robbyrusell\"\n");
+        assert_eq!(theme, vec![(MISSPELLED_THEME.to_string(), Some("robbyrussell".to_string()))]);
+
+        let unsupported = codes("[profile]\nname = \"w\"\nframework = \"oh-my-zsh\"\nframework_theme = \"zap-prompt\"\n");
+        assert_eq!(unsupported, vec![(UNSUPPORTED_THEME.to_string(), None)]);
+
+        let alias = codes("[profile]\nname = \"w\"\nframework = \"zinit\"\nprompt_mode = \"prompt_engine\"\nprompt_engine = \"p10k\"\n");
+        assert_eq!(alias, vec![(ENGINE_ALIAS.to_string(), Some("powerlevel10k".to_string()))]);
+    }
+
+    #[test]
+    fn test_fixes_apply_to_table() {
+        let mut table: toml::Table = toml::from_str(
+            "[profile]\nname = \"w\"\nframework = \"zinit\"\nprompt_mode = \"prompt_engine\"\nprompt_engine = \"starshp\"\n\n[plugins]\nenabled = [\"zsh-autosugestions\", \"git\", \"git\"]\n\n[when.os.\"linux\".plugins]\nenabled = [\"zsh-autosugestions\"]\n",
+        )
+        .unwrap();
+
+        let fixes = [
+            Fix::RenamePlugin { from: "zsh-autosugestions".into(), to: "zsh-autosuggestions".into() },
+            Fix::RemoveDuplicatePlugin("git".into()),
+            Fix::EnablePlugin("fzf".into()),
+            Fix::SetEngine("starship".into()),
+        ];
+        for fix in &fixes {
+            assert!(fix.apply(&mut table), "{fix}");
+        }
+        assert!(!Fix::SetTheme("pure".into()).apply(&mut table));
+
+        let manifest = parse_manifest(&toml::to_string(&table).unwrap()).unwrap();
+        assert_eq!(manifest.plugins.enabled, vec!["zsh-autosuggestions", "git", "fzf"]);
+        assert_eq!(manifest.when.os["linux"].plugins.enabled, vec!["zsh-autosuggestions"]);
+        assert_eq!(manifest.profile.prompt_mode, PromptMode::PromptEngine { engine: "starship".into() });
+    }
+}
@@ -5,18 +5,27 @@
 
 use anyhow::{bail, Context, Result};
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::path::{Path, PathBuf};
 
-use crate::frameworks::FrameworkInfo;
+use crate::core::conditions::{self, ConditionKind, Machine};
+use crate::core::diagnostics::{self, Diagnostic};
+use crate::core::document::ManifestDocument;
+use crate::core::lint;
+use crate::core::schema;
+use crate::frameworks::{plugin, FrameworkInfo, FrameworkType};
 use crate::presets;
+use crate::prompts::engine::PROMPT_ENGINE_NAMES;
+use crate::shell::{keys, options};
 
 /// Supported zsh frameworks
-const SUPPORTED_FRAMEWORKS: &[&str] = &["oh-my-zsh", "zimfw", "prezto", "zinit", "zap"];
+pub(crate) const SUPPORTED_FRAMEWORKS: &[&str] = &["oh-my-zsh", "zimfw", "prezto", "zinit", "zap"];
 
 /// Prompt mode discriminates between standalone prompt engines and framework themes
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(tag = "prompt_mode", rename_all = "snake_case")]
 pub enum PromptMode {
     /// Use a standalone prompt engine (Starship, Powerlevel10k, etc.)
@@ -40,19 +49,46 @@ impl Default for PromptMode {
 }
 
 /// Profile manifest structure following Pattern 4: TOML Manifest Schema
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct Manifest {
+    /// Version of the profile.toml format this manifest was written in
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
     pub profile: ProfileSection,
     #[serde(default)]
     pub plugins: PluginsSection,
     #[serde(default)]
-    pub env: HashMap<String, String>,
+    pub env: HashMap<String, EnvValue>,
+    #[serde(default, skip_serializing_if = "AliasesSection::is_empty")]
+    pub aliases: AliasesSection,
+    #[serde(default, skip_serializing_if = "OptionsSection::is_empty")]
+    pub options: OptionsSection,
+    #[serde(default, skip_serializing_if = "PathSection::is_empty")]
+    pub path: PathSection,
+    #[serde(default, skip_serializing_if = "HooksSection::is_empty")]
+    pub hooks: HooksSection,
+    #[serde(default, skip_serializing_if = "KeysSection::is_empty")]
+    pub keys: KeysSection,
+    #[serde(default, skip_serializing_if = "CompletionSection::is_empty")]
+    pub completion: CompletionSection,
+    #[serde(default, skip_serializing_if = "WhenSection::is_empty")]
+    pub when: WhenSection,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snippets: Vec<Snippet>,
 }
 
 /// Profile metadata section
-#[derive(Debug, Serialize, Clone, PartialEq)]
+///
+/// Older manifests used a bare `theme` key instead of `prompt_mode`; those are
+/// upgraded by the schema migrations before this is deserialized.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct ProfileSection {
     pub name: String,
+    /// Name of the profile this one inherits from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// May be empty in a profile that inherits its framework through `extends`
+    #[serde(default)]
     pub framework: String,
     #[serde(flatten)]
     pub prompt_mode: PromptMode,
@@ -72,143 +108,539 @@ impl ProfileSection {
     }
 }
 
-// Custom deserializer for backward compatibility with old manifests
-impl<'de> Deserialize<'de> for ProfileSection {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        use serde::de::{self, MapAccess, Visitor};
-        use std::fmt;
-
-        #[derive(Deserialize)]
-        #[serde(field_identifier, rename_all = "snake_case")]
-        enum Field {
-            Name,
-            Framework,
-            Theme,
-            PromptMode,
-            PromptEngine,
-            FrameworkTheme,
-            Created,
-            Modified,
+/// Plugins section
+///
+/// Plugins can be listed by name in `enabled`, or as `[[plugins.entry]]`
+/// tables when they need a custom source, a pinned ref, deferred loading or
+/// framework-specific options. An entry whose name is also in `enabled`
+/// configures that plugin in place; other entries load after `enabled`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct PluginsSection {
+    #[serde(default)]
+    pub enabled: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entry: Vec<PluginEntry>,
+}
+
+impl PluginsSection {
+    /// All plugins in load order, with `[[plugins.entry]]` settings applied
+    pub fn resolved(&self) -> Vec<PluginEntry> {
+        let mut plugins: Vec<PluginEntry> = self
+            .enabled
+            .iter()
+            .map(|name| {
+                self.entry
+                    .iter()
+                    .find(|e| &e.name == name)
+                    .cloned()
+                    .unwrap_or_else(|| PluginEntry::named(name))
+            })
+            .collect();
+
+        plugins.extend(
+            self.entry
+                .iter()
+                .filter(|e| !self.enabled.contains(&e.name))
+                .cloned(),
+        );
+
+        plugins
+    }
+
+    /// Names of all plugins in load order
+    pub fn names(&self) -> Vec<String> {
+        self.resolved().into_iter().map(|p| p.name).collect()
+    }
+
+    /// Returns true if no plugins are configured in either form
+    pub fn is_empty(&self) -> bool {
+        self.enabled.is_empty() && self.entry.is_empty()
+    }
+}
+
+/// A single `[[plugins.entry]]` table
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct PluginEntry {
+    /// Plugin name, as used by the framework and `PLUGIN_REGISTRY`
+    pub name: String,
+    /// Repository to install from (`user/repo` or a git URL), overriding the registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Branch, tag or commit to pin the plugin to
+    #[serde(default, rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    /// Load the plugin after the first prompt instead of at startup
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub defer: bool,
+    /// Extra arguments keyed by framework (zinit ice modifiers, zimfw zmodule flags)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub options: HashMap<String, String>,
+}
+
+impl PluginEntry {
+    /// Create an entry with only a name, as produced by the plain string form
+    pub fn named(name: &str) -> Self {
+        PluginEntry {
+            name: name.to_string(),
+            ..Default::default()
         }
+    }
+
+    /// Repository for this plugin under the given framework
+    ///
+    /// An explicit `source` wins; otherwise the registry's `repo_url_for` is used.
+    pub fn repo_for(&self, framework: &FrameworkType) -> Option<String> {
+        self.source.clone().or_else(|| {
+            plugin::find_plugin(&self.name)
+                .and_then(|p| p.compatibility.repo_url_for(framework))
+                .map(|url| url.to_string())
+        })
+    }
+
+    /// Framework-specific extra arguments, if any
+    pub fn options_for(&self, framework: &FrameworkType) -> Option<&str> {
+        self.options.get(framework.name()).map(|s| s.as_str())
+    }
+}
 
-        struct ProfileSectionVisitor;
+/// Frameworks whose plugin declarations accept extra per-plugin arguments
+const PLUGIN_OPTION_FRAMEWORKS: &[&str] = &["zinit", "zimfw"];
 
-        impl<'de> Visitor<'de> for ProfileSectionVisitor {
-            type Value = ProfileSection;
+/// Frameworks that can load a plugin after the first prompt
+const DEFER_FRAMEWORKS: &[&str] = &["zinit", "zap"];
 
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("struct ProfileSection")
-            }
+/// Frameworks that only pin a plugin zprof clones from its entry's `source`
+const SOURCE_REF_FRAMEWORKS: &[&str] = &["oh-my-zsh", "prezto"];
 
-            fn visit_map<V>(self, mut map: V) -> Result<ProfileSection, V::Error>
-            where
-                V: MapAccess<'de>,
-            {
-                let mut name: Option<String> = None;
-                let mut framework: Option<String> = None;
-                let mut legacy_theme: Option<String> = None;
-                let mut prompt_mode_tag: Option<String> = None;
-                let mut prompt_engine: Option<String> = None;
-                let mut framework_theme: Option<String> = None;
-                let mut created: Option<DateTime<Utc>> = None;
-                let mut modified: Option<DateTime<Utc>> = None;
-
-                while let Some(key) = map.next_key()? {
-                    match key {
-                        Field::Name => {
-                            name = Some(map.next_value()?);
-                        }
-                        Field::Framework => {
-                            framework = Some(map.next_value()?);
-                        }
-                        Field::Theme => {
-                            legacy_theme = Some(map.next_value()?);
-                        }
-                        Field::PromptMode => {
-                            prompt_mode_tag = Some(map.next_value()?);
-                        }
-                        Field::PromptEngine => {
-                            prompt_engine = Some(map.next_value()?);
-                        }
-                        Field::FrameworkTheme => {
-                            framework_theme = Some(map.next_value()?);
-                        }
-                        Field::Created => {
-                            created = Some(map.next_value()?);
-                        }
-                        Field::Modified => {
-                            modified = Some(map.next_value()?);
-                        }
-                    }
-                }
+/// Frameworks for which zprof runs compinit itself, so `fast_init` applies
+const COMPINIT_FRAMEWORKS: &[&str] = &["zinit", "zap"];
 
-                let name = name.ok_or_else(|| de::Error::missing_field("name"))?;
-                let framework = framework.ok_or_else(|| de::Error::missing_field("framework"))?;
-                let created = created.unwrap_or_else(default_timestamp);
-                let modified = modified.unwrap_or_else(default_timestamp);
-
-                // Backward compatibility: if no prompt_mode is specified, use legacy theme field
-                let prompt_mode = if let Some(mode_tag) = prompt_mode_tag {
-                    match mode_tag.as_str() {
-                        "prompt_engine" => {
-                            let engine = prompt_engine
-                                .ok_or_else(|| de::Error::missing_field("prompt_engine"))?;
-                            PromptMode::PromptEngine { engine }
-                        }
-                        "framework_theme" => {
-                            let theme = framework_theme
-                                .ok_or_else(|| de::Error::missing_field("framework_theme"))?;
-                            PromptMode::FrameworkTheme { theme }
-                        }
-                        _ => {
-                            return Err(de::Error::unknown_variant(
-                                &mode_tag,
-                                &["prompt_engine", "framework_theme"],
-                            ))
-                        }
-                    }
-                } else {
-                    // Legacy manifest: use theme field, default to empty string
-                    PromptMode::FrameworkTheme {
-                        theme: legacy_theme.unwrap_or_default(),
-                    }
-                };
+/// Shell aliases section
+///
+/// Plain aliases are written directly under `[aliases]`. Global (`alias -g`)
+/// and suffix (`alias -s`) aliases live in the `[aliases.global]` and
+/// `[aliases.suffix]` sub-tables, so `global` and `suffix` cannot be used as
+/// plain alias names.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct AliasesSection {
+    #[serde(flatten)]
+    pub plain: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub global: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub suffix: HashMap<String, String>,
+}
 
-                Ok(ProfileSection {
-                    name,
-                    framework,
-                    prompt_mode,
-                    created,
-                    modified,
-                })
-            }
+impl AliasesSection {
+    /// Returns true if no aliases of any kind are defined
+    pub fn is_empty(&self) -> bool {
+        self.plain.is_empty() && self.global.is_empty() && self.suffix.is_empty()
+    }
+}
+
+/// Check that an alias name can be used verbatim in an `alias` statement
+fn is_valid_alias_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && !name
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '=' | '\'' | '"' | '`' | '$' | '\\' | ';' | '&' | '|'))
+}
+
+/// Shell options section
+///
+/// Option names follow zsh's rules: case and underscores don't matter, so
+/// `AUTO_CD`, `auto_cd` and `autocd` are all the same option.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct OptionsSection {
+    /// Options turned on with `setopt`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub set: Vec<String>,
+    /// Options turned off with `unsetopt`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unset: Vec<String>,
+}
+
+impl OptionsSection {
+    /// Returns true if no options are set or unset
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty() && self.unset.is_empty()
+    }
+}
+
+/// Value of an `[env]` variable
+///
+/// Either a literal string, or a table naming where a secret lives:
+/// `{ command = "pass show gh/token" }`, `{ file = "~/.secrets/token" }` or
+/// `{ keyring = "github" }`. Secrets are looked up when an interactive shell
+/// starts, so their values never end up in generated files or exported
+/// archives.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum EnvValue {
+    Literal(String),
+    Secret(SecretSource),
+}
+
+impl EnvValue {
+    /// The literal value, or None for a secret
+    pub fn as_literal(&self) -> Option<&str> {
+        match self {
+            EnvValue::Literal(value) => Some(value),
+            EnvValue::Secret(_) => None,
         }
+    }
+}
 
-        deserializer.deserialize_struct(
-            "ProfileSection",
-            &[
-                "name",
-                "framework",
-                "theme",
-                "prompt_mode",
-                "prompt_engine",
-                "framework_theme",
-                "created",
-                "modified",
-            ],
-            ProfileSectionVisitor,
-        )
+impl From<String> for EnvValue {
+    fn from(value: String) -> Self {
+        EnvValue::Literal(value)
     }
 }
 
-/// Plugins section
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
-pub struct PluginsSection {
-    #[serde(default)]
-    pub enabled: Vec<String>,
+impl From<&str> for EnvValue {
+    fn from(value: &str) -> Self {
+        EnvValue::Literal(value.to_string())
+    }
+}
+
+/// Where a secret `[env]` value is read from when the shell starts
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum SecretSource {
+    /// Shell command whose output is the value
+    Command(String),
+    /// File whose contents are the value; may start with `~`, and is
+    /// otherwise relative to the directory of the profile that declares it
+    /// unless absolute
+    File(String),
+    /// Service name in the system keyring (macOS Keychain or libsecret)
+    Keyring(String),
+}
+
+impl fmt::Display for SecretSource {
+    /// Describes the source without resolving it, e.g. `<command: pass show gh/token>`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretSource::Command(command) => write!(f, "<command: {command}>"),
+            SecretSource::File(path) => write!(f, "<file: {path}>"),
+            SecretSource::Keyring(service) => write!(f, "<keyring: {service}>"),
+        }
+    }
+}
+
+impl fmt::Display for EnvValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvValue::Literal(value) => f.write_str(value),
+            EnvValue::Secret(source) => source.fmt(f),
+        }
+    }
+}
+
+/// PATH section
+///
+/// Entries may start with `~` and reference environment variables (`$GOPATH/bin`);
+/// both are expanded by the shell at startup rather than when the profile is
+/// generated, so the same manifest works for every user who imports it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct PathSection {
+    /// Directories placed before the inherited PATH, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prepend: Vec<String>,
+    /// Directories placed after the inherited PATH, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub append: Vec<String>,
+    /// Leave out directories that don't exist when the shell starts
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skip_missing: bool,
+}
+
+impl PathSection {
+    /// Returns true if no directories are added to PATH
+    pub fn is_empty(&self) -> bool {
+        self.prepend.is_empty() && self.append.is_empty()
+    }
+}
+
+/// Hooks section
+///
+/// Each hook is a shell command, or the path of a script: absolute, under
+/// `~/`, or relative to the profile directory (`./hooks/activate.zsh`).
+/// `on_activate` and `on_deactivate` are run by `zprof use`; `on_shell_start`
+/// runs at the end of `.zshrc` in every new shell.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct HooksSection {
+    /// Run after switching to this profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_activate: Option<String>,
+    /// Run before switching away from this profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_deactivate: Option<String>,
+    /// Run by every new shell started with this profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_shell_start: Option<String>,
+    /// Seconds an activate/deactivate hook may run before it is stopped (default 10)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+/// Default time limit for activate/deactivate hooks
+const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 10;
+
+impl HooksSection {
+    /// Returns true if no hooks are configured
+    pub fn is_empty(&self) -> bool {
+        self.on_activate.is_none()
+            && self.on_deactivate.is_none()
+            && self.on_shell_start.is_none()
+            && self.timeout.is_none()
+    }
+
+    /// Time limit for activate/deactivate hooks
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS))
+    }
+
+    /// The configured hooks as `(key, command)` pairs
+    fn entries(&self) -> [(&'static str, Option<&String>); 3] {
+        [
+            ("on_activate", self.on_activate.as_ref()),
+            ("on_deactivate", self.on_deactivate.as_ref()),
+            ("on_shell_start", self.on_shell_start.as_ref()),
+        ]
+    }
+}
+
+/// Returns the script path if a hook refers to a script rather than a command
+///
+/// A hook is a script path when it is a single word that starts with `/`,
+/// `~/` or `./`, or ends in `.sh`/`.zsh`. Anything else is run as a command.
+pub fn hook_script_path(hook: &str) -> Option<&str> {
+    let hook = hook.trim();
+    if hook.is_empty() || hook.contains(char::is_whitespace) {
+        return None;
+    }
+
+    let looks_like_path = hook.starts_with('/')
+        || hook.starts_with("~/")
+        || hook.starts_with("./")
+        || hook.ends_with(".sh")
+        || hook.ends_with(".zsh");
+
+    looks_like_path.then_some(hook)
+}
+
+/// Key bindings section
+///
+/// `mode` picks the base keymap (`bindkey -v` or `bindkey -e`); each
+/// `[[keys.bind]]` table then binds a key sequence to a zle widget, in the
+/// `main` keymap unless `keymap` says otherwise.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct KeysSection {
+    /// Line editing mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<KeyMode>,
+    /// Shell functions to register as widgets with `zle -N` before binding
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub widgets: Vec<String>,
+    /// Key bindings, applied in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bind: Vec<KeyBinding>,
+}
+
+impl KeysSection {
+    /// Returns true if no key settings are configured
+    pub fn is_empty(&self) -> bool {
+        self.mode.is_none() && self.widgets.is_empty() && self.bind.is_empty()
+    }
+}
+
+/// Line editing mode for `[keys]`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyMode {
+    Vi,
+    Emacs,
+}
+
+/// A single `[[keys.bind]]` table
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct KeyBinding {
+    /// Key sequence in `bindkey` notation, e.g. `^R` or `\e[A`
+    pub keys: String,
+    /// Name of the zle widget to run
+    pub widget: String,
+    /// Keymap to bind in (`viins`, `vicmd`, ...); defaults to `main`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keymap: Option<String>,
+}
+
+/// Completion section
+///
+/// Applied the same way for every framework: `fpath` entries are added before
+/// the framework loads (so its `compinit` sees them), and the zstyles are set
+/// after it, overriding the framework's own completion defaults.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct CompletionSection {
+    /// `matcher-list` specs, tried in order (e.g. `m:{a-z}={A-Z}` for case-insensitive matching)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matchers: Vec<String>,
+    /// Pick completions from a menu with the arrow keys
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub menu_select: bool,
+    /// Cache the results of slow completers in `$ZDOTDIR/.zcompcache`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cache: bool,
+    /// Directories of completion functions, searched before the default `fpath`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fpath: Vec<String>,
+    /// Skip compinit's checks (`compinit -C`) while the dump is less than a day old
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fast_init: bool,
+    /// Load completions for zprof itself
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub zprof: bool,
+}
+
+impl CompletionSection {
+    /// Returns true if no completion settings are configured
+    pub fn is_empty(&self) -> bool {
+        self.matchers.is_empty()
+            && !self.menu_select
+            && !self.cache
+            && self.fpath.is_empty()
+            && !self.fast_init
+            && !self.zprof
+    }
+
+    /// Returns true if any `zstyle ':completion:*'` lines need to be generated
+    pub fn has_styles(&self) -> bool {
+        !self.matchers.is_empty() || self.menu_select || self.cache
+    }
+}
+
+/// A `[[snippets]]` entry: custom zsh code placed at a fixed point in the generated files
+///
+/// Exactly one of `code` and `file` is set. `file` is relative to the profile
+/// directory, and is bundled by `zprof export`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Snippet {
+    /// Inline zsh code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Script to source, relative to the profile directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Where in the generated files the snippet goes
+    pub phase: SnippetPhase,
+}
+
+/// Load phase of a snippet, in the order zsh reaches them
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum SnippetPhase {
+    /// End of `.zshenv`, read by every zsh including scripts
+    Zshenv,
+    /// `.zprofile`, read by login shells before `.zshrc`
+    Zprofile,
+    /// In `.zshrc` after PATH is set up, before the framework loads
+    PreFramework,
+    /// After the framework and plugins load, before the prompt engine starts
+    PostPlugins,
+    /// After the prompt is set up
+    PostPrompt,
+}
+
+impl SnippetPhase {
+    /// Name as written in profile.toml
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SnippetPhase::Zshenv => "zshenv",
+            SnippetPhase::Zprofile => "zprofile",
+            SnippetPhase::PreFramework => "pre-framework",
+            SnippetPhase::PostPlugins => "post-plugins",
+            SnippetPhase::PostPrompt => "post-prompt",
+        }
+    }
+}
+
+impl Manifest {
+    /// Snippets for one phase, in the order they are listed
+    pub fn snippets_for(&self, phase: SnippetPhase) -> impl Iterator<Item = &Snippet> {
+        self.snippets.iter().filter(move |snippet| snippet.phase == phase)
+    }
+}
+
+/// Conditional sections
+///
+/// Each `[when.host."name"]`, `[when.os."name"]` and `[when.env."VAR"]` table is
+/// an overlay that is merged onto the profile like a child profile in
+/// `extends`, but only on machines where its condition holds. OS overlays are
+/// applied first, then host, then env.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct WhenSection {
+    /// When the conditions are checked
+    #[serde(default, skip_serializing_if = "WhenResolve::is_default")]
+    pub resolve: WhenResolve,
+    /// Overlays keyed by hostname (full or short, case-insensitive)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub host: BTreeMap<String, Overlay>,
+    /// Overlays keyed by operating system (`linux`, `macos`, ...)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub os: BTreeMap<String, Overlay>,
+    /// Overlays keyed by an environment variable that must be set and non-empty
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, Overlay>,
+}
+
+impl WhenSection {
+    /// Returns true if no conditional overlays are defined
+    pub fn is_empty(&self) -> bool {
+        self.host.is_empty() && self.os.is_empty() && self.env.is_empty()
+    }
+}
+
+/// When `[when]` conditions are checked
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WhenResolve {
+    /// By zprof, when the shell files are generated for this machine
+    #[default]
+    Generate,
+    /// By the shell at startup, with each overlay in an `if` block
+    Shell,
+}
+
+impl WhenResolve {
+    fn is_default(&self) -> bool {
+        *self == WhenResolve::Generate
+    }
+}
+
+/// Sections a `[when.*]` overlay may contain
+///
+/// With `resolve = "shell"` only the sections that can be switched at shell
+/// start (env, aliases, options, path) are allowed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Overlay {
+    #[serde(default, skip_serializing_if = "PluginsSection::is_empty")]
+    pub plugins: PluginsSection,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, EnvValue>,
+    #[serde(default, skip_serializing_if = "AliasesSection::is_empty")]
+    pub aliases: AliasesSection,
+    #[serde(default, skip_serializing_if = "OptionsSection::is_empty")]
+    pub options: OptionsSection,
+    #[serde(default, skip_serializing_if = "PathSection::is_empty")]
+    pub path: PathSection,
+}
+
+/// Default schema version for serde
+fn default_schema_version() -> u32 {
+    CURRENT_SCHEMA_VERSION
 }
 
 /// Default timestamp for serde
@@ -247,11 +679,22 @@ impl Manifest {
                 },
                 created: now,
                 modified: now,
+                extends: None,
             },
             plugins: PluginsSection {
                 enabled: framework_info.plugins.clone(),
+                entry: Vec::new(),
             },
             env: HashMap::new(),
+            aliases: AliasesSection::default(),
+            options: OptionsSection::default(),
+            path: PathSection::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         }
     }
 
@@ -281,6 +724,7 @@ impl Manifest {
                 prompt_mode: preset.config.prompt_mode(),
                 created: now,
                 modified: now,
+                extends: None,
             },
             plugins: PluginsSection {
                 enabled: preset
@@ -289,13 +733,31 @@ impl Manifest {
                     .iter()
                     .map(|s| s.to_string())
                     .collect(),
+                entry: Vec::new(),
             },
             env: preset
                 .config
                 .env_vars
                 .iter()
-                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .map(|(k, v)| (k.to_string(), EnvValue::from(*v)))
                 .collect(),
+            aliases: AliasesSection::default(),
+            options: OptionsSection {
+                set: preset
+                    .config
+                    .shell_options
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                unset: Vec::new(),
+            },
+            path: PathSection::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         }
     }
 
@@ -305,8 +767,19 @@ impl Manifest {
     }
 
     /// Write manifest to profile.toml file
+    ///
+    /// A new file starts with a `#:schema` directive pointing at the JSON
+    /// Schema written by `schema::write_schema_file`, for editor completion.
+    /// An existing file is updated in place through `ManifestDocument`, so
+    /// its comments and layout survive.
     pub fn write_to_file(&self, path: &Path) -> Result<()> {
-        let content = self.to_toml_string()?;
+        if path.exists() {
+            let mut document = ManifestDocument::load(path)?;
+            document.set_manifest(self)?;
+            return document.save(path);
+        }
+
+        let content = format!("{}\n\n{}", schema::SCHEMA_DIRECTIVE, self.to_toml_string()?);
         std::fs::write(path, content)
             .with_context(|| format!("Failed to write manifest to {}", path.display()))?;
         Ok(())
@@ -314,86 +787,734 @@ impl Manifest {
 
     /// Validate the manifest schema and values
     ///
+    /// Fails with the first error found; see `check` for the full list.
+    /// Warnings are logged.
+    pub fn validate(&self) -> Result<()> {
+        let problems = self.check();
+        for warning in problems.iter().filter(|d| !d.is_error()) {
+            log::warn!("{warning}");
+        }
+        match problems.iter().find(|d| d.is_error()) {
+            Some(error) => bail!("Validation error: {error}"),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the manifest schema and values, collecting every problem
+    ///
     /// Checks:
     /// - Profile name is not empty
     /// - Framework is one of the 5 supported values
-    /// - Plugins are non-empty strings
-    /// - Environment variable keys are valid shell identifiers
-    pub fn validate(&self) -> Result<()> {
+    /// - Plugins are non-empty strings and plugin entries are well-formed
+    /// - Prompt engine is one zprof can set up
+    /// - Environment variable keys are valid shell identifiers and secret sources are set
+    /// - Shell options are known zsh options and not both set and unset
+    /// - PATH entries are single directories without command substitution
+    /// - Hooks are non-empty and have a positive timeout
+    /// - Key bindings use well-formed sequences, widget names and keymaps
+    /// - Completion matchers and fpath entries are non-empty
+    /// - Snippets set either code or a file inside the profile directory
+    /// - `[when]` conditions are well-formed and each overlay validates
+    ///
+    /// Plugin names that look like misspellings of a registry plugin are
+    /// reported as warnings.
+    pub fn check(&self) -> Vec<Diagnostic> {
+        let mut problems = Vec::new();
+
         // Validate profile name
         if self.profile.name.trim().is_empty() {
-            bail!("Validation error: profile.name is required and cannot be empty");
+            problems.push(Diagnostic::error(
+                "profile.name",
+                "profile.name is required and cannot be empty",
+            ));
         }
 
         // Validate framework
-        if !SUPPORTED_FRAMEWORKS.contains(&self.profile.framework.as_str()) {
-            bail!(
-                "Validation error: profile.framework must be one of: {}\n  Found: '{}'\n  → Check your profile.toml framework field\n\nExample:\n  [profile]\n  framework = \"oh-my-zsh\"",
-                SUPPORTED_FRAMEWORKS.join(", "),
-                self.profile.framework
+        if self.profile.framework.is_empty() && self.profile.extends.is_none() {
+            problems.push(Diagnostic::error(
+                "profile.framework",
+                "profile.framework is required\n  → Set a framework, or inherit one with profile.extends\n\nExample:\n  [profile]\n  framework = \"oh-my-zsh\"",
+            ));
+        } else if !SUPPORTED_FRAMEWORKS.contains(&self.profile.framework.as_str()) {
+            let framework = &self.profile.framework;
+            problems.push(
+                Diagnostic::error(
+                    "profile.framework",
+                    format!(
+                        "profile.framework must be one of: {}\n  Found: '{framework}'\n  → Check your profile.toml framework field\n\nExample:\n  [profile]\n  framework = \"oh-my-zsh\"",
+                        SUPPORTED_FRAMEWORKS.join(", ")
+                    ),
+                )
+                .with_found(framework)
+                .with_suggestion(diagnostics::did_you_mean(framework, SUPPORTED_FRAMEWORKS.iter().copied())),
             );
         }
 
         // Validate plugins are non-empty strings
         for (idx, plugin) in self.plugins.enabled.iter().enumerate() {
+            let field = format!("plugins.enabled[{idx}]");
             if plugin.trim().is_empty() {
-                bail!(
-                    "Validation error: plugins.enabled[{idx}] cannot be empty string\n\nExample:\n  [plugins]\n  enabled = [\"git\", \"docker\"]"
+                problems.push(Diagnostic::error(
+                    field,
+                    format!("plugins.enabled[{idx}] cannot be empty string\n\nExample:\n  [plugins]\n  enabled = [\"git\", \"docker\"]"),
+                ).with_found(plugin.as_str()));
+            } else if let Some(warning) = lint::misspelled_plugin(&field, plugin) {
+                problems.push(warning);
+            }
+        }
+
+        // Validate [[plugins.entry]] tables
+        for (idx, entry) in self.plugins.entry.iter().enumerate() {
+            if entry.name.trim().is_empty() {
+                problems.push(Diagnostic::error(
+                    format!("plugins.entry[{idx}].name"),
+                    format!("plugins.entry[{idx}].name is required and cannot be empty\n\nExample:\n  [[plugins.entry]]\n  name = \"zsh-autosuggestions\"\n  source = \"zsh-users/zsh-autosuggestions\"\n  ref = \"v0.7.0\""),
+                ));
+                continue;
+            }
+            let name = &entry.name;
+            if self.plugins.entry[..idx].iter().any(|e| &e.name == name) {
+                problems.push(
+                    Diagnostic::error(
+                        format!("plugins.entry[{idx}].name"),
+                        format!("plugin '{name}' has more than one [[plugins.entry]] table\n  → Merge the settings into a single entry"),
+                    )
+                    .with_found(name),
                 );
             }
+            if entry.source.is_none() && !self.plugins.enabled.contains(name) {
+                problems.extend(lint::misspelled_plugin(&format!("plugins.entry[{idx}].name"), name));
+            }
+            for (field, value) in [("source", &entry.source), ("ref", &entry.git_ref)] {
+                if let Some(value) = value {
+                    if value.trim().is_empty()
+                        || value
+                            .chars()
+                            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '$' | ';'))
+                    {
+                        problems.push(Diagnostic::error(
+                            format!("plugins.entry[{idx}].{field}"),
+                            format!("plugins.entry '{name}' has an invalid {field}: '{value}'\n  → {field} cannot be empty or contain whitespace, quotes or '$'"),
+                        ));
+                    }
+                }
+            }
+            for framework in entry.options.keys() {
+                if !PLUGIN_OPTION_FRAMEWORKS.contains(&framework.as_str()) {
+                    problems.push(
+                        Diagnostic::error(
+                            format!("plugins.entry[{idx}].options"),
+                            format!(
+                                "plugins.entry '{name}' has options for '{framework}'\n  → Per-plugin options are supported for: {}\n\nExample:\n  [[plugins.entry]]\n  name = \"fast-syntax-highlighting\"\n  options = {{ zinit = \"wait lucid\" }}",
+                                PLUGIN_OPTION_FRAMEWORKS.join(", ")
+                            ),
+                        )
+                        .with_found(framework),
+                    );
+                }
+            }
+            if let (Some(git_ref), None) = (&entry.git_ref, &entry.source) {
+                if SOURCE_REF_FRAMEWORKS.contains(&self.profile.framework.as_str()) {
+                    problems.push(Diagnostic::warning(
+                        format!("plugins.entry[{idx}].ref"),
+                        format!(
+                            "plugin '{name}' is pinned to '{git_ref}', but {} only pins plugins installed from a source; the ref is ignored\n  → Add source = \"user/repo\" to the entry",
+                            self.profile.framework
+                        ),
+                    ));
+                }
+            }
+            if entry.defer && !DEFER_FRAMEWORKS.contains(&self.profile.framework.as_str()) {
+                problems.push(Diagnostic::warning(
+                    format!("plugins.entry[{idx}].defer"),
+                    format!(
+                        "plugin '{name}' is marked defer, but {} cannot defer plugins; it will load at startup",
+                        self.profile.framework
+                    ),
+                ));
+            }
         }
 
         // Validate prompt_mode fields based on the variant
         match &self.profile.prompt_mode {
             PromptMode::PromptEngine { engine } => {
                 if engine.trim().is_empty() {
-                    bail!(
-                        "Validation error: prompt_engine cannot be empty when prompt_mode is 'prompt_engine'\n\nExample:\n  [profile]\n  prompt_mode = \"prompt_engine\"\n  prompt_engine = \"starship\""
+                    problems.push(Diagnostic::error(
+                        "profile.prompt_engine",
+                        "prompt_engine cannot be empty when prompt_mode is 'prompt_engine'\n\nExample:\n  [profile]\n  prompt_mode = \"prompt_engine\"\n  prompt_engine = \"starship\"",
+                    ));
+                } else if !PROMPT_ENGINE_NAMES.contains(&engine.to_lowercase().as_str())
+                    && !lint::PROMPT_ENGINE_ALIASES.iter().any(|(alias, _)| *alias == engine.to_lowercase())
+                {
+                    problems.push(
+                        Diagnostic::error(
+                            "profile.prompt_engine",
+                            format!(
+                                "prompt_engine '{engine}' is not supported\n  → Use one of: {}",
+                                PROMPT_ENGINE_NAMES.join(", ")
+                            ),
+                        )
+                        .with_code(lint::UNKNOWN_ENGINE)
+                        .with_found(engine)
+                        .with_suggestion(diagnostics::did_you_mean(engine, PROMPT_ENGINE_NAMES.iter().copied())),
                     );
                 }
             }
             PromptMode::FrameworkTheme { theme } => {
                 // Theme can be empty (no theme), but cannot be whitespace-only
                 if !theme.is_empty() && theme.trim().is_empty() {
-                    bail!("Validation error: framework_theme cannot be whitespace-only");
+                    problems.push(Diagnostic::error(
+                        "profile.framework_theme",
+                        "framework_theme cannot be whitespace-only",
+                    ));
                 }
             }
         }
 
-        // Validate environment variable keys are valid shell identifiers
-        for (key, value) in &self.env {
+        // Validate environment variable keys are valid shell identifiers, and secret sources
+        let mut env_keys: Vec<&String> = self.env.keys().collect();
+        env_keys.sort();
+        for key in env_keys {
+            let field = format!("env.{}", diagnostics::field_key(key));
             if key.trim().is_empty() {
-                bail!("Validation error: env variable key cannot be empty");
+                problems.push(Diagnostic::error("env", "env variable key cannot be empty"));
+                continue;
             }
             if !key.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                bail!(
-                    "Validation error: env variable key '{key}' contains invalid characters\n  → Keys must be alphanumeric with underscores only\n\nExample:\n  [env]\n  EDITOR = \"vim\"\n  MY_VAR = \"value\""
+                problems.push(
+                    Diagnostic::error(
+                        &field,
+                        format!("env variable key '{key}' contains invalid characters\n  → Keys must be alphanumeric with underscores only\n\nExample:\n  [env]\n  EDITOR = \"vim\"\n  MY_VAR = \"value\""),
+                    )
+                    .with_found(key),
                 );
+                continue;
             }
-            if value.trim().is_empty() {
-                log::warn!("env variable '{key}' has empty value");
+            match &self.env[key] {
+                EnvValue::Literal(value) => {
+                    if value.trim().is_empty() {
+                        problems.push(Diagnostic::warning(field, format!("env variable '{key}' has empty value")));
+                    }
+                }
+                EnvValue::Secret(SecretSource::Command(source) | SecretSource::Keyring(source))
+                    if source.trim().is_empty() =>
+                {
+                    problems.push(Diagnostic::error(
+                        field,
+                        format!("env.{key} secret source cannot be empty\n\nExample:\n  [env]\n  GH_TOKEN = {{ command = \"pass show gh/token\" }}"),
+                    ));
+                }
+                EnvValue::Secret(SecretSource::File(path)) => {
+                    if path.trim().is_empty() || path.contains('`') || path.contains("$(") {
+                        problems.push(Diagnostic::error(
+                            field,
+                            format!("env.{key} file '{path}' must be a single file path\n  → Command substitution is not allowed; use {{ command = \"...\" }} instead\n\nExample:\n  [env]\n  OPENAI_API_KEY = {{ file = \"~/.secrets/openai\" }}"),
+                        ));
+                    }
+                }
+                EnvValue::Secret(_) => {}
             }
         }
 
-        Ok(())
+        // Validate aliases
+        for (kind, aliases) in [
+            ("aliases", &self.aliases.plain),
+            ("aliases.global", &self.aliases.global),
+        ] {
+            for (name, command) in aliases {
+                let field = format!("{kind}.{}", diagnostics::field_key(name));
+                if !is_valid_alias_name(name) {
+                    problems.push(
+                        Diagnostic::error(
+                            field,
+                            format!("{kind} name '{name}' is not a valid alias name\n  → Alias names cannot be empty, start with '-', or contain whitespace, quotes, '=', '$' or shell operators\n\nExample:\n  [aliases]\n  ll = \"ls -la\""),
+                        )
+                        .with_found(name),
+                    );
+                } else if command.trim().is_empty() {
+                    problems.push(Diagnostic::error(field, format!("{kind}.{name} cannot be an empty command")));
+                }
+            }
+        }
+
+        for (extension, command) in &self.aliases.suffix {
+            let field = format!("aliases.suffix.{}", diagnostics::field_key(extension));
+            if extension.is_empty()
+                || !extension
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+            {
+                problems.push(
+                    Diagnostic::error(
+                        field,
+                        format!("aliases.suffix key '{extension}' must be a file extension without the leading dot\n\nExample:\n  [aliases.suffix]\n  md = \"glow\""),
+                    )
+                    .with_found(extension),
+                );
+            } else if command.trim().is_empty() {
+                problems.push(Diagnostic::error(
+                    field,
+                    format!("aliases.suffix.{extension} cannot be an empty command"),
+                ));
+            }
+        }
+
+        // Validate PATH entries
+        for (kind, entries) in [("path.prepend", &self.path.prepend), ("path.append", &self.path.append)] {
+            for (idx, entry) in entries.iter().enumerate() {
+                let field = format!("{kind}[{idx}]");
+                if entry.trim().is_empty() {
+                    problems.push(Diagnostic::error(
+                        field,
+                        format!("{kind}[{idx}] cannot be empty string\n\nExample:\n  [path]\n  prepend = [\"~/.local/bin\", \"$GOPATH/bin\"]"),
+                    ));
+                } else if entry.contains('`') || entry.contains("$(") || entry.contains(':') {
+                    problems.push(
+                        Diagnostic::error(
+                            field,
+                            format!("{kind}[{idx}] '{entry}' must be a single directory\n  → Command substitution and ':'-separated lists are not allowed\n\nExample:\n  [path]\n  prepend = [\"~/.local/bin\", \"$GOPATH/bin\"]"),
+                        )
+                        .with_found(entry),
+                    );
+                }
+            }
+        }
+
+        // Validate shell options against zsh's known option names
+        let mut seen_options: HashMap<String, &str> = HashMap::new();
+        for (kind, names, turn_on) in [
+            ("options.set", &self.options.set, true),
+            ("options.unset", &self.options.unset, false),
+        ] {
+            for name in names {
+                let Some((base, positive)) = options::resolve_option(name) else {
+                    problems.push(
+                        Diagnostic::error(
+                            kind,
+                            format!("{kind} contains unknown zsh option '{name}'\n  → See 'man zshoptions' for the list of option names\n\nExample:\n  [options]\n  set = [\"AUTO_CD\", \"HIST_IGNORE_DUPS\"]\n  unset = [\"BEEP\"]"),
+                        )
+                        .with_found(name),
+                    );
+                    continue;
+                };
+                // NO_BEEP in `set` has the same effect as BEEP in `unset`
+                let enabled = if positive { turn_on } else { !turn_on };
+                let state = if enabled { "set" } else { "unset" };
+                if let Some(previous) = seen_options.insert(base, state) {
+                    if previous != state {
+                        problems.push(
+                            Diagnostic::error(
+                                kind,
+                                format!("zsh option '{name}' is both set and unset in [options]\n  → Remove it from one of the two lists"),
+                            )
+                            .with_found(name),
+                        );
+                    }
+                }
+            }
+        }
+
+        // Validate [hooks]
+        for (key, hook) in self.hooks.entries() {
+            if hook.is_some_and(|h| h.trim().is_empty()) {
+                problems.push(Diagnostic::error(
+                    format!("hooks.{key}"),
+                    format!("hooks.{key} cannot be empty\n  → Remove the line or give it a command or script path\n\nExample:\n  [hooks]\n  {key} = \"./hooks/{key}.zsh\""),
+                ));
+            }
+        }
+        if self.hooks.timeout == Some(0) {
+            problems.push(Diagnostic::error(
+                "hooks.timeout",
+                "hooks.timeout must be at least 1 second\n\nExample:\n  [hooks]\n  timeout = 10",
+            ));
+        }
+
+        // Validate [keys]
+        for widget in &self.keys.widgets {
+            if !keys::is_valid_widget_name(widget) {
+                problems.push(
+                    Diagnostic::error(
+                        "keys.widgets",
+                        format!("invalid widget name '{widget}' in keys.widgets\n  → Use the name of a shell function (letters, digits, '-', '_', '.', ':')"),
+                    )
+                    .with_found(widget),
+                );
+            }
+        }
+        for (idx, binding) in self.keys.bind.iter().enumerate() {
+            if let Err(reason) = keys::validate_key_sequence(&binding.keys) {
+                problems.push(Diagnostic::error(
+                    format!("keys.bind[{idx}].keys"),
+                    format!(
+                        "keys.bind[{idx}] has a malformed key sequence '{}': {reason}\n\nExample:\n  [[keys.bind]]\n  keys = \"^R\"\n  widget = \"history-incremental-search-backward\"",
+                        binding.keys
+                    ),
+                ));
+            }
+            if !keys::is_valid_widget_name(&binding.widget) {
+                problems.push(Diagnostic::error(
+                    format!("keys.bind[{idx}].widget"),
+                    format!(
+                        "keys.bind[{idx}] has an invalid widget name '{}'\n  → Use a zle widget such as 'fzf-history-widget' or 'vi-cmd-mode'",
+                        binding.widget
+                    ),
+                ));
+            }
+            if let Some(keymap) = &binding.keymap {
+                if !keys::KEYMAPS.contains(&keymap.as_str()) {
+                    problems.push(
+                        Diagnostic::error(
+                            format!("keys.bind[{idx}].keymap"),
+                            format!(
+                                "keys.bind[{idx}] uses unknown keymap '{keymap}'\n  → Use one of: {}",
+                                keys::KEYMAPS.join(", ")
+                            ),
+                        )
+                        .with_suggestion(diagnostics::did_you_mean(keymap, keys::KEYMAPS.iter().copied())),
+                    );
+                }
+            }
+        }
+
+        // Validate [completion]
+        for (idx, matcher) in self.completion.matchers.iter().enumerate() {
+            if matcher.trim().is_empty() {
+                problems.push(Diagnostic::error(
+                    format!("completion.matchers[{idx}]"),
+                    format!("completion.matchers[{idx}] cannot be empty string\n\nExample:\n  [completion]\n  matchers = [\"m:{{a-z}}={{A-Z}}\", \"r:|=*\"]"),
+                ));
+            }
+        }
+        for (idx, entry) in self.completion.fpath.iter().enumerate() {
+            let field = format!("completion.fpath[{idx}]");
+            if entry.trim().is_empty() {
+                problems.push(Diagnostic::error(
+                    field,
+                    format!("completion.fpath[{idx}] cannot be empty string\n\nExample:\n  [completion]\n  fpath = [\"~/.zfunc\"]"),
+                ));
+            } else if entry.contains('`') || entry.contains("$(") || entry.contains(':') {
+                problems.push(
+                    Diagnostic::error(
+                        field,
+                        format!("completion.fpath[{idx}] '{entry}' must be a single directory\n  → Command substitution and ':'-separated lists are not allowed\n\nExample:\n  [completion]\n  fpath = [\"~/.zfunc\"]"),
+                    )
+                    .with_found(entry),
+                );
+            }
+        }
+
+        if self.completion.fast_init && !COMPINIT_FRAMEWORKS.contains(&self.profile.framework.as_str()) {
+            problems.push(Diagnostic::warning(
+                "completion.fast_init",
+                format!(
+                    "fast_init has no effect on {}, which runs compinit itself\n  → It applies to: {}",
+                    self.profile.framework,
+                    COMPINIT_FRAMEWORKS.join(", ")
+                ),
+            ));
+        }
+
+        // Validate [[snippets]]
+        for (idx, snippet) in self.snippets.iter().enumerate() {
+            match (&snippet.code, &snippet.file) {
+                (Some(code), None) if code.trim().is_empty() => {
+                    problems.push(Diagnostic::error(
+                        format!("snippets[{idx}].code"),
+                        format!("snippets[{idx}].code cannot be empty"),
+                    ));
+                }
+                (Some(_), None) => {}
+                (None, Some(file)) => {
+                    let relative = Path::new(file);
+                    if file.trim().is_empty()
+                        || relative.is_absolute()
+                        || relative.components().any(|c| matches!(c, std::path::Component::ParentDir))
+                    {
+                        problems.push(Diagnostic::error(
+                            format!("snippets[{idx}].file"),
+                            format!("snippets[{idx}].file '{file}' must be a path inside the profile directory\n  → Use a relative path without '..'\n\nExample:\n  [[snippets]]\n  file = \"snippets/work.zsh\"\n  phase = \"post-plugins\""),
+                        ));
+                    }
+                }
+                _ => {
+                    problems.push(Diagnostic::error(
+                        format!("snippets[{idx}]"),
+                        format!("snippets[{idx}] must set exactly one of 'code' or 'file'\n\nExample:\n  [[snippets]]\n  code = \"zstyle ':omz:update' mode disabled\"\n  phase = \"pre-framework\""),
+                    ));
+                }
+            }
+        }
+
+        // Validate [when]: condition names, and each overlay applied on its own.
+        // Problems the base manifest already has are reported once, above.
+        let base_problems = problems.clone();
+        for condition in self.when.conditions() {
+            let label = condition.label();
+            let name = condition.name;
+            match condition.kind {
+                ConditionKind::Os if conditions::os_type_prefix(name).is_none() => {
+                    let known: Vec<&str> = conditions::OS_NAMES.iter().map(|(os, _)| *os).collect();
+                    problems.push(
+                        Diagnostic::error(
+                            &label,
+                            format!(
+                                "[{label}] is not a known operating system\n  → Use one of: {}\n\nExample:\n  [when.os.\"linux\".plugins]\n  enabled = [\"systemd\"]",
+                                known.join(", ")
+                            ),
+                        )
+                        .with_found(name)
+                        .with_suggestion(diagnostics::did_you_mean(name, known.iter().copied())),
+                    );
+                    continue;
+                }
+                ConditionKind::Host
+                    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_')) =>
+                {
+                    problems.push(
+                        Diagnostic::error(
+                            &label,
+                            format!("[{label}] is not a valid hostname\n  → Hostnames may only contain letters, digits, '-', '.' and '_'\n\nExample:\n  [when.host.\"work-laptop\".env]\n  AWS_PROFILE = \"work\""),
+                        )
+                        .with_found(name),
+                    );
+                    continue;
+                }
+                ConditionKind::Env
+                    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+                {
+                    problems.push(
+                        Diagnostic::error(
+                            &label,
+                            format!("[{label}] is not a valid environment variable name\n\nExample:\n  [when.env.\"CODESPACES\".aliases]\n  open = \"code\""),
+                        )
+                        .with_found(name),
+                    );
+                    continue;
+                }
+                _ => {}
+            }
+
+            if self.when.resolve == WhenResolve::Shell && !condition.overlay.plugins.is_empty() {
+                problems.push(Diagnostic::error(
+                    format!("{label}.plugins"),
+                    format!("[{label}] sets plugins, which can't be switched at shell start\n  → Use resolve = \"generate\" in [when], or move the plugins out of the overlay"),
+                ));
+            }
+
+            let mut base = self.clone();
+            base.when = WhenSection::default();
+            match base.with_overlay(condition.overlay) {
+                Ok(merged) => problems.extend(
+                    merged
+                        .check()
+                        .into_iter()
+                        .filter(|problem| !base_problems.contains(problem))
+                        .map(|problem| problem.within(&label, &format!("\n  → In [{label}]"))),
+                ),
+                Err(e) => problems.push(Diagnostic::error(&label, format!("{e:#}\n  → In [{label}]"))),
+            }
+        }
+
+        problems
+    }
+
+    /// This manifest with an overlay merged on, using the `extends` merge rules
+    pub fn with_overlay(&self, overlay: &Overlay) -> Result<Manifest> {
+        let base = toml::Table::try_from(self).context("Failed to serialize manifest")?;
+        let overlay = toml::Table::try_from(overlay).context("Failed to serialize [when] overlay")?;
+
+        manifest_from_table(merge_manifest_tables(base, overlay))
+    }
+
+    /// Merge the `[when]` overlays that match `machine`
+    ///
+    /// Does nothing with `resolve = "shell"`; the generator emits guarded
+    /// blocks instead. `[when]` itself is kept so callers can report which
+    /// conditions matched.
+    pub fn apply_conditions(self, machine: &Machine) -> Result<Manifest> {
+        if self.when.resolve == WhenResolve::Shell {
+            return Ok(self);
+        }
+
+        let mut manifest = self.clone();
+        for condition in self.when.conditions() {
+            if condition.matches(machine) {
+                log::debug!("Applying [{}]", condition.label());
+                manifest = manifest.with_overlay(condition.overlay)?;
+            }
+        }
+        Ok(manifest)
     }
 }
 
 /// Parse TOML manifest from string content with enhanced error reporting
+///
+/// Manifests on an older schema are migrated in memory first, so callers
+/// always get the current structure. Use `migrate_table` to upgrade the file
+/// itself.
 pub fn parse_manifest(toml_content: &str) -> Result<Manifest> {
-    toml::from_str(toml_content).map_err(|e| {
-        // Try to extract line/column information from the error
-        let error_msg = e.to_string();
-        if error_msg.contains("line") || error_msg.contains("column") {
-            anyhow::anyhow!(
-                "TOML parse error: {error_msg}\n\n  → Check TOML syntax at the indicated location"
-            )
-        } else {
-            anyhow::anyhow!("Failed to parse profile.toml - check TOML syntax\n  {error_msg}")
+    let mut table = parse_manifest_table(toml_content)?;
+    migrate_table(&mut table)?;
+    manifest_from_table(table)
+}
+
+/// Parse profile.toml content into a raw TOML table, without any migration
+pub fn parse_manifest_table(toml_content: &str) -> Result<toml::Table> {
+    toml::from_str(toml_content).map_err(toml_error)
+}
+
+/// Deserialize a migrated manifest table into a `Manifest`
+///
+/// A `[profile]` section without any prompt settings gets an empty framework
+/// theme, which is what a profile with no explicit theme has always meant.
+fn manifest_from_table(table: toml::Table) -> Result<Manifest> {
+    deserialize_table(table).map_err(toml_error)
+}
+
+/// `manifest_from_table`, keeping the raw TOML error
+fn deserialize_table(mut table: toml::Table) -> Result<Manifest, toml::de::Error> {
+    if let Some(toml::Value::Table(profile)) = table.get_mut("profile") {
+        if !profile.contains_key("prompt_mode") {
+            profile.insert("prompt_mode".to_string(), "framework_theme".into());
+            profile
+                .entry("framework_theme")
+                .or_insert_with(|| String::new().into());
         }
+    }
+
+    Manifest::deserialize(toml::Value::Table(table))
+}
+
+/// Turn a TOML error into a user-facing parse error
+fn toml_error(e: toml::de::Error) -> anyhow::Error {
+    // Try to extract line/column information from the error
+    let error_msg = e.to_string();
+    if error_msg.contains("line") || error_msg.contains("column") {
+        anyhow::anyhow!(
+            "TOML parse error: {error_msg}\n\n  → Check TOML syntax at the indicated location"
+        )
+    } else {
+        anyhow::anyhow!("Failed to parse profile.toml - check TOML syntax\n  {error_msg}")
+    }
+}
+
+/// Current profile.toml schema version
+///
+/// Bump this together with a new entry in `MIGRATIONS` whenever the manifest
+/// format changes in a way that older files have to be rewritten for.
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+/// Schema version of manifests written before `schema_version` existed
+const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+/// A single upgrade step from schema version `from` to `from + 1`
+struct Migration {
+    from: u32,
+    /// Applies the step and returns a description of each change it made
+    apply: fn(&mut toml::Table) -> Vec<String>,
+}
+
+/// Migration steps, applied in order starting from a manifest's own version
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 1,
+    apply: migrate_v1_to_v2,
+}];
+
+/// What `migrate_table` changed in a manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub changes: Vec<String>,
+}
+
+impl MigrationReport {
+    /// True if the manifest was already on the current schema
+    pub fn is_up_to_date(&self) -> bool {
+        self.from_version == self.to_version
+    }
+}
+
+/// Read the schema version of a raw manifest table
+///
+/// Manifests without a `schema_version` key are treated as version 1.
+pub fn schema_version(table: &toml::Table) -> Result<u32> {
+    let Some(value) = table.get("schema_version") else {
+        return Ok(LEGACY_SCHEMA_VERSION);
+    };
+
+    let version = value
+        .as_integer()
+        .and_then(|v| u32::try_from(v).ok())
+        .filter(|v| *v >= LEGACY_SCHEMA_VERSION);
+    let Some(version) = version else {
+        bail!(
+            "Validation error: schema_version must be a positive integer\n  Found: {value}\n  → Remove the line to let zprof detect the version\n\nExample:\n  schema_version = {CURRENT_SCHEMA_VERSION}"
+        );
+    };
+
+    if version > CURRENT_SCHEMA_VERSION {
+        bail!(
+            "Validation error: profile.toml uses schema_version {version}, but this version of zprof only supports up to {CURRENT_SCHEMA_VERSION}\n  → Upgrade zprof to use this profile"
+        );
+    }
+
+    Ok(version)
+}
+
+/// Upgrade a raw manifest table to `CURRENT_SCHEMA_VERSION`, step by step
+///
+/// Each step in `MIGRATIONS` takes the table one version forward; the
+/// resulting table has `schema_version` set to the current version.
+pub fn migrate_table(table: &mut toml::Table) -> Result<MigrationReport> {
+    let from_version = schema_version(table)?;
+    let mut changes = Vec::new();
+
+    for version in from_version..CURRENT_SCHEMA_VERSION {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|m| m.from == version)
+            .with_context(|| format!("No migration from schema version {version}"))?;
+        changes.extend((migration.apply)(table));
+    }
+
+    if from_version != CURRENT_SCHEMA_VERSION {
+        table.insert(
+            "schema_version".to_string(),
+            toml::Value::Integer(CURRENT_SCHEMA_VERSION.into()),
+        );
+        changes.push(format!("set schema_version = {CURRENT_SCHEMA_VERSION}"));
+    }
+
+    Ok(MigrationReport {
+        from_version,
+        to_version: CURRENT_SCHEMA_VERSION,
+        changes,
     })
 }
 
+/// v1 → v2: replace the legacy `profile.theme` key with `prompt_mode`
+fn migrate_v1_to_v2(table: &mut toml::Table) -> Vec<String> {
+    let mut changes = Vec::new();
+    let Some(toml::Value::Table(profile)) = table.get_mut("profile") else {
+        return changes;
+    };
+
+    if let Some(theme) = profile.remove("theme") {
+        if profile.contains_key("prompt_mode") {
+            // prompt_mode always took precedence over the legacy key
+            changes.push("removed profile.theme (superseded by prompt_mode)".to_string());
+        } else {
+            profile.insert("prompt_mode".to_string(), "framework_theme".into());
+            profile.insert("framework_theme".to_string(), theme);
+            changes.push("replaced profile.theme with prompt_mode = \"framework_theme\"".to_string());
+        }
+    }
+
+    changes
+}
+
 /// Get path to profile's manifest file
 pub fn get_manifest_path(profile_name: &str) -> PathBuf {
     dirs::home_dir()
@@ -407,7 +1528,9 @@ pub fn get_manifest_path(profile_name: &str) -> PathBuf {
 /// Load and validate a profile manifest
 ///
 /// This is the main entry point for loading and validating manifests.
-/// It combines file reading, parsing, and validation in one step.
+/// It combines file reading, parsing, and validation in one step. If the
+/// profile extends another, the returned manifest is the resolved one, and
+/// `[when]` overlays matching this machine are merged in.
 pub fn load_and_validate(profile_name: &str) -> Result<Manifest> {
     let manifest_path = get_manifest_path(profile_name);
 
@@ -422,8 +1545,30 @@ pub fn load_and_validate(profile_name: &str) -> Result<Manifest> {
     let toml_content = std::fs::read_to_string(&manifest_path)
         .with_context(|| format!("Failed to read profile.toml at {manifest_path:?}"))?;
 
-    let manifest = parse_manifest(&toml_content)
+    let mut table = parse_manifest_table(&toml_content)
         .with_context(|| format!("Invalid TOML in {manifest_path:?}"))?;
+    let report = migrate_table(&mut table)
+        .with_context(|| format!("Unsupported manifest schema in {manifest_path:?}"))?;
+    // Only logged here; `show` and `edit` tell the user how to upgrade
+    if !report.is_up_to_date() {
+        log::warn!(
+            "Profile '{profile_name}' uses manifest schema version {} (current is {})",
+            report.from_version, report.to_version
+        );
+    }
+
+    let manifest = manifest_from_table(table)
+        .with_context(|| format!("Invalid TOML in {manifest_path:?}"))?;
+
+    // Profiles that extend another are validated in their resolved form
+    let manifest = if manifest.profile.extends.is_some() {
+        resolve_manifest(&manifest_path)?
+    } else {
+        manifest
+    };
+
+    // Conditional sections for this machine, when resolved at generation time
+    let manifest = manifest.apply_conditions(&Machine::current())?;
 
     manifest
         .validate()
@@ -433,6 +1578,405 @@ pub fn load_and_validate(profile_name: &str) -> Result<Manifest> {
     Ok(manifest)
 }
 
+/// Check a profile.toml and report every problem found, with locations
+///
+/// Unlike `load_and_validate`, this doesn't stop at the first problem: all
+/// TOML syntax errors are reported or, once the file parses, every validation
+/// error and warning of the resolved manifest. An empty list means the
+/// manifest loads cleanly. Only failing to read the file is an `Err`.
+pub fn diagnose(manifest_path: &Path) -> Result<Vec<Diagnostic>> {
+    let source = std::fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read profile.toml at {manifest_path:?}"))?;
+
+    let mut table: toml::Table = match toml::from_str(&source) {
+        Ok(table) => table,
+        Err(e) => {
+            let (_, errors) = toml::de::DeTable::parse_recoverable(&source);
+            let errors = if errors.is_empty() { vec![e] } else { errors };
+            return Ok(errors
+                .iter()
+                .map(|e| Diagnostic::from_toml_error(e, &source))
+                .collect());
+        }
+    };
+    if let Err(e) = migrate_table(&mut table) {
+        let mut problems = vec![Diagnostic::error("schema_version", format!("{e:#}"))];
+        diagnostics::locate(&mut problems, &source);
+        return Ok(problems);
+    }
+
+    let mut problems = match deserialize_table(table) {
+        Err(e) => vec![Diagnostic::from_toml_error(&e, &source)],
+        Ok(manifest) => {
+            let resolved = if manifest.profile.extends.is_some() {
+                resolve_manifest(manifest_path)
+            } else {
+                Ok(manifest)
+            };
+            match resolved {
+                Err(e) => vec![Diagnostic::error("profile.extends", format!("{e:#}"))],
+                Ok(manifest) => match manifest.apply_conditions(&Machine::current()) {
+                    Ok(manifest) => manifest.check(),
+                    Err(e) => vec![Diagnostic::error("when", format!("{e:#}"))],
+                },
+            }
+        }
+    };
+    locate_in_chain(&mut problems, &source);
+    Ok(problems)
+}
+
+/// Locate `problems` in a profile's profile.toml `source`, and name the
+/// parent profile of those inherited through `extends`
+///
+/// Inherited problems are left without a location: they can only be fixed
+/// in the parent's file.
+pub fn locate_in_chain(problems: &mut [Diagnostic], source: &str) {
+    diagnostics::locate(problems, source);
+
+    let extends = |source: &str| -> Option<String> {
+        let table: toml::Table = toml::from_str(source).ok()?;
+        table.get("profile")?.get("extends")?.as_str().map(str::to_string)
+    };
+    let mut chain = Vec::new();
+    let mut parent = extends(source);
+    while let Some(name) = parent.filter(|name| !chain.contains(name)) {
+        let Ok(parent_source) = std::fs::read_to_string(get_manifest_path(&name)) else {
+            break;
+        };
+        diagnostics::attribute(problems, &parent_source, &name);
+        parent = extends(&parent_source);
+        chain.push(name);
+    }
+}
+
+/// Load a profile manifest exactly as written, without resolving `extends`
+///
+/// Used to show what a profile defines on its own. A profile that extends
+/// another may leave `framework` empty here; use `load_and_validate` for the
+/// resolved, validated manifest.
+pub fn load_unresolved(profile_name: &str) -> Result<Manifest> {
+    let manifest_path = get_manifest_path(profile_name);
+    let toml_content = std::fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Failed to read profile.toml at {manifest_path:?}"))?;
+
+    parse_manifest(&toml_content).with_context(|| format!("Invalid TOML in {manifest_path:?}"))
+}
+
+/// `[profile]` keys that together describe the prompt mode
+///
+/// When a child profile sets any of these, the parent's prompt settings are
+/// dropped entirely so the two can't mix (e.g. a parent theme with a child engine).
+const PROMPT_KEYS: &[&str] = &["prompt_mode", "prompt_engine", "framework_theme", "theme"];
+
+/// `[profile]` keys that always belong to the child and are never inherited
+const OWN_PROFILE_KEYS: &[&str] = &["name", "extends", "created", "modified"];
+
+/// Resolve a manifest's `extends` chain and parse the merged result
+///
+/// `manifest_path` is the child's profile.toml; parents are looked up by
+/// profile name in the profiles directory. Merge rules, applied from the
+/// root of the chain down:
+/// - `[profile]`: child keys override; prompt settings are replaced as a unit
+/// - lists (plugins, PATH entries, ...): appended with duplicates removed
+/// - tables (env, aliases, ...): merged key by key, child wins
+/// - `[[plugins.entry]]`-style lists of named tables: merged by `name`
+/// - `[options]`: a child's set/unset of an option replaces the parent's
+///
+/// Relative snippet files, secret files and hook scripts from a parent are
+/// rewritten to point into the parent's directory.
+pub fn resolve_manifest(manifest_path: &Path) -> Result<Manifest> {
+    let mut chain = Vec::new();
+    let merged = resolve_table(manifest_path, &mut chain)?;
+
+    manifest_from_table(merged).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to parse resolved manifest for {}\n  {e}\n  → Inheritance chain: {}",
+            manifest_path.display(),
+            chain.join(" → ")
+        )
+    })
+}
+
+/// Read a manifest as a raw TOML table and merge it onto its parent, recursively
+fn resolve_table(manifest_path: &Path, chain: &mut Vec<String>) -> Result<toml::Table> {
+    let toml_content = std::fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read profile.toml at {manifest_path:?}"))?;
+    let mut table: toml::Table = toml::from_str(&toml_content).map_err(|e| {
+        anyhow::anyhow!("TOML parse error in {}: {e}\n\n  → Check TOML syntax at the indicated location", manifest_path.display())
+    })?;
+    // Parents may be on an older schema than the child
+    migrate_table(&mut table)
+        .with_context(|| format!("Unsupported manifest schema in {manifest_path:?}"))?;
+
+    let profile = table.get("profile").and_then(|p| p.as_table());
+    let name = profile
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .unwrap_or("?")
+        .to_string();
+    let parent = profile
+        .and_then(|p| p.get("extends"))
+        .and_then(|e| e.as_str())
+        .map(str::to_string);
+
+    // `extends` names a profile directory, which is what the chain tracks;
+    // `[profile].name` may differ from it after a hand edit
+    let dir_name = manifest_path
+        .parent()
+        .and_then(Path::file_name)
+        .map(|dir| dir.to_string_lossy().into_owned())
+        .unwrap_or_else(|| name.clone());
+    chain.push(dir_name);
+
+    let Some(parent) = parent else {
+        return Ok(table);
+    };
+
+    if chain.contains(&parent) {
+        chain.push(parent);
+        bail!(
+            "Validation error: profile inheritance cycle detected\n  {}\n  → Remove 'extends' from one of these profiles",
+            chain.join(" → ")
+        );
+    }
+
+    let parent_path = get_manifest_path(&parent);
+    if !parent_path.exists() {
+        bail!(
+            "Validation error: profile '{name}' extends '{parent}', which does not exist\n  → Create '{parent}' or change profile.extends\n\nExample:\n  [profile]\n  extends = \"base\""
+        );
+    }
+
+    let mut base = resolve_table(&parent_path, chain)?;
+    anchor_snippet_files(&mut base, &parent);
+    anchor_secret_files(&mut base, &parent);
+    anchor_hook_scripts(&mut base, &parent);
+    Ok(merge_manifest_tables(base, table))
+}
+
+/// Point a parent's `[[snippets]]` files at the parent's own directory
+///
+/// Snippet files are relative to the profile that declares them, so once
+/// inherited they are rewritten to `~/.zsh-profiles/profiles/<parent>/...`.
+fn anchor_snippet_files(table: &mut toml::Table, profile_name: &str) {
+    let Some(toml::Value::Array(snippets)) = table.get_mut("snippets") else {
+        return;
+    };
+    for snippet in snippets.iter_mut().filter_map(|s| s.as_table_mut()) {
+        if let Some(toml::Value::String(file)) = snippet.get_mut("file") {
+            if !file.starts_with("~/") {
+                *file = format!("~/.zsh-profiles/profiles/{profile_name}/{file}");
+            }
+        }
+    }
+}
+
+/// Point a parent's `[hooks]` scripts at the parent's own directory
+///
+/// Hook commands are left alone; only hooks that `hook_script_path` takes
+/// for a relative script path are rewritten.
+fn anchor_hook_scripts(table: &mut toml::Table, profile_name: &str) {
+    let Some(toml::Value::Table(hooks)) = table.get_mut("hooks") else {
+        return;
+    };
+    for (_, hook) in hooks.iter_mut() {
+        let toml::Value::String(hook) = hook else {
+            continue;
+        };
+        if let Some(script) = hook_script_path(hook).filter(|script| !script.starts_with(['/', '~'])) {
+            *hook = format!("~/.zsh-profiles/profiles/{profile_name}/{}", script.trim_start_matches("./"));
+        }
+    }
+}
+
+/// Point a parent's relative `{ file = "..." }` secrets at the parent's own
+/// directory, in `[env]` and in every `[when.*.env]` overlay
+fn anchor_secret_files(table: &mut toml::Table, profile_name: &str) {
+    for (key, value) in table.iter_mut() {
+        match (key.as_str(), value) {
+            ("env", toml::Value::Table(env)) => anchor_env_secret_files(env, profile_name),
+            ("when", toml::Value::Table(when)) => {
+                let overlays = when
+                    .iter_mut()
+                    .filter_map(|(_, overlays)| overlays.as_table_mut())
+                    .flat_map(|overlays| overlays.iter_mut())
+                    .filter_map(|(_, overlay)| overlay.as_table_mut());
+                for overlay in overlays {
+                    if let Some(toml::Value::Table(env)) = overlay.get_mut("env") {
+                        anchor_env_secret_files(env, profile_name);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn anchor_env_secret_files(env: &mut toml::Table, profile_name: &str) {
+    for (_, secret) in env.iter_mut() {
+        let Some(secret) = secret.as_table_mut() else {
+            continue;
+        };
+        if let Some(toml::Value::String(file)) = secret.get_mut("file") {
+            if !file.starts_with(['/', '~', '$']) {
+                *file = format!("~/.zsh-profiles/profiles/{profile_name}/{}", file.trim_start_matches("./"));
+            }
+        }
+    }
+}
+
+/// Merge a child manifest table onto its (already resolved) parent
+fn merge_manifest_tables(mut base: toml::Table, child: toml::Table) -> toml::Table {
+    for (key, child_value) in child {
+        let merged = match (key.as_str(), base.remove(&key), child_value) {
+            ("profile", Some(toml::Value::Table(base_profile)), toml::Value::Table(child_profile)) => {
+                toml::Value::Table(merge_profile_tables(base_profile, child_profile))
+            }
+            ("options", Some(toml::Value::Table(base_options)), toml::Value::Table(child_options)) => {
+                toml::Value::Table(merge_options_tables(base_options, child_options))
+            }
+            // A secret table replaces the parent's value as a whole instead of merging into it
+            ("env", Some(toml::Value::Table(mut base_env)), toml::Value::Table(child_env)) => {
+                base_env.extend(child_env);
+                toml::Value::Table(base_env)
+            }
+            (_, Some(base_value), child_value) => merge_values(base_value, child_value),
+            (_, None, child_value) => child_value,
+        };
+        base.insert(key, merged);
+    }
+    base
+}
+
+/// Merge `[profile]`: inherit framework and prompt, keep the child's identity
+fn merge_profile_tables(mut base: toml::Table, child: toml::Table) -> toml::Table {
+    for key in OWN_PROFILE_KEYS {
+        base.remove(*key);
+    }
+    if PROMPT_KEYS.iter().any(|key| child.contains_key(*key)) {
+        for key in PROMPT_KEYS {
+            base.remove(*key);
+        }
+    }
+    base.extend(child);
+    base
+}
+
+/// Merge `[options]`: the child decides the state of every option it mentions
+fn merge_options_tables(base: toml::Table, child: toml::Table) -> toml::Table {
+    let names = |table: &toml::Table, key: &str| -> Vec<toml::Value> {
+        table
+            .get(key)
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default()
+    };
+    let option_base = |value: &toml::Value| {
+        value
+            .as_str()
+            .map(|name| options::resolve_option(name).map(|(base, _)| base).unwrap_or_else(|| options::normalize_option_name(name)))
+    };
+
+    let child_mentions: Vec<String> = ["set", "unset"]
+        .iter()
+        .flat_map(|key| names(&child, key))
+        .filter_map(|v| option_base(&v))
+        .collect();
+
+    let mut merged = toml::Table::new();
+    for key in ["set", "unset"] {
+        let mut list: Vec<toml::Value> = names(&base, key)
+            .into_iter()
+            .filter(|v| option_base(v).is_none_or(|b| !child_mentions.contains(&b)))
+            .collect();
+        for value in names(&child, key) {
+            if !list.contains(&value) {
+                list.push(value);
+            }
+        }
+        if !list.is_empty() {
+            merged.insert(key.to_string(), toml::Value::Array(list));
+        }
+    }
+    merged
+}
+
+/// Generic merge: tables merge by key, lists append without duplicates,
+/// anything else is replaced by the child's value
+fn merge_values(base: toml::Value, child: toml::Value) -> toml::Value {
+    match (base, child) {
+        (toml::Value::Table(mut base), toml::Value::Table(child)) => {
+            for (key, child_value) in child {
+                let merged = match base.remove(&key) {
+                    Some(base_value) => merge_values(base_value, child_value),
+                    None => child_value,
+                };
+                base.insert(key, merged);
+            }
+            toml::Value::Table(base)
+        }
+        (toml::Value::Array(mut base), toml::Value::Array(child)) => {
+            for child_item in child {
+                // Named tables ([[plugins.entry]]) replace the parent's entry of the same name
+                let child_name = child_item.get("name").and_then(|n| n.as_str()).map(str::to_string);
+                let existing = child_name.as_deref().and_then(|name| {
+                    base.iter().position(|item| item.get("name").and_then(|n| n.as_str()) == Some(name))
+                });
+                match existing {
+                    Some(idx) => base[idx] = child_item,
+                    None if !base.contains(&child_item) => base.push(child_item),
+                    None => {}
+                }
+            }
+            toml::Value::Array(base)
+        }
+        (_, child) => child,
+    }
+}
+
+/// Names of profiles that extend `profile_name`, directly or through another profile
+///
+/// Returned in dependency order (a profile always comes after its parent) so
+/// they can be regenerated one after another.
+pub fn find_descendants(profile_name: &str) -> Result<Vec<String>> {
+    let profiles_dir = crate::core::profile::get_profiles_dir()?;
+    if !profiles_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut parents: Vec<(String, String)> = Vec::new();
+    for entry in std::fs::read_dir(&profiles_dir)? {
+        let path = entry?.path().join("profile.toml");
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(table) = toml::from_str::<toml::Table>(&content) else {
+            continue;
+        };
+        let profile = table.get("profile").and_then(|p| p.as_table());
+        let name = profile.and_then(|p| p.get("name")).and_then(|n| n.as_str());
+        let extends = profile.and_then(|p| p.get("extends")).and_then(|e| e.as_str());
+        if let (Some(name), Some(extends)) = (name, extends) {
+            parents.push((name.to_string(), extends.to_string()));
+        }
+    }
+    parents.sort();
+
+    let mut descendants: Vec<String> = Vec::new();
+    let mut frontier = vec![profile_name.to_string()];
+    while let Some(current) = frontier.pop() {
+        for (child, parent) in &parents {
+            if parent == &current && child != profile_name && !descendants.contains(child) {
+                descendants.push(child.clone());
+                frontier.insert(0, child.clone());
+            }
+        }
+    }
+
+    Ok(descendants)
+}
+
 // Test-only helper functions
 #[cfg(test)]
 pub fn manifest_exists(profile_name: &str) -> bool {
@@ -550,7 +2094,7 @@ EDITOR = "vim"
         assert_eq!(manifest.profile.name, "test");
         assert_eq!(manifest.profile.framework, "oh-my-zsh");
         assert_eq!(manifest.plugins.enabled.len(), 2);
-        assert_eq!(manifest.env.get("EDITOR"), Some(&"vim".to_string()));
+        assert_eq!(manifest.env.get("EDITOR"), Some(&EnvValue::from("vim")));
     }
 
     #[test]
@@ -586,15 +2130,26 @@ framework = "zimfw"
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: PluginsSection {
                 enabled: vec!["git".to_string(), "docker".to_string()],
+                entry: Vec::new(),
             },
             env: {
                 let mut map = HashMap::new();
-                map.insert("EDITOR".to_string(), "vim".to_string());
+                map.insert("EDITOR".to_string(), "vim".into());
                 map
             },
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         };
 
         assert!(manifest.validate().is_ok());
@@ -611,9 +2166,19 @@ framework = "zimfw"
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: Default::default(),
             env: Default::default(),
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         };
 
         let result = manifest.validate();
@@ -632,9 +2197,19 @@ framework = "zimfw"
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: Default::default(),
             env: Default::default(),
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         };
 
         let result = manifest.validate();
@@ -656,9 +2231,19 @@ framework = "zimfw"
                     },
                     created: Utc::now(),
                     modified: Utc::now(),
+                    extends: None,
                 },
                 plugins: Default::default(),
                 env: Default::default(),
+                aliases: Default::default(),
+                options: Default::default(),
+                path: Default::default(),
+                schema_version: CURRENT_SCHEMA_VERSION,
+                hooks: HooksSection::default(),
+                keys: KeysSection::default(),
+                completion: CompletionSection::default(),
+                when: WhenSection::default(),
+                snippets: Vec::new(),
             };
 
             manifest
@@ -678,11 +2263,22 @@ framework = "zimfw"
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: PluginsSection {
                 enabled: vec!["git".to_string(), "".to_string()],
+                entry: Vec::new(),
             },
             env: Default::default(),
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         };
 
         let result = manifest.validate();
@@ -701,18 +2297,110 @@ framework = "zimfw"
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: Default::default(),
             env: {
                 let mut map = HashMap::new();
-                map.insert("MY-VAR".to_string(), "value".to_string());
+                map.insert("MY-VAR".to_string(), "value".into());
                 map
             },
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         };
 
         let result = manifest.validate();
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("invalid characters"));
+        assert!(result.unwrap_err().to_string().contains("invalid characters"));
+    }
+
+    #[test]
+    fn test_parse_manifest_with_aliases() {
+        let toml = r#"
+[profile]
+name = "test"
+framework = "zap"
+
+[aliases]
+ll = "ls -la"
+gs = "git status"
+
+[aliases.global]
+G = "| grep"
+
+[aliases.suffix]
+md = "glow"
+        "#;
+
+        let manifest = parse_manifest(toml).expect("Should parse aliases");
+        assert_eq!(manifest.aliases.plain.len(), 2);
+        assert_eq!(manifest.aliases.plain.get("ll"), Some(&"ls -la".to_string()));
+        assert_eq!(manifest.aliases.global.get("G"), Some(&"| grep".to_string()));
+        assert_eq!(manifest.aliases.suffix.get("md"), Some(&"glow".to_string()));
+        manifest.validate().expect("Aliases should validate");
+    }
+
+    #[test]
+    fn test_aliases_roundtrip() {
+        let info = create_test_framework_info();
+        let mut manifest = Manifest::from_framework_info("test", &info);
+        manifest.aliases.plain.insert("ll".to_string(), "ls -la".to_string());
+        manifest.aliases.global.insert("G".to_string(), "| grep".to_string());
+        manifest.aliases.suffix.insert("md".to_string(), "glow".to_string());
+
+        let toml_str = manifest.to_toml_string().unwrap();
+        let parsed = parse_manifest(&toml_str).unwrap();
+
+        assert_eq!(parsed.aliases, manifest.aliases);
+    }
+
+    #[test]
+    fn test_empty_aliases_not_serialized() {
+        let info = create_test_framework_info();
+        let manifest = Manifest::from_framework_info("test", &info);
+        let toml_str = manifest.to_toml_string().unwrap();
+
+        assert!(!toml_str.contains("[aliases"));
+    }
+
+    #[test]
+    fn test_validate_invalid_alias_name() {
+        let info = create_test_framework_info();
+        let mut manifest = Manifest::from_framework_info("test", &info);
+        manifest.aliases.plain.insert("my alias".to_string(), "ls".to_string());
+
+        let result = manifest.validate();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("not a valid alias name"));
+    }
+
+    #[test]
+    fn test_validate_empty_alias_command() {
+        let info = create_test_framework_info();
+        let mut manifest = Manifest::from_framework_info("test", &info);
+        manifest.aliases.global.insert("G".to_string(), "  ".to_string());
+
+        let result = manifest.validate();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("cannot be an empty command"));
+    }
+
+    #[test]
+    fn test_validate_invalid_suffix_alias() {
+        let info = create_test_framework_info();
+        let mut manifest = Manifest::from_framework_info("test", &info);
+        manifest.aliases.suffix.insert(".md".to_string(), "glow".to_string());
+
+        let result = manifest.validate();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("file extension"));
     }
 
     #[test]
@@ -771,9 +2459,19 @@ name = "broken"
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: Default::default(),
             env: Default::default(),
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         };
 
         let result = manifest.validate();
@@ -851,7 +2549,7 @@ EDITOR = "vim"
         );
         assert_eq!(parsed.plugins.enabled.len(), 2);
         assert_eq!(parsed.plugins.enabled[0], "git");
-        assert_eq!(parsed.env.get("EDITOR"), Some(&"vim".to_string()));
+        assert_eq!(parsed.env.get("EDITOR"), Some(&EnvValue::from("vim")));
 
         // Test error case: invalid TOML
         let invalid_toml = r#"
@@ -961,9 +2659,19 @@ modified = "2025-11-01T10:00:00Z"
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: Default::default(),
             env: Default::default(),
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         };
 
         let result = manifest.validate();
@@ -985,9 +2693,19 @@ modified = "2025-11-01T10:00:00Z"
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: Default::default(),
             env: Default::default(),
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         };
 
         assert!(manifest.validate().is_ok());
@@ -1004,9 +2722,19 @@ modified = "2025-11-01T10:00:00Z"
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: Default::default(),
             env: Default::default(),
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         };
 
         assert!(manifest.validate().is_ok());
@@ -1023,9 +2751,19 @@ modified = "2025-11-01T10:00:00Z"
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: Default::default(),
             env: Default::default(),
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         };
 
         let toml_str = manifest.to_toml_string().unwrap();
@@ -1050,9 +2788,19 @@ modified = "2025-11-01T10:00:00Z"
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: Default::default(),
             env: Default::default(),
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         };
 
         let toml_str = manifest.to_toml_string().unwrap();
@@ -1077,9 +2825,19 @@ modified = "2025-11-01T10:00:00Z"
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: Default::default(),
             env: Default::default(),
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            hooks: HooksSection::default(),
+            keys: KeysSection::default(),
+            completion: CompletionSection::default(),
+            when: WhenSection::default(),
+            snippets: Vec::new(),
         };
 
         let toml_str = manifest.to_toml_string().unwrap();
@@ -1175,7 +2933,7 @@ modified = "2025-11-01T10:00:00Z"
         for (key, value) in developer.config.env_vars {
             assert_eq!(
                 manifest.env.get(*key),
-                Some(&value.to_string()),
+                Some(&EnvValue::from(*value)),
                 "Env var {key} should be set to {value}"
             );
         }
@@ -1230,6 +2988,262 @@ modified = "2025-11-01T10:00:00Z"
         assert_eq!(parsed.profile.prompt_mode, manifest.profile.prompt_mode);
         assert_eq!(parsed.plugins.enabled, manifest.plugins.enabled);
         assert_eq!(parsed.env, manifest.env);
+        assert_eq!(parsed.options, manifest.options);
+    }
+
+    #[test]
+    fn test_from_preset_copies_shell_options() {
+        use crate::presets::PRESET_REGISTRY;
+
+        for preset in PRESET_REGISTRY {
+            let manifest = Manifest::from_preset("test", preset);
+            let expected: Vec<String> = preset
+                .config
+                .shell_options
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+            assert_eq!(
+                manifest.options.set, expected,
+                "Shell options should match for preset {}",
+                preset.id
+            );
+            assert!(manifest.options.unset.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_manifest_with_options() {
+        let toml = r#"
+[profile]
+name = "test"
+framework = "zap"
+
+[options]
+set = ["AUTO_CD", "hist_ignore_dups", "extendedglob"]
+unset = ["BEEP"]
+        "#;
+
+        let manifest = parse_manifest(toml).expect("Should parse options");
+        assert_eq!(manifest.options.set.len(), 3);
+        assert_eq!(manifest.options.unset, vec!["BEEP".to_string()]);
+        manifest.validate().expect("Options should validate");
+    }
+
+    #[test]
+    fn test_validate_unknown_shell_option() {
+        let info = create_test_framework_info();
+        let mut manifest = Manifest::from_framework_info("test", &info);
+        manifest.options.set = vec!["AUTO_CDD".to_string()];
+
+        let result = manifest.validate();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("unknown zsh option 'AUTO_CDD'"));
+    }
+
+    #[test]
+    fn test_parse_manifest_with_plugin_entries() {
+        let toml = r#"
+[profile]
+name = "test"
+framework = "zinit"
+
+[plugins]
+enabled = ["git", "zsh-autosuggestions"]
+
+[[plugins.entry]]
+name = "zsh-autosuggestions"
+ref = "v0.7.0"
+
+[[plugins.entry]]
+name = "fast-syntax-highlighting"
+source = "zdharma-continuum/fast-syntax-highlighting"
+defer = true
+options = { zinit = "atinit'zicompinit'" }
+        "#;
+
+        let manifest = parse_manifest(toml).expect("Should parse plugin entries");
+        manifest.validate().expect("Plugin entries should validate");
+
+        let resolved = manifest.plugins.resolved();
+        let names: Vec<&str> = resolved.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["git", "zsh-autosuggestions", "fast-syntax-highlighting"]);
+        assert_eq!(resolved[0], PluginEntry::named("git"));
+        assert_eq!(resolved[1].git_ref.as_deref(), Some("v0.7.0"));
+        assert!(resolved[2].defer);
+        assert_eq!(resolved[2].options_for(&FrameworkType::Zinit), Some("atinit'zicompinit'"));
+
+        let reparsed = parse_manifest(&manifest.to_toml_string().unwrap()).unwrap();
+        assert_eq!(reparsed.plugins, manifest.plugins);
+    }
+
+    #[test]
+    fn test_plain_plugins_not_serialized_as_entries() {
+        let info = create_test_framework_info();
+        let manifest = Manifest::from_framework_info("test", &info);
+        let toml_str = manifest.to_toml_string().unwrap();
+
+        assert!(!toml_str.contains("[[plugins.entry]]"));
+    }
+
+    #[test]
+    fn test_plugin_entry_repo_for() {
+        // Registry lookup when no source is given
+        let entry = PluginEntry::named("zsh-autosuggestions");
+        assert_eq!(
+            entry.repo_for(&FrameworkType::Zap).as_deref(),
+            Some("zsh-users/zsh-autosuggestions")
+        );
+
+        // Explicit source overrides the registry for every framework
+        let entry = PluginEntry {
+            source: Some("me/my-fork".to_string()),
+            ..PluginEntry::named("zsh-autosuggestions")
+        };
+        assert_eq!(entry.repo_for(&FrameworkType::Zap).as_deref(), Some("me/my-fork"));
+        assert_eq!(entry.repo_for(&FrameworkType::Zinit).as_deref(), Some("me/my-fork"));
+
+        assert_eq!(PluginEntry::named("not-in-registry").repo_for(&FrameworkType::Zap), None);
+    }
+
+    #[test]
+    fn test_validate_plugin_entry_errors() {
+        let info = create_test_framework_info();
+
+        let mut manifest = Manifest::from_framework_info("test", &info);
+        manifest.plugins.entry = vec![PluginEntry::named("")];
+        assert!(manifest.validate().is_err());
+
+        manifest.plugins.entry = vec![PluginEntry::named("git"), PluginEntry::named("git")];
+        let err = manifest.validate().unwrap_err().to_string();
+        assert!(err.contains("more than one"));
+
+        manifest.plugins.entry = vec![PluginEntry {
+            git_ref: Some("main; rm -rf ~".to_string()),
+            ..PluginEntry::named("git")
+        }];
+        let err = manifest.validate().unwrap_err().to_string();
+        assert!(err.contains("invalid ref"));
+
+        let mut entry = PluginEntry::named("git");
+        entry.options.insert("oh-my-zsh".to_string(), "--foo".to_string());
+        manifest.plugins.entry = vec![entry];
+        let err = manifest.validate().unwrap_err().to_string();
+        assert!(err.contains("options for 'oh-my-zsh'"));
+
+        // oh-my-zsh only pins plugins it clones from a source
+        manifest.plugins.entry = vec![PluginEntry {
+            git_ref: Some("v1.0".to_string()),
+            ..PluginEntry::named("git")
+        }];
+        let problems = manifest.check();
+        assert_eq!(problems[0].field.as_deref(), Some("plugins.entry[0].ref"));
+        assert!(!problems[0].is_error());
+        manifest.plugins.entry[0].source = Some("user/git".to_string());
+        assert!(manifest.check().is_empty());
+    }
+
+    #[test]
+    fn test_merge_manifest_tables_plugin_entries_by_name() {
+        let base: toml::Table = toml::from_str(
+            r#"
+[profile]
+name = "base"
+framework = "zinit"
+created = "2025-01-01T00:00:00Z"
+
+[[plugins.entry]]
+name = "fzf-tab"
+ref = "v1"
+
+[[plugins.entry]]
+name = "zsh-autosuggestions"
+"#,
+        )
+        .unwrap();
+        let child: toml::Table = toml::from_str(
+            r#"
+[profile]
+name = "child"
+extends = "base"
+
+[[plugins.entry]]
+name = "fzf-tab"
+ref = "v2"
+"#,
+        )
+        .unwrap();
+
+        let merged = manifest_from_table(merge_manifest_tables(base, child)).unwrap();
+
+        assert_eq!(merged.profile.name, "child");
+        assert_eq!(merged.profile.framework, "zinit");
+        assert_eq!(merged.plugins.entry.len(), 2);
+        assert_eq!(merged.plugins.entry[0].git_ref.as_deref(), Some("v2"));
+        // Creation time belongs to the child, not the parent
+        assert_ne!(merged.profile.created.to_rfc3339(), "2025-01-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn test_merge_manifest_tables_env_secret_replaces_parent() {
+        let base: toml::Table =
+            toml::from_str("[profile]\nname = \"base\"\nframework = \"zinit\"\n\n[env]\nTOKEN = { command = \"pass show token\" }\n").unwrap();
+        let child: toml::Table =
+            toml::from_str("[profile]\nname = \"child\"\nextends = \"base\"\n\n[env]\nTOKEN = { file = \"~/.token\" }\n").unwrap();
+
+        let merged = manifest_from_table(merge_manifest_tables(base, child)).unwrap();
+
+        assert_eq!(merged.env["TOKEN"], EnvValue::Secret(SecretSource::File("~/.token".to_string())));
+    }
+
+    #[test]
+    fn test_parse_manifest_with_path() {
+        let toml = r#"
+[profile]
+name = "test"
+framework = "zap"
+
+[path]
+prepend = ["~/.local/bin", "$GOPATH/bin"]
+append = ["/opt/tools/bin"]
+skip_missing = true
+        "#;
+
+        let manifest = parse_manifest(toml).expect("Should parse path");
+        assert_eq!(manifest.path.prepend, vec!["~/.local/bin", "$GOPATH/bin"]);
+        assert_eq!(manifest.path.append, vec!["/opt/tools/bin"]);
+        assert!(manifest.path.skip_missing);
+        manifest.validate().expect("Path should validate");
+
+        let reparsed = parse_manifest(&manifest.to_toml_string().unwrap()).unwrap();
+        assert_eq!(reparsed.path, manifest.path);
+    }
+
+    #[test]
+    fn test_validate_path_rejects_lists_and_substitution() {
+        let info = create_test_framework_info();
+        for entry in ["", "/usr/bin:/bin", "$(brew --prefix)/bin", "`pwd`"] {
+            let mut manifest = Manifest::from_framework_info("test", &info);
+            manifest.path.append = vec![entry.to_string()];
+            assert!(manifest.validate().is_err(), "'{entry}' should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_validate_option_set_and_unset() {
+        let info = create_test_framework_info();
+        let mut manifest = Manifest::from_framework_info("test", &info);
+        manifest.options.set = vec!["AUTO_CD".to_string()];
+        manifest.options.unset = vec!["autocd".to_string()];
+
+        let result = manifest.validate();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("both set and unset"));
+
+        // NO_BEEP in set and BEEP in unset agree with each other
+        manifest.options.set = vec!["NO_BEEP".to_string()];
+        manifest.options.unset = vec!["BEEP".to_string()];
+        assert!(manifest.validate().is_ok());
     }
 
     #[test]
@@ -1302,4 +3316,493 @@ modified = "2025-11-01T10:00:00Z"
             }
         }
     }
+
+    #[test]
+    fn test_migrate_legacy_theme_to_prompt_mode() {
+        let mut table = parse_manifest_table(
+            r#"
+[profile]
+name = "old"
+framework = "oh-my-zsh"
+theme = "agnoster"
+"#,
+        )
+        .unwrap();
+
+        let report = migrate_table(&mut table).unwrap();
+        assert_eq!(report.from_version, 1);
+        assert_eq!(report.to_version, CURRENT_SCHEMA_VERSION);
+        assert!(!report.is_up_to_date());
+        assert_eq!(report.changes.len(), 2);
+
+        let profile = table["profile"].as_table().unwrap();
+        assert!(!profile.contains_key("theme"));
+        assert_eq!(profile["prompt_mode"].as_str(), Some("framework_theme"));
+        assert_eq!(profile["framework_theme"].as_str(), Some("agnoster"));
+        assert_eq!(
+            table["schema_version"].as_integer(),
+            Some(CURRENT_SCHEMA_VERSION as i64)
+        );
+
+        // Migrating again is a no-op
+        let report = migrate_table(&mut table).unwrap();
+        assert!(report.is_up_to_date());
+        assert!(report.changes.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_drops_theme_superseded_by_prompt_mode() {
+        let mut table = parse_manifest_table(
+            r#"
+[profile]
+name = "old"
+framework = "zap"
+theme = "ignored"
+prompt_mode = "prompt_engine"
+prompt_engine = "starship"
+"#,
+        )
+        .unwrap();
+
+        migrate_table(&mut table).unwrap();
+        let profile = table["profile"].as_table().unwrap();
+        assert!(!profile.contains_key("theme"));
+        assert!(!profile.contains_key("framework_theme"));
+        assert_eq!(profile["prompt_engine"].as_str(), Some("starship"));
+    }
+
+    #[test]
+    fn test_schema_version_rejects_newer_and_invalid_versions() {
+        let newer = format!(
+            "schema_version = {}\n[profile]\nname = \"x\"\nframework = \"zap\"\n",
+            CURRENT_SCHEMA_VERSION + 1
+        );
+        let err = parse_manifest(&newer).unwrap_err().to_string();
+        assert!(err.contains("Upgrade zprof"), "unexpected error: {err}");
+
+        let invalid = "schema_version = \"two\"\n[profile]\nname = \"x\"\nframework = \"zap\"\n";
+        let err = parse_manifest(invalid).unwrap_err().to_string();
+        assert!(err.contains("positive integer"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn test_new_manifests_are_written_with_current_schema() {
+        let manifest = Manifest::from_framework_info("test", &create_test_framework_info());
+        let toml = manifest.to_toml_string().unwrap();
+        assert!(toml.starts_with(&format!("schema_version = {CURRENT_SCHEMA_VERSION}\n")));
+
+        let table = parse_manifest_table(&toml).unwrap();
+        assert_eq!(schema_version(&table).unwrap(), CURRENT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_migrated_document_roundtrip() {
+        let mut document = ManifestDocument::parse(
+            "# mine\n[profile]\nname = \"old\"\nframework = \"zimfw\"\ntheme = \"x\"\n\n[env]\nEDITOR = \"vim\"\n",
+        )
+        .unwrap();
+        let mut table = document.table().unwrap();
+        migrate_table(&mut table).unwrap();
+        document.set_table(&table);
+
+        let written = document.to_string();
+        assert!(written.starts_with("schema_version = "));
+        assert!(written.contains("\n# mine\n[profile]\n"));
+        let manifest = parse_manifest(&written).unwrap();
+        assert_eq!(manifest.profile.theme(), "x");
+        assert_eq!(manifest.env.get("EDITOR").and_then(EnvValue::as_literal), Some("vim"));
+    }
+
+    #[test]
+    fn test_write_to_file_adds_schema_directive() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("profile.toml");
+        let manifest = Manifest::from_framework_info("test", &create_test_framework_info());
+
+        manifest.write_to_file(&path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(&format!("{}\n", schema::SCHEMA_DIRECTIVE)));
+        assert_eq!(parse_manifest(&content).unwrap(), manifest);
+    }
+
+    #[test]
+    fn test_write_to_file_keeps_comments_in_existing_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("profile.toml");
+        let mut manifest = Manifest::from_framework_info("test", &create_test_framework_info());
+        manifest.write_to_file(&path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let commented = content.replace("[profile]", "# Why this profile exists\n[profile]");
+        std::fs::write(&path, commented).unwrap();
+
+        manifest.plugins.enabled.push("docker".to_string());
+        manifest.write_to_file(&path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("# Why this profile exists\n[profile]"));
+        assert!(content.starts_with(&format!("{}\n", schema::SCHEMA_DIRECTIVE)));
+        assert_eq!(parse_manifest(&content).unwrap(), manifest);
+    }
+
+    #[test]
+    fn test_parse_manifest_with_keys() {
+        let manifest = parse_manifest(
+            r#"
+[profile]
+name = "vi-user"
+framework = "zinit"
+
+[keys]
+mode = "vi"
+widgets = ["edit-command-line"]
+
+[[keys.bind]]
+keys = "^R"
+widget = "fzf-history-widget"
+
+[[keys.bind]]
+keys = '\e[A'
+widget = "history-substring-search-up"
+keymap = "viins"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.keys.mode, Some(KeyMode::Vi));
+        assert_eq!(manifest.keys.bind.len(), 2);
+        assert_eq!(manifest.keys.bind[1].keys, "\\e[A");
+        assert_eq!(manifest.keys.bind[1].keymap.as_deref(), Some("viins"));
+        manifest.validate().unwrap();
+
+        let reparsed = parse_manifest(&manifest.to_toml_string().unwrap()).unwrap();
+        assert_eq!(reparsed.keys, manifest.keys);
+    }
+
+    #[test]
+    fn test_validate_rejects_malformed_key_bindings() {
+        let base = parse_manifest("[profile]\nname = \"k\"\nframework = \"zap\"\n").unwrap();
+        let binding = |keys: &str, widget: &str, keymap: Option<&str>| KeyBinding {
+            keys: keys.to_string(),
+            widget: widget.to_string(),
+            keymap: keymap.map(str::to_string),
+        };
+
+        for (bind, expected) in [
+            (binding("^", "beginning-of-line", None), "malformed key sequence"),
+            (binding("\\q", "beginning-of-line", None), "unknown escape"),
+            (binding("^A", "foo; rm -rf ~", None), "invalid widget name"),
+            (binding("^A", "beginning-of-line", Some("vimode")), "unknown keymap"),
+        ] {
+            let mut manifest = base.clone();
+            manifest.keys.bind = vec![bind];
+            let err = manifest.validate().unwrap_err().to_string();
+            assert!(err.contains(expected), "{err}");
+        }
+
+        let err = parse_manifest("[profile]\nname = \"k\"\nframework = \"zap\"\n[keys]\nmode = \"vim\"\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("vim"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_manifest_with_completion() {
+        let manifest = parse_manifest(
+            r#"
+[profile]
+name = "completion"
+framework = "zinit"
+
+[completion]
+matchers = ["m:{a-z}={A-Z}", "r:|=*"]
+menu_select = true
+cache = true
+fpath = ["~/.zfunc"]
+fast_init = true
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.completion.matchers.len(), 2);
+        assert!(manifest.completion.menu_select && manifest.completion.cache && manifest.completion.fast_init);
+        assert_eq!(manifest.completion.fpath, vec!["~/.zfunc"]);
+        manifest.validate().unwrap();
+
+        let reparsed = parse_manifest(&manifest.to_toml_string().unwrap()).unwrap();
+        assert_eq!(reparsed.completion, manifest.completion);
+
+        assert!(manifest.check().is_empty());
+        let mut ignored = manifest.clone();
+        ignored.profile.framework = "oh-my-zsh".to_string();
+        let problems = ignored.check();
+        assert_eq!(problems[0].field.as_deref(), Some("completion.fast_init"));
+        assert!(!problems[0].is_error());
+
+        let mut invalid = manifest.clone();
+        invalid.completion.fpath = vec!["~/.zfunc:/usr/share/zsh".to_string()];
+        let err = invalid.validate().unwrap_err().to_string();
+        assert!(err.contains("completion.fpath[0]"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_manifest_with_secret_env() {
+        let manifest = parse_manifest(
+            r#"
+[profile]
+name = "secrets"
+framework = "zinit"
+
+[env]
+EDITOR = "vim"
+GH_TOKEN = { command = "pass show gh/token" }
+OPENAI_API_KEY = { file = "~/.secrets/openai" }
+NPM_TOKEN = { keyring = "npm" }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.env["EDITOR"], EnvValue::from("vim"));
+        assert_eq!(
+            manifest.env["GH_TOKEN"],
+            EnvValue::Secret(SecretSource::Command("pass show gh/token".to_string()))
+        );
+        assert_eq!(manifest.env["NPM_TOKEN"].to_string(), "<keyring: npm>");
+        manifest.validate().unwrap();
+
+        let reparsed = parse_manifest(&manifest.to_toml_string().unwrap()).unwrap();
+        assert_eq!(reparsed.env, manifest.env);
+
+        let mut invalid = manifest.clone();
+        invalid
+            .env
+            .insert("OPENAI_API_KEY".to_string(), EnvValue::Secret(SecretSource::File("$(whoami)".to_string())));
+        let err = invalid.validate().unwrap_err().to_string();
+        assert!(err.contains("env.OPENAI_API_KEY"), "{err}");
+
+        // Unknown secret sources are rejected rather than treated as literals
+        assert!(parse_manifest("[profile]\nname = \"x\"\nframework = \"zinit\"\n\n[env]\nTOKEN = { vault = \"x\" }\n").is_err());
+    }
+
+    #[test]
+    fn test_apply_conditions_merges_matching_overlays() {
+        let manifest = parse_manifest(
+            r#"
+[profile]
+name = "synced"
+framework = "zinit"
+
+[plugins]
+enabled = ["git"]
+
+[env]
+EDITOR = "vim"
+
+[when.os."linux".plugins]
+enabled = ["systemd"]
+
+[when.os."macos".plugins]
+enabled = ["macos"]
+
+[when.host."work-laptop".env]
+AWS_PROFILE = "work"
+
+[when.env."CI".options]
+unset = ["BEEP"]
+"#,
+        )
+        .unwrap();
+        manifest.validate().unwrap();
+
+        let machine = Machine {
+            host: "work-laptop.local".to_string(),
+            os: "linux".to_string(),
+            env: HashMap::new(),
+        };
+        let applied = manifest.clone().apply_conditions(&machine).unwrap();
+
+        assert_eq!(applied.plugins.enabled, vec!["git", "systemd"]);
+        assert_eq!(applied.env["AWS_PROFILE"], EnvValue::from("work"));
+        assert_eq!(applied.env["EDITOR"], EnvValue::from("vim"));
+        assert!(applied.options.is_empty());
+        assert_eq!(applied.when, manifest.when);
+
+        // With resolve = "shell" the overlays are left for the generator
+        let mut shell = manifest.clone();
+        shell.when.os.clear();
+        shell.when.resolve = WhenResolve::Shell;
+        assert_eq!(shell.clone().apply_conditions(&machine).unwrap(), shell);
+    }
+
+    #[test]
+    fn test_validate_when_sections() {
+        let base = "[profile]\nname = \"x\"\nframework = \"zinit\"\n\n";
+
+        let err = parse_manifest(&format!("{base}[when.os.\"windows\".env]\nA = \"b\"\n"))
+            .unwrap()
+            .validate()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("when.os.\"windows\"") && err.contains("macos"), "{err}");
+
+        let err = parse_manifest(&format!("{base}[when]\nresolve = \"shell\"\n\n[when.env.\"CI\".plugins]\nenabled = [\"git\"]\n"))
+            .unwrap()
+            .validate()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("can't be switched at shell start"), "{err}");
+
+        // Overlay contents are validated as if applied
+        let err = parse_manifest(&format!("{base}[when.host.\"dev-vm\".env]\n\"BAD-KEY\" = \"x\"\n"))
+            .unwrap()
+            .validate()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("BAD-KEY") && err.contains("In [when.host.\"dev-vm\"]"), "{err}");
+
+        // Unknown overlay sections are a parse error
+        assert!(parse_manifest(&format!("{base}[when.os.\"linux\".profile]\nname = \"y\"\n")).is_err());
+    }
+
+    #[test]
+    fn test_parse_manifest_with_snippets() {
+        let manifest = parse_manifest(
+            r#"
+[profile]
+name = "snippets"
+framework = "zap"
+
+[[snippets]]
+code = "zstyle ':omz:update' mode disabled"
+phase = "pre-framework"
+
+[[snippets]]
+file = "snippets/work.zsh"
+phase = "post-plugins"
+"#,
+        )
+        .unwrap();
+        manifest.validate().unwrap();
+
+        assert_eq!(manifest.snippets.len(), 2);
+        assert_eq!(manifest.snippets_for(SnippetPhase::PostPlugins).count(), 1);
+        assert_eq!(manifest.snippets[1].file.as_deref(), Some("snippets/work.zsh"));
+
+        let reparsed = parse_manifest(&manifest.to_toml_string().unwrap()).unwrap();
+        assert_eq!(reparsed.snippets, manifest.snippets);
+
+        for (code, file) in [(Some("true"), Some("x.zsh")), (None, None), (None, Some("../other/x.zsh")), (None, Some("/etc/zshrc"))] {
+            let mut invalid = manifest.clone();
+            invalid.snippets = vec![Snippet {
+                code: code.map(str::to_string),
+                file: file.map(str::to_string),
+                phase: SnippetPhase::Zshenv,
+            }];
+            let err = invalid.validate().unwrap_err().to_string();
+            assert!(err.contains("snippets[0]"), "{err}");
+        }
+
+        assert!(parse_manifest("[profile]\nname = \"x\"\nframework = \"zap\"\n\n[[snippets]]\ncode = \"true\"\nphase = \"post-theme\"\n").is_err());
+    }
+
+    #[test]
+    fn test_anchor_snippet_files_points_at_parent() {
+        let mut table: toml::Table = toml::from_str(
+            "[[snippets]]\nfile = \"base.zsh\"\nphase = \"zshenv\"\n\n[[snippets]]\nfile = \"~/.zsh-profiles/profiles/root/x.zsh\"\nphase = \"zshenv\"\n",
+        )
+        .unwrap();
+        anchor_snippet_files(&mut table, "base");
+
+        let files: Vec<&str> = table["snippets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["file"].as_str().unwrap())
+            .collect();
+        assert_eq!(files, vec!["~/.zsh-profiles/profiles/base/base.zsh", "~/.zsh-profiles/profiles/root/x.zsh"]);
+    }
+
+    #[test]
+    fn test_anchor_hook_scripts_points_at_parent() {
+        let mut table: toml::Table = toml::from_str(
+            "[hooks]\non_activate = \"hooks/up.sh\"\non_deactivate = \"./down.zsh\"\non_shell_start = \"echo hi\"\ntimeout = 5\n",
+        )
+        .unwrap();
+        anchor_hook_scripts(&mut table, "base");
+
+        let hooks = table["hooks"].as_table().unwrap();
+        assert_eq!(hooks["on_activate"].as_str(), Some("~/.zsh-profiles/profiles/base/hooks/up.sh"));
+        assert_eq!(hooks["on_deactivate"].as_str(), Some("~/.zsh-profiles/profiles/base/down.zsh"));
+        assert_eq!(hooks["on_shell_start"].as_str(), Some("echo hi"));
+        assert_eq!(hooks["timeout"].as_integer(), Some(5));
+    }
+
+    #[test]
+    fn test_check_collects_every_problem() {
+        let manifest = parse_manifest(
+            "[profile]\nname = \"work\"\nframework = \"oh-my-zhs\"\nprompt_mode = \"prompt_engine\"\nprompt_engine = \"starshp\"\n\n[plugins]\nenabled = [\"git\", \"zsh-autosugestions\"]\n\n[options]\nset = [\"AUTOCDD\"]\n",
+        )
+        .unwrap();
+
+        let problems = manifest.check();
+        let summary: Vec<(bool, &str, Option<&str>)> = problems
+            .iter()
+            .map(|d| (d.is_error(), d.field.as_deref().unwrap(), d.suggestion.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (true, "profile.framework", Some("oh-my-zsh")),
+                (false, "plugins.enabled[1]", Some("zsh-autosuggestions")),
+                (true, "profile.prompt_engine", Some("starship")),
+                (true, "options.set", None),
+            ]
+        );
+
+        // validate() still fails on the first error, with the suggestion
+        let err = manifest.validate().unwrap_err().to_string();
+        assert!(err.starts_with("Validation error: profile.framework must be one of"), "{err}");
+        assert!(err.contains("Did you mean 'oh-my-zsh'?"), "{err}");
+    }
+
+    #[test]
+    fn test_check_reports_base_problems_once_with_overlays() {
+        let manifest = parse_manifest(
+            "[profile]\nname = \"work\"\nframework = \"zap\"\n\n[path]\nprepend = [\"\"]\n\n[when.os.\"linux\".path]\nappend = [\"a:b\"]\n\n[when.os.\"macos\".env]\nA = \"b\"\n",
+        )
+        .unwrap();
+
+        let fields: Vec<String> = manifest.check().into_iter().filter_map(|d| d.field).collect();
+        assert_eq!(fields, vec!["path.prepend[0]", "when.os.\"linux\".path.append[0]"]);
+    }
+
+    #[test]
+    fn test_diagnose_locates_problems_in_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manifest_path = temp_dir.path().join("profile.toml");
+
+        std::fs::write(
+            &manifest_path,
+            "[profile]\nname = \"work\"\nframework = \"zinit\"\n\n[env]\n\"MY-VAR\" = \"x\"\n\n[when.os.linx.env]\nA = \"b\"\n",
+        )
+        .unwrap();
+        let problems = diagnose(&manifest_path).unwrap();
+        let locations: Vec<(usize, usize)> = problems
+            .iter()
+            .map(|d| d.location.as_ref().map(|l| (l.line, l.column)).unwrap())
+            .collect();
+        assert_eq!(locations, vec![(6, 1), (8, 10)]);
+        assert_eq!(problems[1].suggestion.as_deref(), Some("linux"));
+
+        // Syntax errors are all reported, each at its own position
+        std::fs::write(&manifest_path, "[profile\nname = = \"work\"\n").unwrap();
+        let problems = diagnose(&manifest_path).unwrap();
+        assert!(problems.len() >= 2);
+        assert_eq!(problems[0].location.as_ref().unwrap().line, 1);
+
+        // A valid manifest has nothing to report
+        std::fs::write(&manifest_path, "[profile]\nname = \"work\"\nframework = \"zinit\"\n").unwrap();
+        assert!(diagnose(&manifest_path).unwrap().is_empty());
+    }
 }
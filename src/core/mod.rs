@@ -1,5 +1,17 @@
+pub mod activation;
+pub mod auto;
 pub mod backup_manifest;
+pub mod bench;
+pub mod conditions;
 pub mod config;
+pub mod copy;
+pub mod diagnostics;
+pub mod diff;
+pub mod doctor;
+pub mod document;
 pub mod filesystem;
+pub mod info;
+pub mod lint;
 pub mod manifest;
 pub mod profile;
+pub mod schema;
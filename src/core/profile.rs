@@ -1,4 +1,4 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -30,8 +30,12 @@ struct ProfileManifest {
 #[derive(Debug, Serialize, Deserialize)]
 struct ProfileMetadata {
     name: String,
+    /// Empty when inherited through `extends`; filled in by `read_profile_manifest`
+    #[serde(default)]
     framework: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    extends: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     theme: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     created: Option<String>,
@@ -103,9 +107,13 @@ fn read_profile_manifest(path: &Path) -> Result<ProfileManifest> {
     let content = fs::read_to_string(path)
         .with_context(|| format!("Failed to read profile manifest from {}", path.display()))?;
 
-    let manifest: ProfileManifest = toml::from_str(&content)
+    let mut manifest: ProfileManifest = toml::from_str(&content)
         .with_context(|| format!("Failed to parse profile manifest at {}", path.display()))?;
 
+    if manifest.profile.framework.is_empty() && manifest.profile.extends.is_some() {
+        manifest.profile.framework = crate::core::manifest::resolve_manifest(path)?.profile.framework;
+    }
+
     Ok(manifest)
 }
 
@@ -147,6 +155,35 @@ pub fn load_profile_metadata(profile_name: &str) -> Result<ProfileMetadataFull>
     })
 }
 
+/// Validate profile name against allowed pattern
+///
+/// Profile names must:
+/// - Be non-empty
+/// - Contain only alphanumeric characters and hyphens
+/// - Not contain path traversal attempts
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        bail!("✗ Error: Profile name cannot be empty");
+    }
+
+    // Check for valid characters (alphanumeric and hyphens)
+    let valid_pattern = regex::Regex::new(r"^[a-zA-Z0-9\-]+$").unwrap();
+    if !valid_pattern.is_match(name) {
+        bail!(
+            "✗ Error: Invalid profile name '{name}'\n  → Use alphanumeric characters and hyphens only"
+        );
+    }
+
+    // Check for path traversal attempts
+    if name.contains("..") || name.contains('/') || name.contains('\\') {
+        bail!(
+            "✗ Error: Invalid profile name '{name}'\n  → Profile names cannot contain path separators"
+        );
+    }
+
+    Ok(())
+}
+
 /// Get the path to a profile directory, returning error if it doesn't exist
 pub fn get_profile_path(profile_name: &str) -> Result<PathBuf> {
     let profiles_dir = get_profiles_dir()?;
@@ -0,0 +1,170 @@
+//! JSON Schema for profile.toml
+//!
+//! The schema is derived from the `Manifest` types and then filled in with
+//! the values zprof knows about (frameworks, prompt engines, and the plugin
+//! and theme registries), so editors with Taplo/JSON Schema support can
+//! complete and check manifests while they are being written.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::core::filesystem;
+use crate::core::manifest::{self, Manifest};
+use crate::frameworks::{plugin::PLUGIN_REGISTRY, theme::THEME_REGISTRY};
+use crate::prompts::engine::PROMPT_ENGINE_NAMES;
+use crate::shell::keys::KEYMAPS;
+
+/// File name of the schema, written to the root of `~/.zsh-profiles`
+pub const SCHEMA_FILE_NAME: &str = "profile.schema.json";
+
+/// Taplo directive placed at the top of profile.toml
+///
+/// The path is relative to `profiles/<name>/profile.toml`, so it keeps
+/// working if the profiles directory is moved or synced between machines.
+pub const SCHEMA_DIRECTIVE: &str = "#:schema ../../profile.schema.json";
+
+/// Build the JSON Schema for profile.toml
+pub fn manifest_schema() -> Value {
+    let mut schema = schemars::schema_for!(Manifest).to_value();
+
+    schema["title"] = json!("zprof profile.toml");
+    schema["description"] = json!("Profile manifest for zprof (https://github.com/annabarnes1138/zprof)");
+
+    let version = &mut schema["properties"]["schema_version"];
+    version["minimum"] = json!(1);
+    version["maximum"] = json!(manifest::CURRENT_SCHEMA_VERSION);
+
+    let plugin_names: Vec<&str> = PLUGIN_REGISTRY.iter().map(|p| p.name).collect();
+    let theme_names: Vec<&str> = THEME_REGISTRY.iter().map(|t| t.name).collect();
+
+    let defs = &mut schema["$defs"];
+
+    let profile = &mut defs["ProfileSection"];
+    let framework = &mut profile["properties"]["framework"];
+    framework["enum"] = json!(manifest::SUPPORTED_FRAMEWORKS);
+    remove_key(framework, "default");
+    // Creation times default to "now", which has no place in a static schema
+    for key in ["created", "modified"] {
+        remove_key(&mut profile["properties"][key], "default");
+    }
+
+    // The prompt settings are a flattened, tagged enum: one branch per mode
+    if let Some(modes) = profile["oneOf"].as_array_mut() {
+        for mode in modes.iter_mut() {
+            let properties = &mut mode["properties"];
+            if properties.get("prompt_engine").is_some() {
+                properties["prompt_engine"]["enum"] = json!(PROMPT_ENGINE_NAMES);
+            }
+            if properties.get("framework_theme").is_some() {
+                properties["framework_theme"] = known_names(&theme_names);
+            }
+        }
+        // prompt_mode may be left out entirely (no theme, or inherited via extends)
+        modes.push(json!({
+            "description": "No prompt settings",
+            "not": { "required": ["prompt_mode"] }
+        }));
+    }
+
+    defs["PluginsSection"]["properties"]["enabled"]["items"] = known_names(&plugin_names);
+    defs["PluginEntry"]["properties"]["name"] = known_names(&plugin_names);
+
+    let mut keymaps: Vec<Value> = KEYMAPS.iter().map(|k| json!(k)).collect();
+    keymaps.push(Value::Null);
+    defs["KeyBinding"]["properties"]["keymap"]["enum"] = Value::Array(keymaps);
+
+    schema
+}
+
+/// Schema for a string that is usually, but not necessarily, a registry name
+///
+/// Unknown names are still valid (custom plugins and themes are allowed), but
+/// the enum branch gives editors the list to complete from.
+fn known_names(names: &[&str]) -> Value {
+    json!({
+        "anyOf": [
+            { "enum": names },
+            { "type": "string" }
+        ]
+    })
+}
+
+fn remove_key(value: &mut Value, key: &str) {
+    if let Some(object) = value.as_object_mut() {
+        object.remove(key);
+    }
+}
+
+/// Render the schema as pretty-printed JSON
+pub fn manifest_schema_string() -> Result<String> {
+    serde_json::to_string_pretty(&manifest_schema()).context("Failed to serialize JSON Schema")
+}
+
+/// Write the schema to `~/.zsh-profiles/profile.schema.json`
+///
+/// Rewritten on every call so the file always matches the installed zprof.
+pub fn write_schema_file() -> Result<PathBuf> {
+    let schema_path = filesystem::get_zprof_dir()?.join(SCHEMA_FILE_NAME);
+    let content = manifest_schema_string()?;
+
+    fs::write(&schema_path, content + "\n")
+        .with_context(|| format!("Failed to write JSON Schema to {}", schema_path.display()))?;
+
+    Ok(schema_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_lists_frameworks_and_engines() {
+        let schema = manifest_schema();
+        let profile = &schema["$defs"]["ProfileSection"];
+
+        let frameworks = profile["properties"]["framework"]["enum"].as_array().unwrap();
+        assert_eq!(frameworks.len(), 5);
+        assert!(frameworks.contains(&json!("oh-my-zsh")));
+
+        let engine_mode = profile["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|m| m["properties"]["prompt_engine"].is_object())
+            .unwrap();
+        assert!(engine_mode["properties"]["prompt_engine"]["enum"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("starship")));
+    }
+
+    #[test]
+    fn test_schema_lists_registry_plugins_and_themes() {
+        let schema = manifest_schema();
+
+        let plugins = &schema["$defs"]["PluginsSection"]["properties"]["enabled"]["items"]["anyOf"][0]["enum"];
+        assert_eq!(plugins.as_array().unwrap().len(), PLUGIN_REGISTRY.len());
+        assert!(plugins.as_array().unwrap().contains(&json!("zsh-autosuggestions")));
+
+        let theme_mode = schema["$defs"]["ProfileSection"]["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|m| m["properties"]["framework_theme"].is_object())
+            .unwrap();
+        let themes = &theme_mode["properties"]["framework_theme"]["anyOf"][0]["enum"];
+        assert_eq!(themes.as_array().unwrap().len(), THEME_REGISTRY.len());
+    }
+
+    #[test]
+    fn test_schema_is_deterministic() {
+        // No timestamps or other run-dependent defaults
+        assert_eq!(manifest_schema_string().unwrap(), manifest_schema_string().unwrap());
+        assert_eq!(
+            manifest_schema()["properties"]["schema_version"]["maximum"],
+            json!(manifest::CURRENT_SCHEMA_VERSION)
+        );
+    }
+}
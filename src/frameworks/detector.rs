@@ -17,6 +17,15 @@ pub enum FrameworkType {
 }
 
 impl FrameworkType {
+    /// Every supported framework, in the order zprof lists them
+    pub const ALL: &'static [FrameworkType] = &[
+        FrameworkType::OhMyZsh,
+        FrameworkType::Zimfw,
+        FrameworkType::Prezto,
+        FrameworkType::Zinit,
+        FrameworkType::Zap,
+    ];
+
     /// Returns the human-readable name of the framework
     pub fn name(&self) -> &str {
         match self {
@@ -27,6 +36,18 @@ impl FrameworkType {
             FrameworkType::Zap => "zap",
         }
     }
+
+    /// Parse a framework from the name used in profile.toml
+    pub fn from_name(name: &str) -> Option<FrameworkType> {
+        match name {
+            "oh-my-zsh" => Some(FrameworkType::OhMyZsh),
+            "zimfw" => Some(FrameworkType::Zimfw),
+            "prezto" => Some(FrameworkType::Prezto),
+            "zinit" => Some(FrameworkType::Zinit),
+            "zap" => Some(FrameworkType::Zap),
+            _ => None,
+        }
+    }
 }
 
 /// Information about a detected framework installation
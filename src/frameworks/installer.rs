@@ -5,11 +5,12 @@
 
 use anyhow::{Context, Result};
 use indicatif::{ProgressBar, ProgressStyle};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs;
 
+use crate::core::manifest::PluginsSection;
 use crate::frameworks::{FrameworkType};
-use crate::git::clone_repository;
+use crate::git::{checkout_ref, clone_repository};
 
 /// Wizard state containing all user selections for profile creation
 #[derive(Debug, Clone)]
@@ -89,6 +90,17 @@ pub fn install_framework(framework: &FrameworkType, profile_path: &Path) -> Resu
     }
 }
 
+/// Where `install_framework` clones a framework inside a profile directory
+pub fn framework_dir(framework: &FrameworkType, profile_path: &Path) -> PathBuf {
+    match framework {
+        FrameworkType::OhMyZsh => profile_path.join(".oh-my-zsh"),
+        FrameworkType::Zap => profile_path.join(".zap"),
+        FrameworkType::Zimfw => profile_path.join(".zim"),
+        FrameworkType::Prezto => profile_path.join(".zprezto"),
+        FrameworkType::Zinit => profile_path.join(".zinit").join("zinit.git"),
+    }
+}
+
 /// Install Oh-My-Zsh framework from GitHub
 fn install_oh_my_zsh(profile_path: &Path) -> Result<()> {
     let framework_dir = profile_path.join(".oh-my-zsh");
@@ -252,6 +264,65 @@ fn install_plugin(
     Ok(())
 }
 
+/// Clone plugins that have a custom `source` in profile.toml
+///
+/// zimfw, zinit and zap fetch plugins themselves from the generated
+/// declarations, so only oh-my-zsh (custom plugins directory) and prezto
+/// (contrib modules) need the repository cloned up front. Plugins that are
+/// already present are left alone, so this is cheap to call on every
+/// regenerate.
+pub fn install_plugin_sources(
+    framework: &FrameworkType,
+    plugins: &PluginsSection,
+    profile_path: &Path,
+) -> Result<()> {
+    let plugins_dir = match framework {
+        FrameworkType::OhMyZsh => profile_path.join(".oh-my-zsh/custom/plugins"),
+        FrameworkType::Prezto => profile_path.join(".zprezto/contrib"),
+        FrameworkType::Zimfw | FrameworkType::Zinit | FrameworkType::Zap => return Ok(()),
+    };
+
+    for plugin in plugins.resolved() {
+        let Some(source) = &plugin.source else {
+            continue;
+        };
+
+        let plugin_dir = plugins_dir.join(&plugin.name);
+        let already_installed = plugin_dir.join(".git").exists();
+        if already_installed {
+            log::debug!("Plugin {} already installed at {}", plugin.name, plugin_dir.display());
+            continue;
+        }
+
+        // Remove an empty placeholder left by install_plugin so the clone can proceed
+        if plugin_dir.exists() {
+            fs::remove_dir(&plugin_dir).with_context(|| {
+                format!("Plugin directory {} exists and is not empty", plugin_dir.display())
+            })?;
+        }
+
+        println!("→ Installing plugin {} from {source}...", plugin.name);
+        clone_repository(&git_clone_url(source), &plugin_dir, None)
+            .with_context(|| format!("Failed to clone plugin {}", plugin.name))?;
+
+        if let Some(git_ref) = &plugin.git_ref {
+            checkout_ref(&plugin_dir, git_ref)
+                .with_context(|| format!("Failed to pin plugin {} to {git_ref}", plugin.name))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Turn a `user/repo` shorthand into a GitHub clone URL; full URLs pass through
+fn git_clone_url(source: &str) -> String {
+    if source.contains("://") || source.starts_with("git@") {
+        source.to_string()
+    } else {
+        format!("https://github.com/{source}.git")
+    }
+}
+
 /// Install a prompt engine to the profile directory
 ///
 /// Currently supports Starship and Pure.
@@ -324,6 +395,38 @@ mod tests {
         // This is kept as documentation of expected behavior
     }
 
+    #[test]
+    fn test_git_clone_url() {
+        assert_eq!(
+            git_clone_url("zsh-users/zsh-autosuggestions"),
+            "https://github.com/zsh-users/zsh-autosuggestions.git"
+        );
+        assert_eq!(
+            git_clone_url("https://gitlab.com/me/plugin.git"),
+            "https://gitlab.com/me/plugin.git"
+        );
+        assert_eq!(git_clone_url("git@github.com:me/plugin.git"), "git@github.com:me/plugin.git");
+    }
+
+    #[test]
+    fn test_install_plugin_sources_skips_self_installing_frameworks() {
+        let temp_dir = TempDir::new().unwrap();
+        let plugins = PluginsSection {
+            enabled: vec![],
+            entry: vec![crate::core::manifest::PluginEntry {
+                name: "my-plugin".to_string(),
+                source: Some("me/my-plugin".to_string()),
+                ..Default::default()
+            }],
+        };
+
+        for framework in [FrameworkType::Zimfw, FrameworkType::Zinit, FrameworkType::Zap] {
+            install_plugin_sources(&framework, &plugins, temp_dir.path()).unwrap();
+        }
+
+        assert!(fs::read_dir(temp_dir.path()).unwrap().next().is_none());
+    }
+
     #[test]
     fn test_install_profile_creates_framework_and_plugins() {
         let _temp_dir = TempDir::new().unwrap();
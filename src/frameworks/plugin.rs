@@ -20,6 +20,11 @@ pub fn get_plugins_for_framework(framework: &FrameworkType) -> Vec<Plugin> {
         .collect()
 }
 
+/// Look up a plugin in the registry by name
+pub fn find_plugin(name: &str) -> Option<&'static Plugin> {
+    PLUGIN_REGISTRY.iter().find(|p| p.name == name)
+}
+
 /// Central plugin registry with full compatibility metadata
 pub const PLUGIN_REGISTRY: &[Plugin] = &[
     // === Core Utility Plugins (Recommended for most users) ===
@@ -97,6 +97,40 @@ pub fn clone_repository(
     Ok(repo)
 }
 
+/// Check out a branch, tag or commit in a shallow clone
+///
+/// Fetches just that ref from `origin` so it works on `--depth 1` clones
+/// made by `clone_repository`, then detaches HEAD at it.
+pub fn checkout_ref(repo_path: &Path, git_ref: &str) -> Result<()> {
+    if git_ref.is_empty() {
+        anyhow::bail!("Git ref cannot be empty");
+    }
+
+    if std::env::var("ZPROF_TEST_MODE").is_ok() {
+        log::info!("Test mode: Simulating checkout of {} in {}", git_ref, repo_path.display());
+        return Ok(());
+    }
+
+    for args in [
+        vec!["fetch", "--depth", "1", "origin", git_ref],
+        vec!["checkout", "--quiet", "FETCH_HEAD"],
+    ] {
+        let output = Command::new("git")
+            .args(&args)
+            .current_dir(repo_path)
+            .output()
+            .with_context(|| format!("Failed to execute git {}", args[0]))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Git {} of '{git_ref}' failed:\nStderr: {stderr}", args[0]);
+        }
+    }
+
+    log::info!("Checked out {} in {}", git_ref, repo_path.display());
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,6 +162,15 @@ mod tests {
         assert!(error_msg.contains("already exists"));
     }
 
+    #[test]
+    fn test_checkout_ref_empty() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = checkout_ref(temp_dir.path(), "");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("cannot be empty"));
+    }
+
     #[test]
     #[ignore]
     fn test_clone_real_repository() {
@@ -3,6 +3,9 @@ mod backup;
 mod cleanup;
 mod cli;
 mod core;
+// The binary only uses Nerd Font detection; the rest of the font registry is library API
+#[allow(dead_code, unused_imports)]
+mod fonts;
 mod frameworks;
 mod git;
 mod presets;
@@ -10,8 +13,10 @@ mod prompts;
 mod shell;
 mod tui;
 
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{bail, Result};
+use clap::{CommandFactory, Parser, Subcommand};
+
+use cli::format::OutputFormat;
 
 /// zprof - Manage multiple zsh profiles with ease
 ///
@@ -24,22 +29,42 @@ use clap::{Parser, Subcommand};
 Switch between configurations instantly, experiment safely, and share profiles.\n\n\
 Available via CLI (this tool) or GUI (run 'zprof gui' to launch graphical interface).")]
 struct Cli {
+    /// Output format for list, show, current, available, version and doctor
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Plain)]
+    format: OutputFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Debug, Subcommand)]
 enum Commands {
+    /// Explain which profile a directory switches to
+    Auto(cli::auto::AutoArgs),
     /// List available frameworks, plugins, and themes
     Available(cli::available::AvailableArgs),
+    /// Measure and compare shell startup time of profiles
+    Bench(cli::bench::BenchArgs),
+    /// Print a shell completion script
+    Completions(cli::completions::CompletionsArgs),
+    /// Copy a profile under a new name
+    Copy(cli::copy::CopyArgs),
     /// Create a new profile
     Create(cli::create::CreateArgs),
     /// Display the currently active profile
     Current(cli::current::CurrentArgs),
     /// Delete a profile
     Delete(cli::delete::DeleteArgs),
+    /// Compare two profiles, archives or GitHub repositories
+    Diff(cli::diff::DiffArgs),
+    /// Check zprof's shell integration and every profile for problems
+    Doctor(cli::doctor::DoctorArgs),
     /// Edit a profile's TOML manifest
     Edit(cli::edit::EditArgs),
+    /// Set a profile's prompt engine
+    Engine(cli::engine::EngineArgs),
+    /// Set or unset a profile's environment variables
+    Env(cli::env::EnvArgs),
     /// Export a profile to a .zprof archive
     Export(cli::export::ExportArgs),
     /// Launch the graphical user interface
@@ -49,14 +74,30 @@ enum Commands {
     Import(cli::import::ImportArgs),
     /// Initialize zprof directory structure
     Init(cli::init::InitArgs),
+    /// Check profile manifests against the plugin, theme and engine registries
+    Lint(cli::lint::LintArgs),
     /// List all available zsh profiles
     List(cli::list::ListArgs),
+    /// Upgrade profile.toml files to the current manifest schema
+    Migrate(cli::migrate::MigrateArgs),
+    /// Add, remove or list a profile's plugins
+    Plugin(cli::plugin::PluginArgs),
     /// Regenerate shell configuration files from profile.toml
     Regenerate(cli::regenerate::RegenerateArgs),
+    /// Rename a profile
+    Rename(cli::rename::RenameArgs),
     /// Restore original shell configuration (rollback zprof)
     Rollback(cli::rollback::RollbackArgs),
+    /// Run one command with a profile, without switching to it
+    Run(cli::run::RunArgs),
+    /// Print the JSON Schema for profile.toml
+    Schema(cli::schema::SchemaArgs),
+    /// Start zsh with a profile for this session only
+    Shell(cli::shell::ShellArgs),
     /// Show detailed information about a profile
     Show(cli::show::ShowArgs),
+    /// Set a profile's framework theme
+    Theme(cli::theme::ThemeArgs),
     /// Uninstall zprof and optionally restore shell configuration
     Uninstall(cli::uninstall::UninstallArgs),
     /// Switch to a different profile
@@ -65,29 +106,72 @@ enum Commands {
     Version(cli::version::VersionArgs),
 }
 
+impl Commands {
+    /// Whether the command can print JSON or TOML with `--format`
+    fn has_structured_output(&self) -> bool {
+        matches!(
+            self,
+            Commands::Available(_)
+                | Commands::Current(_)
+                | Commands::Doctor(_)
+                | Commands::List(_)
+                | Commands::Show(_)
+                | Commands::Version(_)
+        )
+    }
+}
+
 fn main() -> Result<()> {
     // Install panic hook to restore terminal on crashes
     tui::install_panic_hook();
 
+    // Answer TAB requests from the completion scripts before parsing
+    clap_complete::CompleteEnv::with_factory(Cli::command)
+        .var(cli::completions::COMPLETE_VAR)
+        .complete();
+
     let cli = Cli::parse();
+    let format = cli.format;
+    if !format.is_plain() && !cli.command.has_structured_output() {
+        bail!(
+            "✗ Error: --format only applies to list, show, current, available, version and doctor\n  \
+             → Drop --format to run this command"
+        );
+    }
 
     match cli.command {
-        Commands::Available(args) => cli::available::execute(args),
+        Commands::Auto(args) => cli::auto::execute(args),
+        Commands::Available(args) => cli::available::execute(args, format),
+        Commands::Bench(args) => cli::bench::execute(args),
+        Commands::Completions(args) => cli::completions::execute(args),
+        Commands::Copy(args) => cli::copy::execute(args),
         Commands::Create(args) => cli::create::execute(args),
-        Commands::Current(args) => cli::current::execute(args),
+        Commands::Current(args) => cli::current::execute(args, format),
         Commands::Delete(args) => cli::delete::execute(args),
+        Commands::Diff(args) => cli::diff::execute(args),
+        Commands::Doctor(args) => cli::doctor::execute(args, format),
         Commands::Edit(args) => cli::edit::execute(args),
+        Commands::Engine(args) => cli::engine::execute(args),
+        Commands::Env(args) => cli::env::execute(args),
         Commands::Export(args) => cli::export::execute(args),
         #[cfg(feature = "gui")]
         Commands::Gui(args) => cli::gui::execute(args),
         Commands::Import(args) => cli::import::execute(args),
         Commands::Init(args) => cli::init::execute(args),
-        Commands::List(args) => cli::list::execute(args),
+        Commands::Lint(args) => cli::lint::execute(args),
+        Commands::List(args) => cli::list::execute(args, format),
+        Commands::Migrate(args) => cli::migrate::execute(args),
+        Commands::Plugin(args) => cli::plugin::execute(args),
         Commands::Regenerate(args) => cli::regenerate::execute(args),
+        Commands::Rename(args) => cli::rename::execute(args),
         Commands::Rollback(args) => cli::rollback::execute(args),
-        Commands::Show(args) => cli::show::execute(args),
+        Commands::Run(args) => cli::run::execute(args),
+        Commands::Schema(args) => cli::schema::execute(args),
+        Commands::Shell(args) => cli::shell::execute(args),
+        Commands::Show(args) => cli::show::execute(args, format),
+        Commands::Theme(args) => cli::theme::execute(args),
         Commands::Uninstall(args) => cli::uninstall::execute(args),
         Commands::Use(args) => cli::use_cmd::execute(args),
-        Commands::Version(args) => cli::version::execute(args),
+        Commands::Version(args) => cli::version::execute(args, format),
     }
 }
@@ -25,6 +25,9 @@ pub enum PromptEngine {
     Spaceship,
 }
 
+/// Values accepted for `prompt_engine` in profile.toml
+pub const PROMPT_ENGINE_NAMES: &[&str] = &["starship", "powerlevel10k", "oh-my-posh", "pure", "spaceship"];
+
 /// Installation method for a prompt engine
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InstallMethod {
@@ -56,6 +59,18 @@ pub struct EngineMetadata {
 }
 
 impl PromptEngine {
+    /// The engine for a `prompt_engine` value from profile.toml
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "starship" => Some(PromptEngine::Starship),
+            "powerlevel10k" => Some(PromptEngine::Powerlevel10k),
+            "oh-my-posh" => Some(PromptEngine::OhMyPosh),
+            "pure" => Some(PromptEngine::Pure),
+            "spaceship" => Some(PromptEngine::Spaceship),
+            _ => None,
+        }
+    }
+
     /// Get metadata for this prompt engine
     pub fn metadata(&self) -> EngineMetadata {
         match self {
@@ -113,7 +128,6 @@ impl PromptEngine {
     }
 
     /// Returns the human-readable name of the engine
-    #[allow(dead_code)] // Will be used when installer is integrated in future stories
     pub fn name(&self) -> &str {
         self.metadata().name
     }
@@ -221,6 +235,15 @@ mod tests {
         assert_eq!(PromptEngine::Spaceship.name(), "Spaceship");
     }
 
+    #[test]
+    fn test_from_name_covers_manifest_names() {
+        for name in PROMPT_ENGINE_NAMES {
+            assert!(PromptEngine::from_name(name).is_some(), "no engine for '{name}'");
+        }
+        assert_eq!(PromptEngine::from_name("pure"), Some(PromptEngine::Pure));
+        assert_eq!(PromptEngine::from_name("p10k"), None);
+    }
+
     #[test]
     fn test_installation_methods() {
         // Verify installation method types
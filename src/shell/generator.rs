@@ -12,11 +12,19 @@ use std::fs;
 use std::path::Path;
 use std::time::Instant;
 
-use crate::core::manifest::{Manifest, PromptMode};
+use crate::core::manifest::{
+    hook_script_path, EnvValue, KeyMode, Manifest, PluginEntry, PromptMode, SecretSource, SnippetPhase,
+    WhenResolve,
+};
+use crate::frameworks::FrameworkType;
+use crate::prompts::engine::PROMPT_ENGINE_NAMES;
 
 /// Current zprof version for generated file headers
 const ZPROF_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// First line of every file zprof generates
+const GENERATED_HEADER: &str = "# Auto-generated by zprof from profile.toml\n";
+
 /// Write generated .zshrc and .zshenv files from manifest (Story 2.2)
 ///
 /// This is the main entry point for shell file generation from manifests.
@@ -95,6 +103,32 @@ pub fn write_generated_files(profile_name: &str, manifest: &Manifest) -> Result<
     // Validate syntax (optional, requires zsh binary)
     validate_zsh_syntax(&zshrc_path)?;
 
+    // .zprofile only exists for profiles with zprofile snippets
+    let zprofile_path = profile_dir.join(".zprofile");
+    if manifest.snippets_for(SnippetPhase::Zprofile).next().is_some() {
+        fs::write(&zprofile_path, generate_zprofile_from_manifest(manifest)?)
+            .with_context(|| format!("Failed to write .zprofile to {zprofile_path:?}"))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&zprofile_path, fs::Permissions::from_mode(0o644))
+                .with_context(|| format!("Failed to set permissions on {zprofile_path:?}"))?;
+        }
+
+        log::info!("Generated: {zprofile_path:?}");
+    } else if fs::read_to_string(&zprofile_path).is_ok_and(|content| content.starts_with(GENERATED_HEADER)) {
+        // Left over from a previous generation; a hand-written .zprofile is kept
+        fs::remove_file(&zprofile_path)
+            .with_context(|| format!("Failed to remove stale {zprofile_path:?}"))?;
+    }
+
+    for file in manifest.snippets.iter().filter_map(|snippet| snippet.file.as_deref()) {
+        if !file.starts_with("~/") && !profile_dir.join(file).exists() {
+            eprintln!("⚠ Warning: snippet file '{file}' does not exist in {}", profile_dir.display());
+        }
+    }
+
     // Generate framework-specific files (e.g., .zimrc for zimfw)
     if manifest.profile.framework == "zimfw" {
         let zimrc_content = generate_zimrc_from_manifest(manifest)?;
@@ -152,9 +186,39 @@ pub fn generate_zshenv_from_manifest(manifest: &Manifest) -> Result<String> {
     // No need to set it here in profile .zshenv
 
     // Environment variables from manifest (AC #2)
-    if !manifest.env.is_empty() {
+    add_env(&mut output, manifest);
+    add_conditional(&mut output, manifest, add_env);
+
+    add_snippets(&mut output, manifest, SnippetPhase::Zshenv);
+
+    Ok(output)
+}
+
+/// Add exports for the manifest's [env] section
+///
+/// Literal values are escaped; secrets become lookups run when an
+/// interactive shell starts.
+/// Variables are sorted by name so regenerating gives the same file.
+fn add_env(output: &mut String, manifest: &Manifest) {
+    let mut literals: Vec<(&String, &str)> = manifest
+        .env
+        .iter()
+        .filter_map(|(key, value)| value.as_literal().map(|value| (key, value)))
+        .collect();
+    let mut secrets: Vec<(&String, &SecretSource)> = manifest
+        .env
+        .iter()
+        .filter_map(|(key, value)| match value {
+            EnvValue::Secret(source) => Some((key, source)),
+            EnvValue::Literal(_) => None,
+        })
+        .collect();
+    literals.sort_by_key(|(key, _)| *key);
+    secrets.sort_by_key(|(key, _)| *key);
+
+    if !literals.is_empty() {
         output.push_str("# Custom environment variables\n");
-        for (key, value) in &manifest.env {
+        for (key, value) in literals {
             // Escape quotes and special characters
             let escaped_value = escape_shell_value(value);
             output.push_str(&format!("export {key}=\"{escaped_value}\"\n"));
@@ -162,9 +226,63 @@ pub fn generate_zshenv_from_manifest(manifest: &Manifest) -> Result<String> {
         output.push('\n');
     }
 
+    // Only interactive shells look secrets up: .zshenv is read by every zsh,
+    // and scripts or `zsh -c` from an editor shouldn't run `pass` or wait on a
+    // keyring prompt. They still get the values exported by the shell they
+    // were started from.
+    if !secrets.is_empty() {
+        output.push_str("# Secrets (looked up by interactive shells, never written to disk)\n");
+        output.push_str("if [[ -o interactive ]]; then\n");
+        for (key, source) in secrets {
+            let mut lookup = String::new();
+            add_secret_lookup(&mut lookup, key, source);
+            output.push_str(&format!("  {lookup}"));
+        }
+        output.push_str("fi\n\n");
+    }
+}
+
+/// Generate .zprofile content from manifest
+///
+/// Only written when the manifest has `zprofile` snippets; login shells read
+/// it between `.zshenv` and `.zshrc`.
+pub fn generate_zprofile_from_manifest(manifest: &Manifest) -> Result<String> {
+    let mut output = String::new();
+
+    output.push_str(GENERATED_HEADER);
+    output.push_str("# DO NOT EDIT THIS FILE DIRECTLY - Edit profile.toml instead\n");
+    output.push_str(&format!("# Generated: {}\n", Utc::now().format("%Y-%m-%d %H:%M:%S")));
+    output.push_str(&format!("# zprof version: {ZPROF_VERSION}\n"));
+    output.push_str(&format!("# Profile: {}\n", manifest.profile.name));
+
+    add_snippets(&mut output, manifest, SnippetPhase::Zprofile);
+
     Ok(output)
 }
 
+/// Emit the shell code that resolves a secret `[env]` value
+///
+/// The variable is only exported if the lookup succeeds, so a locked keyring
+/// or missing file leaves it unset rather than exported empty.
+fn add_secret_lookup(output: &mut String, key: &str, source: &SecretSource) {
+    let lookup = match source {
+        // The command is shell code written by the user, so it goes in as-is
+        SecretSource::Command(command) => command.clone(),
+        // Relative paths are relative to the profile directory, as export sees them
+        SecretSource::File(path) if !path.starts_with(['/', '~', '$']) => {
+            format!("cat -- {} 2>/dev/null", quote_path_entry(&format!("$ZDOTDIR/{}", path.trim_start_matches("./"))))
+        }
+        SecretSource::File(path) => format!("cat -- {} 2>/dev/null", quote_path_entry(path)),
+        SecretSource::Keyring(service) => {
+            let service = quote_single(service);
+            format!(
+                "if (( $+commands[security] )); then security find-generic-password -w -s {service}; else secret-tool lookup service {service}; fi 2>/dev/null"
+            )
+        }
+    };
+    output.push_str(&format!("{key}=\"$({lookup})\" && export {key} || unset {key}\n"));
+}
+
 /// Escape shell special characters in environment variable values
 ///
 /// Escapes: backslashes, double quotes, dollar signs, and backticks
@@ -231,7 +349,10 @@ fn add_prompt_engine_init(output: &mut String, engine: &str) -> Result<()> {
             output.push_str("source $HOME/.zprof/engines/spaceship-prompt/spaceship.zsh\n");
         }
         _ => {
-            bail!("Unsupported prompt engine: {engine}. Supported engines: starship, powerlevel10k, oh-my-posh, pure, spaceship");
+            bail!(
+                "Unsupported prompt engine: {engine}. Supported engines: {}",
+                PROMPT_ENGINE_NAMES.join(", ")
+            );
         }
     }
 
@@ -275,6 +396,19 @@ pub fn generate_zshrc_from_manifest(manifest: &Manifest) -> Result<String> {
     output.push_str("export HISTSIZE=10000\n");
     output.push_str("export SAVEHIST=10000\n\n");
 
+    // PATH goes before framework init so plugins can find the tools they wrap
+    add_path(&mut output, manifest);
+    add_conditional(&mut output, manifest, add_path);
+
+    // fpath too, so whichever compinit the framework runs picks up the extra functions
+    add_completion_fpath(&mut output, manifest);
+
+    // The keymap goes before framework init: switching it later would drop
+    // the bindings plugins make with plain `bindkey`
+    add_key_mode(&mut output, manifest);
+
+    add_snippets(&mut output, manifest, SnippetPhase::PreFramework);
+
     // Auto-install external binary dependencies for selected theme
     if manifest.profile.theme() == "starship" {
         add_starship_installation_check(&mut output);
@@ -290,9 +424,385 @@ pub fn generate_zshrc_from_manifest(manifest: &Manifest) -> Result<String> {
         _ => bail!("Unsupported framework: {}", manifest.profile.framework),
     }
 
+    // Completion styles come after framework init so they override framework defaults
+    add_completion_styles(&mut output, manifest);
+    add_zprof_completion(&mut output, manifest);
+
+    // Shell options come after framework init so they override framework defaults
+    add_shell_options(&mut output, manifest);
+    add_conditional(&mut output, manifest, add_shell_options);
+
+    // Key bindings come after plugins so framework and plugin bindings don't override them
+    add_key_bindings(&mut output, manifest);
+
+    // Profile aliases go last so they win over plugin and shared aliases
+    add_aliases(&mut output, manifest);
+    add_conditional(&mut output, manifest, add_aliases);
+
+    // The shell start hook sees the fully configured shell
+    add_shell_start_hook(&mut output, manifest);
+
+    // Last, since switching replaces the shell
+    add_auto_switch(&mut output, manifest);
+
     Ok(output)
 }
 
+/// Emit `[when]` overlays as guarded blocks, for `resolve = "shell"`
+///
+/// `add` generates one section from a manifest. It is run against a copy of
+/// the manifest holding only the overlay's sections, and whatever it emits is
+/// wrapped in an `if` on the overlay's condition.
+fn add_conditional(output: &mut String, manifest: &Manifest, add: fn(&mut String, &Manifest)) {
+    if manifest.when.resolve != WhenResolve::Shell {
+        return;
+    }
+
+    for condition in manifest.when.conditions() {
+        let mut overlay_only = manifest.clone();
+        overlay_only.env = condition.overlay.env.clone();
+        overlay_only.aliases = condition.overlay.aliases.clone();
+        overlay_only.options = condition.overlay.options.clone();
+        overlay_only.path = condition.overlay.path.clone();
+
+        let mut block = String::new();
+        add(&mut block, &overlay_only);
+        let block = block.trim_matches('\n');
+        if block.is_empty() {
+            continue;
+        }
+
+        output.push_str(&format!("# [{}]\n", condition.label()));
+        output.push_str(&format!("if {}; then\n", condition.guard()));
+        for line in block.lines() {
+            if line.is_empty() {
+                output.push('\n');
+            } else {
+                output.push_str(&format!("  {line}\n"));
+            }
+        }
+        output.push_str("fi\n\n");
+    }
+}
+
+/// Add the line editing mode from the manifest's [keys] section
+///
+/// `bindkey -v`/`-e` re-links the `main` keymap, which drops every binding
+/// made in it before, so this has to come before anything else binds keys.
+fn add_key_mode(output: &mut String, manifest: &Manifest) {
+    let mode = match manifest.keys.mode {
+        Some(KeyMode::Vi) => "-v",
+        Some(KeyMode::Emacs) => "-e",
+        None => return,
+    };
+
+    output.push_str("# Line editing mode (from profile.toml [keys])\n");
+    output.push_str(&format!("bindkey {mode}\n\n"));
+}
+
+/// Add widget and bindkey lines from the manifest's [keys] section
+fn add_key_bindings(output: &mut String, manifest: &Manifest) {
+    let keys = &manifest.keys;
+    if keys.widgets.is_empty() && keys.bind.is_empty() {
+        return;
+    }
+
+    output.push_str("\n# Key bindings (from profile.toml [keys])\n");
+
+    for widget in &keys.widgets {
+        output.push_str(&format!("zle -N {widget}\n"));
+    }
+
+    for binding in &keys.bind {
+        let keymap = binding
+            .keymap
+            .as_ref()
+            .map(|keymap| format!("-M {keymap} "))
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "bindkey {keymap}{} {}\n",
+            quote_single(&binding.keys),
+            binding.widget
+        ));
+    }
+}
+
+/// Add the `on_shell_start` hook from the manifest's [hooks] section
+///
+/// Script paths are sourced (relative paths from the profile directory,
+/// which is `$ZDOTDIR`); anything else is written out as a command.
+fn add_shell_start_hook(output: &mut String, manifest: &Manifest) {
+    let Some(hook) = &manifest.hooks.on_shell_start else {
+        return;
+    };
+
+    output.push_str("\n# Shell start hook (from profile.toml [hooks])\n");
+    match hook_script_path(hook) {
+        Some(script) => {
+            let script = match script.strip_prefix("./") {
+                Some(relative) => format!("\"$ZDOTDIR/{}\"", relative.replace('"', "\\\"")),
+                None if script.starts_with('/') || script.starts_with("~/") => quote_path_entry(script),
+                None => format!("\"$ZDOTDIR/{}\"", script.replace('"', "\\\"")),
+            };
+            output.push_str(&format!("source {script}\n"));
+        }
+        None => {
+            output.push_str(hook.trim());
+            output.push('\n');
+        }
+    }
+}
+
+/// Add the `chpwd` hook that offers another profile when a directory wants one
+///
+/// Looking for a `.zprof-profile` marker is done in zsh; zprof itself only
+/// runs when there is one, or when config.toml had an `[auto]` section as the
+/// shell started. Nothing runs for `zsh -c`, so `zprof run` and `zprof bench`
+/// never prompt.
+fn add_auto_switch(output: &mut String, manifest: &Manifest) {
+    let profile = quote_single(&manifest.profile.name);
+    output.push_str("\n# Directory-based profile switching (see 'zprof auto status')\n");
+    output.push_str(
+        r#"_zprof_auto_config="$HOME/.zsh-profiles/config.toml"
+[[ -r $_zprof_auto_config && "$(<$_zprof_auto_config)" == *'[auto'* ]] && _zprof_auto_rules=1 || _zprof_auto_rules=0
+_zprof_auto_switch() {
+  [[ -z $ZSH_EXECUTION_STRING ]] && (( $+commands[zprof] )) || return 0
+  [[ $PWD == $_zprof_auto_declined(|/*) ]] || unset _zprof_auto_declined
+  local dir=$PWD
+  while [[ $dir != / && ! -f $dir/.zprof-profile ]]; do dir=${dir:h}; done
+  [[ -f $dir/.zprof-profile ]] || (( _zprof_auto_rules )) || return 0
+  local -a found=("${(@ps:	:)$(command zprof auto resolve "$PWD" 2>/dev/null)}")
+"#,
+    );
+    output.push_str(&format!(
+        "  [[ -n $found[1] && $found[1] != {profile} && $found[2] != $_zprof_auto_declined ]] || return 0
+"
+    ));
+    output.push_str(
+        r#"  case $found[3] in
+    switch) exec zprof shell $found[1] ;;
+    notify) print -r -- "zprof: $found[2] uses profile '$found[1]' (run 'zprof shell $found[1]')" ;;
+    *)
+      if read -q "?zprof: $found[2] uses profile '$found[1]'. Switch this shell? [y/N] "; then
+        print
+        exec zprof shell $found[1]
+      fi
+      print ;;
+  esac
+  # Not again until the shell leaves this tree
+  _zprof_auto_declined=$found[2]
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd _zprof_auto_switch
+_zprof_auto_switch
+"#,
+    );
+}
+
+/// Add the manifest's `[[snippets]]` for one load phase
+///
+/// Inline code is copied in as written; files are sourced from the profile
+/// directory (`$ZDOTDIR`) when the shell reaches that phase.
+fn add_snippets(output: &mut String, manifest: &Manifest, phase: SnippetPhase) {
+    for snippet in manifest.snippets_for(phase) {
+        output.push_str(&format!("\n# Snippet (from profile.toml [[snippets]], phase {})\n", phase.as_str()));
+        match (&snippet.code, &snippet.file) {
+            (Some(code), _) => {
+                output.push_str(code.trim_end());
+                output.push('\n');
+            }
+            (None, Some(file)) => {
+                let path = if file.starts_with("~/") {
+                    quote_path_entry(file)
+                } else {
+                    format!("\"$ZDOTDIR/{}\"", file.trim_start_matches("./").replace('"', "\\\""))
+                };
+                output.push_str(&format!("source {path}\n"));
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Add PATH management from the manifest's [path] section
+///
+/// Uses zsh's `path` array tied to `$PATH` with `typeset -U` so entries that
+/// are already present (e.g. from /etc/paths) are not duplicated. When
+/// `skip_missing` is set, the `(N-/)` glob qualifier drops entries that are
+/// not directories at shell start.
+fn add_path(output: &mut String, manifest: &Manifest) {
+    let path = &manifest.path;
+    if path.is_empty() {
+        return;
+    }
+
+    let quote_all = |entries: &[String]| -> Vec<String> {
+        entries.iter().map(|entry| quote_path_entry(entry)).collect()
+    };
+    let prepend = quote_all(&path.prepend);
+    let append = quote_all(&path.append);
+
+    output.push_str("# PATH (from profile.toml [path])\n");
+    output.push_str("typeset -U path\n");
+
+    if path.skip_missing {
+        output.push_str("() {\n");
+        output.push_str(&format!("  local -a prepend=({})\n", prepend.join(" ")));
+        output.push_str(&format!("  local -a append=({})\n", append.join(" ")));
+        output.push_str("  path=(${^prepend}(N-/) $path ${^append}(N-/))\n");
+        output.push_str("}\n");
+    } else {
+        let mut words = prepend;
+        words.push("$path".to_string());
+        words.extend(append);
+        output.push_str(&format!("path=({})\n", words.join(" ")));
+    }
+    output.push('\n');
+}
+
+/// Double-quote a PATH entry, turning a leading `~` into `$HOME`
+///
+/// Variables are left unescaped so `$GOPATH/bin` expands at shell start.
+fn quote_path_entry(entry: &str) -> String {
+    let expanded = if entry == "~" {
+        "$HOME".to_string()
+    } else if let Some(rest) = entry.strip_prefix("~/") {
+        format!("$HOME/{rest}")
+    } else {
+        entry.to_string()
+    };
+
+    format!("\"{}\"", expanded.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Add `fpath` entries from the manifest's [completion] section
+fn add_completion_fpath(output: &mut String, manifest: &Manifest) {
+    let fpath = &manifest.completion.fpath;
+    if fpath.is_empty() {
+        return;
+    }
+
+    let entries: Vec<String> = fpath.iter().map(|entry| quote_path_entry(entry)).collect();
+    output.push_str("# Completion functions (from profile.toml [completion])\n");
+    output.push_str(&format!("fpath=({} $fpath)\n", entries.join(" ")));
+    output.push('\n');
+}
+
+/// Add `zstyle ':completion:*'` lines from the manifest's [completion] section
+fn add_completion_styles(output: &mut String, manifest: &Manifest) {
+    let completion = &manifest.completion;
+    if !completion.has_styles() {
+        return;
+    }
+
+    output.push_str("\n# Completion (from profile.toml [completion])\n");
+
+    if !completion.matchers.is_empty() {
+        let matchers: Vec<String> = completion.matchers.iter().map(|m| quote_single(m)).collect();
+        output.push_str(&format!("zstyle ':completion:*' matcher-list {}\n", matchers.join(" ")));
+    }
+    if completion.menu_select {
+        // complist provides the menuselect keymap, so [keys] can bind in it
+        output.push_str("zmodload zsh/complist\n");
+        output.push_str("zstyle ':completion:*' menu select\n");
+    }
+    if completion.cache {
+        output.push_str("zstyle ':completion:*' use-cache on\n");
+        output.push_str("zstyle ':completion:*' cache-path \"$ZDOTDIR/.zcompcache\"\n");
+    }
+}
+
+/// Load zprof's own completions, after the framework has run `compinit`
+///
+/// The script is asked from the installed zprof at each shell start, so it
+/// always matches the binary it calls back into.
+fn add_zprof_completion(output: &mut String, manifest: &Manifest) {
+    if !manifest.completion.zprof {
+        return;
+    }
+
+    output.push_str("\n# zprof completions (from profile.toml [completion])\n");
+    output.push_str("(( $+commands[zprof] )) && source <(COMPLETE=zsh command zprof)\n");
+}
+
+/// Initialize the completion system for frameworks that leave it to the user
+///
+/// oh-my-zsh, prezto and zimfw run `compinit` themselves (and already reuse a
+/// recent dump); zinit and zap call this. With `fast_init`, a dump younger
+/// than a day is trusted with `compinit -C`, and a full check refreshes it.
+fn add_compinit(output: &mut String, manifest: &Manifest) {
+    output.push_str("# Initialize completion system\n");
+    output.push_str("autoload -Uz compinit\n");
+
+    if !manifest.completion.fast_init {
+        output.push_str("compinit\n");
+        return;
+    }
+
+    output.push_str("() {\n");
+    output.push_str("  local dump=\"${ZDOTDIR:-$HOME}/.zcompdump\"\n");
+    output.push_str("  local -a fresh=($dump(N.mh-24))\n");
+    output.push_str("  if (( $#fresh )); then\n");
+    output.push_str("    compinit -C -d \"$dump\"\n");
+    output.push_str("  else\n");
+    output.push_str("    compinit -d \"$dump\"\n");
+    output.push_str("    touch \"$dump\"\n");
+    output.push_str("  fi\n");
+    output.push_str("}\n");
+}
+
+/// Add setopt/unsetopt lines from the manifest's [options] section
+///
+/// Option names are emitted as written; zsh accepts any case and underscore
+/// spelling, and validation has already rejected unknown names.
+fn add_shell_options(output: &mut String, manifest: &Manifest) {
+    if manifest.options.is_empty() {
+        return;
+    }
+
+    output.push_str("\n# Shell options (from profile.toml [options])\n");
+
+    if !manifest.options.set.is_empty() {
+        output.push_str(&format!("setopt {}\n", manifest.options.set.join(" ")));
+    }
+    if !manifest.options.unset.is_empty() {
+        output.push_str(&format!("unsetopt {}\n", manifest.options.unset.join(" ")));
+    }
+}
+
+/// Add alias definitions from the manifest's [aliases] section
+///
+/// Emits plain, global (`alias -g`) and suffix (`alias -s`) aliases sorted by
+/// name so regenerating an unchanged manifest produces identical output.
+fn add_aliases(output: &mut String, manifest: &Manifest) {
+    if manifest.aliases.is_empty() {
+        return;
+    }
+
+    output.push_str("\n# Profile aliases (from profile.toml [aliases])\n");
+
+    for (flag, aliases) in [
+        ("", &manifest.aliases.plain),
+        ("-g ", &manifest.aliases.global),
+        ("-s ", &manifest.aliases.suffix),
+    ] {
+        let mut names: Vec<&String> = aliases.keys().collect();
+        names.sort();
+        for name in names {
+            let command = quote_single(&aliases[name]);
+            output.push_str(&format!("alias {flag}{name}={command}\n"));
+        }
+    }
+}
+
+/// Wrap a value in single quotes for zsh, escaping embedded single quotes
+///
+/// Unlike `escape_shell_value`, nothing inside the quotes is expanded when
+/// the line is read, which is what alias bodies need.
+fn quote_single(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
 /// Generate oh-my-zsh specific configuration
 fn generate_oh_my_zsh_config(output: &mut String, manifest: &Manifest) -> Result<()> {
     output.push_str("# oh-my-zsh configuration\n");
@@ -317,10 +827,10 @@ fn generate_oh_my_zsh_config(output: &mut String, manifest: &Manifest) -> Result
     }
     output.push('\n');
 
-    // Set plugins
-    if !manifest.plugins.enabled.is_empty() {
+    // Set plugins (custom sources are cloned into $ZSH/custom/plugins by the installer)
+    if !manifest.plugins.is_empty() {
         output.push_str("plugins=(\n");
-        for plugin in &manifest.plugins.enabled {
+        for plugin in manifest.plugins.names() {
             output.push_str(&format!("  {plugin}\n"));
         }
         output.push_str(")\n");
@@ -330,11 +840,15 @@ fn generate_oh_my_zsh_config(output: &mut String, manifest: &Manifest) -> Result
     // Source oh-my-zsh
     output.push_str("source $ZSH/oh-my-zsh.sh\n");
 
+    add_snippets(output, manifest, SnippetPhase::PostPlugins);
+
     // Initialize prompt engine if using PromptEngine mode (AFTER framework)
     if let PromptMode::PromptEngine { engine } = &manifest.profile.prompt_mode {
         add_prompt_engine_init(output, engine)?;
     }
 
+    add_snippets(output, manifest, SnippetPhase::PostPrompt);
+
     // Source shared customizations
     output.push_str("\n# Source shared customizations (edit ~/.zsh-profiles/shared/custom.zsh)\n");
     output.push_str("[ -f \"$HOME/.zsh-profiles/shared/custom.zsh\" ] && source \"$HOME/.zsh-profiles/shared/custom.zsh\"\n");
@@ -345,7 +859,7 @@ fn generate_oh_my_zsh_config(output: &mut String, manifest: &Manifest) -> Result
 /// Generate .zimrc content from manifest (zimfw module declarations)
 ///
 /// Creates .zimrc file with module declarations for zimfw to process
-fn generate_zimrc_from_manifest(manifest: &Manifest) -> Result<String> {
+pub fn generate_zimrc_from_manifest(manifest: &Manifest) -> Result<String> {
     let mut output = String::new();
 
     // Header comment
@@ -357,10 +871,21 @@ fn generate_zimrc_from_manifest(manifest: &Manifest) -> Result<String> {
     output.push('\n');
 
     // Add plugins as zmodules
-    if !manifest.plugins.enabled.is_empty() {
+    if !manifest.plugins.is_empty() {
         output.push_str("# Plugins\n");
-        for plugin in &manifest.plugins.enabled {
-            output.push_str(&format!("zmodule {plugin}\n"));
+        for plugin in manifest.plugins.resolved() {
+            let mut line = match &plugin.source {
+                Some(source) => format!("zmodule {source} --name {}", plugin.name),
+                None => format!("zmodule {}", plugin.name),
+            };
+            // git clone -b accepts both branches and tags
+            if let Some(git_ref) = &plugin.git_ref {
+                line.push_str(&format!(" --branch {git_ref}"));
+            }
+            if let Some(options) = plugin.options_for(&FrameworkType::Zimfw) {
+                line.push_str(&format!(" {options}"));
+            }
+            output.push_str(&format!("{line}\n"));
         }
         output.push('\n');
     }
@@ -413,11 +938,15 @@ fn generate_zimfw_config(output: &mut String, manifest: &Manifest) -> Result<()>
     output.push_str("# Initialize modules\n");
     output.push_str("source ${ZIM_HOME}/init.zsh\n");
 
+    add_snippets(output, manifest, SnippetPhase::PostPlugins);
+
     // Initialize prompt engine if using PromptEngine mode (AFTER framework)
     if let PromptMode::PromptEngine { engine } = &manifest.profile.prompt_mode {
         add_prompt_engine_init(output, engine)?;
     }
 
+    add_snippets(output, manifest, SnippetPhase::PostPrompt);
+
     // Source shared customizations
     output.push_str("\n# Source shared customizations (edit ~/.zsh-profiles/shared/custom.zsh)\n");
     output.push_str("[ -f \"$HOME/.zsh-profiles/shared/custom.zsh\" ] && source \"$HOME/.zsh-profiles/shared/custom.zsh\"\n");
@@ -434,11 +963,13 @@ fn generate_prezto_config(output: &mut String, manifest: &Manifest) -> Result<()
     output.push('\n');
 
     // Load plugins
-    if !manifest.plugins.enabled.is_empty() {
+    // (custom sources are cloned into $PREZTO_DIR/contrib by the installer)
+    let plugins = manifest.plugins.names();
+    if !plugins.is_empty() {
         output.push_str("# Prezto modules\n");
         output.push_str("zstyle ':prezto:load' pmodule \\\n");
-        for (idx, plugin) in manifest.plugins.enabled.iter().enumerate() {
-            if idx == manifest.plugins.enabled.len() - 1 {
+        for (idx, plugin) in plugins.iter().enumerate() {
+            if idx == plugins.len() - 1 {
                 output.push_str(&format!("  '{plugin}'\n"));
             } else {
                 output.push_str(&format!("  '{plugin}' \\\n"));
@@ -465,11 +996,15 @@ fn generate_prezto_config(output: &mut String, manifest: &Manifest) -> Result<()
     // Source prezto
     output.push_str("source $PREZTO_DIR/init.zsh\n");
 
+    add_snippets(output, manifest, SnippetPhase::PostPlugins);
+
     // Initialize prompt engine if using PromptEngine mode (AFTER framework)
     if let PromptMode::PromptEngine { engine } = &manifest.profile.prompt_mode {
         add_prompt_engine_init(output, engine)?;
     }
 
+    add_snippets(output, manifest, SnippetPhase::PostPrompt);
+
     // Source shared customizations
     output.push_str("\n# Source shared customizations (edit ~/.zsh-profiles/shared/custom.zsh)\n");
     output.push_str("[ -f \"$HOME/.zsh-profiles/shared/custom.zsh\" ] && source \"$HOME/.zsh-profiles/shared/custom.zsh\"\n");
@@ -490,14 +1025,36 @@ fn generate_zinit_config(output: &mut String, manifest: &Manifest) -> Result<()>
     output.push('\n');
 
     // Load plugins
-    if !manifest.plugins.enabled.is_empty() {
+    if !manifest.plugins.is_empty() {
         output.push_str("# Plugins\n");
-        for plugin in &manifest.plugins.enabled {
-            output.push_str(&format!("zinit light {plugin}\n"));
+        for plugin in manifest.plugins.resolved() {
+            let mut ice = Vec::new();
+            if plugin.defer {
+                ice.push("wait lucid".to_string());
+            }
+            if let Some(git_ref) = &plugin.git_ref {
+                ice.push(format!("ver\"{git_ref}\""));
+            }
+            if let Some(options) = plugin.options_for(&FrameworkType::Zinit) {
+                ice.push(options.to_string());
+            }
+            if !ice.is_empty() {
+                output.push_str(&format!("zinit ice {}\n", ice.join(" ")));
+            }
+
+            let repo = plugin
+                .repo_for(&FrameworkType::Zinit)
+                .unwrap_or_else(|| plugin.name.clone());
+            output.push_str(&format!("zinit light {repo}\n"));
         }
         output.push('\n');
     }
 
+    // Plugins' compdef calls are queued by zinit and replayed once compinit has run
+    add_compinit(output, manifest);
+    output.push_str("zinit cdreplay -q\n");
+    output.push('\n');
+
     // Load theme (only if using FrameworkTheme mode)
     match &manifest.profile.prompt_mode {
         PromptMode::PromptEngine { .. } => {
@@ -511,11 +1068,15 @@ fn generate_zinit_config(output: &mut String, manifest: &Manifest) -> Result<()>
         }
     }
 
+    add_snippets(output, manifest, SnippetPhase::PostPlugins);
+
     // Initialize prompt engine if using PromptEngine mode
     if let PromptMode::PromptEngine { engine } = &manifest.profile.prompt_mode {
         add_prompt_engine_init(output, engine)?;
     }
 
+    add_snippets(output, manifest, SnippetPhase::PostPrompt);
+
     // Source shared customizations
     output.push_str("\n# Source shared customizations (edit ~/.zsh-profiles/shared/custom.zsh)\n");
     output.push_str("[ -f \"$HOME/.zsh-profiles/shared/custom.zsh\" ] && source \"$HOME/.zsh-profiles/shared/custom.zsh\"\n");
@@ -540,24 +1101,31 @@ fn generate_zap_config(output: &mut String, manifest: &Manifest) -> Result<()> {
     output.push('\n');
 
     // Initialize completion system before loading plugins to prevent compdef warnings
-    output.push_str("# Initialize completion system\n");
-    output.push_str("autoload -Uz compinit\n");
-    output.push_str("compinit\n");
+    add_compinit(output, manifest);
     output.push('\n');
 
     // Load plugins
-    if !manifest.plugins.enabled.is_empty() {
+    if !manifest.plugins.is_empty() {
+        let (deferred, eager): (Vec<_>, Vec<_>) =
+            manifest.plugins.resolved().into_iter().partition(|p| p.defer);
+
         output.push_str("# Plugins\n");
-        for plugin in &manifest.plugins.enabled {
-            // Look up plugin repo URL from registry metadata
-            if let Some(p) = crate::frameworks::plugin::PLUGIN_REGISTRY
-                .iter()
-                .find(|p| p.name == plugin.as_str())
-            {
-                if let Some(repo_url) = p.compatibility.repo_url_for(&crate::frameworks::FrameworkType::Zap) {
-                    output.push_str(&format!("plug \"{repo_url}\"\n"));
-                }
+        for plugin in &eager {
+            add_zap_plug(output, plugin, "");
+        }
+
+        // Zap has no lazy loading, so deferred plugins are plugged from a
+        // one-shot precmd hook that runs once the first prompt is drawn
+        if !deferred.is_empty() {
+            output.push_str("\n# Deferred plugins (loaded after the first prompt)\n");
+            output.push_str("_zprof_load_deferred_plugins() {\n");
+            output.push_str("  add-zsh-hook -d precmd _zprof_load_deferred_plugins\n");
+            for plugin in &deferred {
+                add_zap_plug(output, plugin, "  ");
             }
+            output.push_str("}\n");
+            output.push_str("autoload -Uz add-zsh-hook\n");
+            output.push_str("add-zsh-hook precmd _zprof_load_deferred_plugins\n");
         }
         output.push('\n');
     }
@@ -589,11 +1157,15 @@ fn generate_zap_config(output: &mut String, manifest: &Manifest) -> Result<()> {
         }
     }
 
+    add_snippets(output, manifest, SnippetPhase::PostPlugins);
+
     // Initialize prompt engine if using PromptEngine mode
     if let PromptMode::PromptEngine { engine } = &manifest.profile.prompt_mode {
         add_prompt_engine_init(output, engine)?;
     }
 
+    add_snippets(output, manifest, SnippetPhase::PostPrompt);
+
     // Source shared customizations
     output.push_str("\n# Source shared customizations (edit ~/.zsh-profiles/shared/custom.zsh)\n");
     output.push_str("[ -f \"$HOME/.zsh-profiles/shared/custom.zsh\" ] && source \"$HOME/.zsh-profiles/shared/custom.zsh\"\n");
@@ -601,6 +1173,22 @@ fn generate_zap_config(output: &mut String, manifest: &Manifest) -> Result<()> {
     Ok(())
 }
 
+/// Add a zap `plug` line for a plugin, pinned to its ref if one is set
+///
+/// Plugins with no source and no zap repo in the registry are skipped, since
+/// zap can only install from a repository.
+fn add_zap_plug(output: &mut String, plugin: &PluginEntry, indent: &str) {
+    let Some(repo_url) = plugin.repo_for(&FrameworkType::Zap) else {
+        log::warn!("No zap repository known for plugin '{}'; skipping", plugin.name);
+        return;
+    };
+
+    match &plugin.git_ref {
+        Some(git_ref) => output.push_str(&format!("{indent}plug \"{repo_url}\" \"{git_ref}\"\n")),
+        None => output.push_str(&format!("{indent}plug \"{repo_url}\"\n")),
+    }
+}
+
 /// Validate generated zsh file syntax using zsh -n
 ///
 /// Runs 'zsh -n <file>' to check syntax without executing.
@@ -640,7 +1228,7 @@ fn validate_zsh_syntax(file_path: &Path) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::manifest::{Manifest, ProfileSection, PluginsSection};
+    use crate::core::manifest::{self, Manifest, PluginEntry, ProfileSection, PluginsSection};
     use chrono::Utc;
     use std::collections::HashMap;
     use std::fs;
@@ -658,11 +1246,22 @@ mod tests {
                 },
                 created: Utc::now(),
                 modified: Utc::now(),
+                extends: None,
             },
             plugins: PluginsSection {
                 enabled: plugins,
+                entry: Vec::new(),
             },
-            env,
+            env: env.into_iter().map(|(key, value)| (key, value.into())).collect(),
+            aliases: Default::default(),
+            options: Default::default(),
+            path: Default::default(),
+            schema_version: manifest::CURRENT_SCHEMA_VERSION,
+            hooks: Default::default(),
+            keys: Default::default(),
+            completion: Default::default(),
+            when: Default::default(),
+            snippets: Vec::new(),
         }
     }
 
@@ -684,6 +1283,13 @@ mod tests {
         assert_eq!(escape_shell_value("\\$PATH:`pwd`"), "\\\\\\$PATH:\\`pwd\\`");
     }
 
+    #[test]
+    fn test_quote_single() {
+        assert_eq!(quote_single("ls -la"), "'ls -la'");
+        assert_eq!(quote_single("echo 'hi'"), r"'echo '\''hi'\'''");
+        assert_eq!(quote_single("$HOME"), "'$HOME'");
+    }
+
     #[test]
     fn test_generate_zshenv_from_manifest_basic() -> Result<()> {
         let manifest = create_test_manifest("oh-my-zsh", vec![], HashMap::new());
@@ -735,6 +1341,30 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_generate_zshenv_secret_lookups() -> Result<()> {
+        let mut manifest = create_test_manifest("oh-my-zsh", vec![], HashMap::new());
+        manifest.env.insert("GH_TOKEN".to_string(), EnvValue::Secret(SecretSource::Command("pass show gh/token".to_string())));
+        manifest.env.insert("OPENAI_API_KEY".to_string(), EnvValue::Secret(SecretSource::File("~/.secrets/openai".to_string())));
+        manifest.env.insert("NPM_TOKEN".to_string(), EnvValue::Secret(SecretSource::Keyring("npm".to_string())));
+        manifest.env.insert("NPM_TOKEN_FILE".to_string(), EnvValue::Secret(SecretSource::File("./secrets/npm".to_string())));
+        let content = generate_zshenv_from_manifest(&manifest)?;
+
+        assert!(content.contains("GH_TOKEN=\"$(pass show gh/token)\" && export GH_TOKEN || unset GH_TOKEN\n"));
+        assert!(content.contains("OPENAI_API_KEY=\"$(cat -- \"$HOME/.secrets/openai\" 2>/dev/null)\" && export OPENAI_API_KEY"));
+        assert!(content.contains("NPM_TOKEN_FILE=\"$(cat -- \"$ZDOTDIR/secrets/npm\" 2>/dev/null)\""), "{content}");
+        assert!(content.contains("security find-generic-password -w -s 'npm'; else secret-tool lookup service 'npm'; fi 2>/dev/null)\""));
+        assert!(!content.contains("export GH_TOKEN=\""));
+
+        // Non-interactive shells (scripts, `zsh -c`) skip the lookups
+        let guard = content.find("if [[ -o interactive ]]; then\n").unwrap();
+        let lookup = content.find("  GH_TOKEN=\"$(pass show gh/token)\"").unwrap();
+        let end = content[lookup..].find("\nfi\n").unwrap() + lookup;
+        assert!(guard < lookup && content[lookup..end].contains("NPM_TOKEN_FILE="), "{content}");
+
+        Ok(())
+    }
+
     #[test]
     fn test_generate_zshrc_from_manifest_oh_my_zsh() -> Result<()> {
         let manifest = create_test_manifest(
@@ -760,6 +1390,20 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_zshrc_ends_with_auto_switch_hook() -> Result<()> {
+        let manifest = create_test_manifest("zap", vec!["git".to_string()], HashMap::new());
+        let content = generate_zshrc_from_manifest(&manifest)?;
+
+        let hook = content.find("_zprof_auto_switch() {").expect("auto switch hook");
+        assert!(hook > content.find("plug \"").expect("plugins"));
+        assert!(content.contains(&format!("$found[1] != '{}'", manifest.profile.name)));
+        assert!(content.contains("add-zsh-hook chpwd _zprof_auto_switch\n"));
+        assert!(content.ends_with("_zprof_auto_switch\n"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_generate_zshrc_from_manifest_zimfw() -> Result<()> {
         let manifest = create_test_manifest(
@@ -1210,6 +1854,215 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_aliases_emitted_for_all_frameworks() -> Result<()> {
+        for framework in ["oh-my-zsh", "zimfw", "prezto", "zinit", "zap"] {
+            let mut manifest = create_test_manifest(framework, vec!["git".to_string()], HashMap::new());
+            manifest.aliases.plain.insert("gs".to_string(), "git status".to_string());
+            manifest.aliases.global.insert("G".to_string(), "| grep".to_string());
+            manifest.aliases.suffix.insert("md".to_string(), "glow".to_string());
+
+            let content = generate_zshrc_from_manifest(&manifest)?;
+
+            assert!(content.contains("alias gs='git status'"), "{framework} missing plain alias");
+            assert!(content.contains("alias -g G='| grep'"), "{framework} missing global alias");
+            assert!(content.contains("alias -s md='glow'"), "{framework} missing suffix alias");
+
+            // Aliases must come after plugins and shared customizations so they win
+            let alias_pos = content.find("alias gs=").unwrap();
+            let custom_pos = content.find("source \"$HOME/.zsh-profiles/shared/custom.zsh\"").unwrap();
+            assert!(alias_pos > custom_pos, "{framework}: aliases must be emitted last");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_alias_section_without_aliases() -> Result<()> {
+        let manifest = create_test_manifest("oh-my-zsh", vec![], HashMap::new());
+        let content = generate_zshrc_from_manifest(&manifest)?;
+
+        assert!(!content.contains("Profile aliases"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shell_options_emitted_after_framework() -> Result<()> {
+        for framework in ["oh-my-zsh", "zimfw", "prezto", "zinit", "zap"] {
+            let mut manifest = create_test_manifest(framework, vec![], HashMap::new());
+            manifest.options.set = vec!["AUTO_CD".to_string(), "HIST_IGNORE_DUPS".to_string()];
+            manifest.options.unset = vec!["BEEP".to_string()];
+
+            let content = generate_zshrc_from_manifest(&manifest)?;
+
+            let setopt_pos = content
+                .find("setopt AUTO_CD HIST_IGNORE_DUPS\n")
+                .unwrap_or_else(|| panic!("{framework}: setopt line missing"));
+            let unsetopt_pos = content
+                .find("unsetopt BEEP\n")
+                .unwrap_or_else(|| panic!("{framework}: unsetopt line missing"));
+            let custom_pos = content
+                .find("source \"$HOME/.zsh-profiles/shared/custom.zsh\"")
+                .unwrap();
+
+            assert!(setopt_pos > custom_pos, "{framework}: options must follow framework init");
+            assert!(unsetopt_pos > setopt_pos);
+        }
+
+        Ok(())
+    }
+
+    fn plugin_entry(name: &str) -> PluginEntry {
+        PluginEntry::named(name)
+    }
+
+    #[test]
+    fn test_zinit_plugin_entries() -> Result<()> {
+        let mut manifest = create_test_manifest("zinit", vec!["git".to_string()], HashMap::new());
+        let mut fsh = plugin_entry("fast-syntax-highlighting");
+        fsh.source = Some("zdharma-continuum/fast-syntax-highlighting".to_string());
+        fsh.git_ref = Some("v1.55".to_string());
+        fsh.defer = true;
+        fsh.options.insert("zinit".to_string(), "atinit'zicompinit'".to_string());
+        manifest.plugins.entry = vec![fsh];
+
+        let content = generate_zshrc_from_manifest(&manifest)?;
+
+        assert!(content.contains("zinit light git\n"));
+        assert!(content.contains(
+            "zinit ice wait lucid ver\"v1.55\" atinit'zicompinit'\nzinit light zdharma-continuum/fast-syntax-highlighting\n"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zap_plugin_entries() -> Result<()> {
+        let mut manifest = create_test_manifest(
+            "zap",
+            vec!["zsh-autosuggestions".to_string(), "zsh-syntax-highlighting".to_string()],
+            HashMap::new(),
+        );
+        let mut pinned = plugin_entry("zsh-autosuggestions");
+        pinned.source = Some("me/zsh-autosuggestions".to_string());
+        pinned.git_ref = Some("abc1234".to_string());
+        let mut deferred = plugin_entry("zsh-syntax-highlighting");
+        deferred.defer = true;
+        manifest.plugins.entry = vec![pinned, deferred];
+
+        let content = generate_zshrc_from_manifest(&manifest)?;
+
+        assert!(content.contains("plug \"me/zsh-autosuggestions\" \"abc1234\"\n"));
+        assert!(content.contains("_zprof_load_deferred_plugins() {\n"));
+        assert!(content.contains("  plug \"zsh-users/zsh-syntax-highlighting\"\n"));
+        assert!(content.contains("add-zsh-hook precmd _zprof_load_deferred_plugins\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zimfw_plugin_entries() -> Result<()> {
+        let mut manifest = create_test_manifest("zimfw", vec!["git".to_string()], HashMap::new());
+        let mut custom = plugin_entry("my-plugin");
+        custom.source = Some("https://gitlab.com/me/my-plugin.git".to_string());
+        custom.git_ref = Some("v2".to_string());
+        custom.options.insert("zimfw".to_string(), "--source init.zsh".to_string());
+        manifest.plugins.entry = vec![custom];
+
+        let content = generate_zimrc_from_manifest(&manifest)?;
+
+        assert!(content.contains("zmodule git\n"));
+        assert!(content.contains(
+            "zmodule https://gitlab.com/me/my-plugin.git --name my-plugin --branch v2 --source init.zsh\n"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plugin_entries_listed_by_name_for_omz_and_prezto() -> Result<()> {
+        for framework in ["oh-my-zsh", "prezto"] {
+            let mut manifest = create_test_manifest(framework, vec!["git".to_string()], HashMap::new());
+            let mut custom = plugin_entry("my-plugin");
+            custom.source = Some("me/my-plugin".to_string());
+            manifest.plugins.entry = vec![custom];
+
+            let content = generate_zshrc_from_manifest(&manifest)?;
+
+            assert!(content.contains("git"), "{framework}");
+            assert!(content.contains("my-plugin"), "{framework}");
+            assert!(!content.contains("me/my-plugin"), "{framework}: source is installed, not referenced");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_quote_path_entry() {
+        assert_eq!(quote_path_entry("~"), "\"$HOME\"");
+        assert_eq!(quote_path_entry("~/.local/bin"), "\"$HOME/.local/bin\"");
+        assert_eq!(quote_path_entry("$GOPATH/bin"), "\"$GOPATH/bin\"");
+        assert_eq!(quote_path_entry("/opt/my \"tools\""), "\"/opt/my \\\"tools\\\"\"");
+        // Only a bare leading tilde is expanded
+        assert_eq!(quote_path_entry("/tmp/~x"), "\"/tmp/~x\"");
+    }
+
+    #[test]
+    fn test_path_emitted_before_framework() -> Result<()> {
+        let mut manifest = create_test_manifest("oh-my-zsh", vec![], HashMap::new());
+        manifest.path.prepend = vec!["~/.local/bin".to_string(), "$GOPATH/bin".to_string()];
+        manifest.path.append = vec!["/opt/tools/bin".to_string()];
+
+        let content = generate_zshrc_from_manifest(&manifest)?;
+
+        assert!(content.contains("typeset -U path\n"));
+        assert!(content.contains(
+            "path=(\"$HOME/.local/bin\" \"$GOPATH/bin\" $path \"/opt/tools/bin\")\n"
+        ));
+        let path_pos = content.find("typeset -U path").unwrap();
+        let framework_pos = content.find("# oh-my-zsh configuration").unwrap();
+        assert!(path_pos < framework_pos);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_path_skip_missing() -> Result<()> {
+        let mut manifest = create_test_manifest("zap", vec![], HashMap::new());
+        manifest.path.prepend = vec!["~/bin".to_string()];
+        manifest.path.skip_missing = true;
+
+        let content = generate_zshrc_from_manifest(&manifest)?;
+
+        assert!(content.contains("  local -a prepend=(\"$HOME/bin\")\n"));
+        assert!(content.contains("  local -a append=()\n"));
+        assert!(content.contains("  path=(${^prepend}(N-/) $path ${^append}(N-/))\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_path_section_without_path() -> Result<()> {
+        let manifest = create_test_manifest("zap", vec![], HashMap::new());
+        let content = generate_zshrc_from_manifest(&manifest)?;
+
+        assert!(!content.contains("typeset -U path"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_options_section_without_options() -> Result<()> {
+        let manifest = create_test_manifest("zap", vec![], HashMap::new());
+        let content = generate_zshrc_from_manifest(&manifest)?;
+
+        assert!(!content.contains("setopt"));
+        assert!(!content.contains("Shell options"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_spaceship_init() -> Result<()> {
         let mut manifest = create_test_manifest("prezto", vec![], HashMap::new());
@@ -1223,4 +2076,180 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_key_mode_before_plugins_and_bindings_after() {
+        let mut manifest = create_test_manifest("oh-my-zsh", vec!["git".to_string()], HashMap::new());
+        manifest.keys.mode = Some(KeyMode::Vi);
+        manifest.keys.widgets = vec!["edit-command-line".to_string()];
+        manifest.keys.bind = vec![
+            crate::core::manifest::KeyBinding {
+                keys: "^R".to_string(),
+                widget: "fzf-history-widget".to_string(),
+                keymap: None,
+            },
+            crate::core::manifest::KeyBinding {
+                keys: "jk".to_string(),
+                widget: "vi-cmd-mode".to_string(),
+                keymap: Some("viins".to_string()),
+            },
+        ];
+        manifest.aliases.plain.insert("ll".to_string(), "ls -la".to_string());
+
+        let output = generate_zshrc_from_manifest(&manifest).unwrap();
+        assert!(output.contains("# Line editing mode (from profile.toml [keys])\nbindkey -v\n"));
+        assert!(output.contains(
+            "# Key bindings (from profile.toml [keys])\nzle -N edit-command-line\nbindkey '^R' fzf-history-widget\nbindkey -M viins 'jk' vi-cmd-mode\n"
+        ));
+
+        // Plugins bind in the keymap chosen by the mode, and user bindings win over theirs
+        let mode = output.find("bindkey -v").unwrap();
+        let framework = output.find("source $ZSH/oh-my-zsh.sh").unwrap();
+        let keys = output.find("zle -N edit-command-line").unwrap();
+        let aliases = output.find("alias ll=").unwrap();
+        assert!(mode < framework && framework < keys && keys < aliases);
+        assert_eq!(output.matches("bindkey -v").count(), 1);
+    }
+
+    #[test]
+    fn test_no_key_bindings_section_when_empty() {
+        let manifest = create_test_manifest("zap", vec![], HashMap::new());
+        let output = generate_zshrc_from_manifest(&manifest).unwrap();
+        assert!(!output.contains("bindkey"));
+    }
+
+    #[test]
+    fn test_completion_emitted_for_all_frameworks() -> Result<()> {
+        for framework in ["oh-my-zsh", "zimfw", "prezto", "zinit", "zap"] {
+            let mut manifest = create_test_manifest(framework, vec![], HashMap::new());
+            manifest.completion.matchers = vec!["m:{a-z}={A-Z}".to_string(), "r:|=*".to_string()];
+            manifest.completion.menu_select = true;
+            manifest.completion.cache = true;
+            manifest.completion.fpath = vec!["~/.zfunc".to_string()];
+
+            let output = generate_zshrc_from_manifest(&manifest)?;
+            assert!(
+                output.contains("zstyle ':completion:*' matcher-list 'm:{a-z}={A-Z}' 'r:|=*'\n"),
+                "{framework}"
+            );
+            assert!(output.contains("zstyle ':completion:*' menu select\n"), "{framework}");
+            assert!(output.contains("zstyle ':completion:*' cache-path \"$ZDOTDIR/.zcompcache\"\n"), "{framework}");
+
+            // fpath before the framework's compinit, styles after the framework
+            let fpath = output.find("fpath=(\"$HOME/.zfunc\" $fpath)").unwrap();
+            let framework_init = output.find(&format!("# {framework} configuration")).unwrap();
+            let styles = output.find("# Completion (from profile.toml [completion])").unwrap();
+            assert!(fpath < framework_init && framework_init < styles, "{framework}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_zprof_completion_loaded_after_framework() -> Result<()> {
+        let mut manifest = create_test_manifest("zap", vec![], HashMap::new());
+        let line = "(( $+commands[zprof] )) && source <(COMPLETE=zsh command zprof)\n";
+        assert!(!generate_zshrc_from_manifest(&manifest)?.contains(line));
+
+        manifest.completion.zprof = true;
+        let output = generate_zshrc_from_manifest(&manifest)?;
+        let compinit = output.find("compinit").unwrap();
+        assert!(output.find(line).unwrap() > compinit);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compinit_for_zinit_and_zap() -> Result<()> {
+        let zinit = generate_zshrc_from_manifest(&create_test_manifest("zinit", vec!["zsh-users/zsh-completions".to_string()], HashMap::new()))?;
+        assert!(zinit.contains("zinit light zsh-users/zsh-completions\n\n# Initialize completion system\nautoload -Uz compinit\ncompinit\nzinit cdreplay -q\n"));
+
+        let mut manifest = create_test_manifest("zap", vec![], HashMap::new());
+        manifest.completion.fast_init = true;
+        let zap = generate_zshrc_from_manifest(&manifest)?;
+        assert!(zap.contains("compinit -C -d \"$dump\"\n"));
+        assert!(zap.contains("local -a fresh=($dump(N.mh-24))\n"));
+        assert!(!zap.contains("\ncompinit\n"));
+
+        // Frameworks that run compinit themselves don't get a second one
+        let omz = generate_zshrc_from_manifest(&create_test_manifest("oh-my-zsh", vec![], HashMap::new()))?;
+        assert!(!omz.contains("compinit"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_when_shell_mode_emits_guarded_blocks() -> Result<()> {
+        let mut manifest = create_test_manifest("zap", vec![], HashMap::new());
+        manifest.when.resolve = WhenResolve::Shell;
+
+        let mut linux = manifest::Overlay::default();
+        linux.aliases.plain.insert("open".to_string(), "xdg-open".to_string());
+        linux.path.prepend = vec!["/snap/bin".to_string()];
+        manifest.when.os.insert("linux".to_string(), linux);
+
+        let mut work = manifest::Overlay::default();
+        work.env.insert("AWS_PROFILE".to_string(), "work".into());
+        manifest.when.host.insert("work-laptop".to_string(), work);
+
+        let zshrc = generate_zshrc_from_manifest(&manifest)?;
+        assert!(zshrc.contains("# [when.os.\"linux\"]\nif [[ $OSTYPE == linux* ]]; then\n  # PATH (from profile.toml [path])\n  typeset -U path\n  path=(\"/snap/bin\" $path)\nfi\n"));
+        assert!(zshrc.contains("if [[ $OSTYPE == linux* ]]; then\n  # Profile aliases (from profile.toml [aliases])\n  alias open='xdg-open'\nfi\n"));
+        assert!(!zshrc.contains("AWS_PROFILE"));
+
+        let zshenv = generate_zshenv_from_manifest(&manifest)?;
+        assert!(zshenv.contains("# [when.host.\"work-laptop\"]\nif [[ ${HOST:l} == 'work-laptop' || ${${HOST%%.*}:l} == 'work-laptop' ]]; then\n  # Custom environment variables\n  export AWS_PROFILE=\"work\"\nfi\n"));
+        assert!(!zshenv.contains("OSTYPE"));
+
+        // Resolved at generation time, nothing is guarded
+        manifest.when.resolve = WhenResolve::Generate;
+        assert!(!generate_zshrc_from_manifest(&manifest)?.contains("OSTYPE"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_snippets_placed_by_phase() -> Result<()> {
+        for framework in ["oh-my-zsh", "zimfw", "prezto", "zinit", "zap"] {
+            let mut manifest = create_test_manifest(framework, vec![], HashMap::new());
+            manifest.profile.prompt_mode = PromptMode::PromptEngine { engine: "starship".to_string() };
+            for (phase, code) in [
+                (SnippetPhase::PreFramework, "echo pre"),
+                (SnippetPhase::PostPlugins, "echo plugins"),
+                (SnippetPhase::PostPrompt, "echo prompt"),
+                (SnippetPhase::Zshenv, "echo env"),
+            ] {
+                manifest.snippets.push(manifest::Snippet { code: Some(code.to_string()), file: None, phase });
+            }
+            manifest.snippets.push(manifest::Snippet {
+                code: None,
+                file: Some("snippets/work.zsh".to_string()),
+                phase: SnippetPhase::PostPlugins,
+            });
+
+            let zshrc = generate_zshrc_from_manifest(&manifest)?;
+            let pos = |needle: &str| zshrc.find(needle).unwrap_or_else(|| panic!("{framework}: missing {needle}"));
+            assert!(pos("echo pre") < pos(&format!("# {framework} configuration")), "{framework}");
+            assert!(pos("echo plugins") < pos("# Prompt engine initialization"), "{framework}");
+            assert!(pos("source \"$ZDOTDIR/snippets/work.zsh\"") < pos("# Prompt engine initialization"), "{framework}");
+            assert!(pos("# Prompt engine initialization") < pos("echo prompt"), "{framework}");
+            assert!(!zshrc.contains("echo env"), "{framework}");
+
+            let zshenv = generate_zshenv_from_manifest(&manifest)?;
+            assert!(zshenv.contains("# Snippet (from profile.toml [[snippets]], phase zshenv)\necho env\n"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_zprofile_from_manifest() -> Result<()> {
+        let mut manifest = create_test_manifest("zap", vec![], HashMap::new());
+        manifest.snippets.push(manifest::Snippet {
+            code: Some("eval \"$(/opt/homebrew/bin/brew shellenv)\"".to_string()),
+            file: None,
+            phase: SnippetPhase::Zprofile,
+        });
+
+        let zprofile = generate_zprofile_from_manifest(&manifest)?;
+        assert!(zprofile.starts_with(GENERATED_HEADER));
+        assert!(zprofile.ends_with("phase zprofile)\neval \"$(/opt/homebrew/bin/brew shellenv)\"\n"));
+        assert!(!generate_zshrc_from_manifest(&manifest)?.contains("brew shellenv"));
+        Ok(())
+    }
 }
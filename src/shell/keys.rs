@@ -0,0 +1,129 @@
+//! Key sequence and keymap validation
+//!
+//! Used to check the `[keys]` section of profile.toml before any `bindkey`
+//! lines are generated. Key sequences use `bindkey`'s own notation: `^X` for
+//! control characters, and backslash escapes such as `\e`, `\C-x`, `\M-x`,
+//! `\033` and `\x1b`. Literal control characters are rejected so manifests
+//! stay readable and diffable.
+
+/// Keymaps that exist in every zsh with zle, plus `menuselect` from zsh/complist
+pub const KEYMAPS: &[&str] = &[
+    "main", "emacs", "viins", "vicmd", "viopp", "visual", "isearch", "command", "menuselect",
+];
+
+/// Characters allowed after `^` in a key sequence
+const CARET_CHARS: &str = "?@[\\]^_";
+
+/// Simple backslash escapes understood by `bindkey`
+const SIMPLE_ESCAPES: &str = "aebEfnrtv\\^'\"";
+
+/// Check a `bindkey` key sequence, returning a description of the first problem
+pub fn validate_key_sequence(sequence: &str) -> Result<(), String> {
+    if sequence.is_empty() {
+        return Err("key sequence is empty".to_string());
+    }
+
+    let chars: Vec<char> = sequence.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '^' => {
+                let Some(&next) = chars.get(i + 1) else {
+                    return Err("'^' at the end must be followed by a letter, e.g. '^R'".to_string());
+                };
+                if !next.is_ascii_alphabetic() && !CARET_CHARS.contains(next) {
+                    return Err(format!("'^{next}' is not a control character; use a letter or one of {CARET_CHARS}"));
+                }
+                i += 2;
+            }
+            '\\' => {
+                let Some(&next) = chars.get(i + 1) else {
+                    return Err("trailing '\\' is not a complete escape".to_string());
+                };
+                match next {
+                    'C' | 'M' => {
+                        if chars.get(i + 2) != Some(&'-') || i + 3 >= chars.len() {
+                            return Err(format!("'\\{next}-' must be followed by a key, e.g. '\\{next}-x'"));
+                        }
+                        // The modified key is checked on the next iteration
+                        i += 3;
+                    }
+                    'x' => {
+                        let digits = chars[i + 2..].iter().take(2).take_while(|d| d.is_ascii_hexdigit()).count();
+                        if digits == 0 {
+                            return Err("'\\x' must be followed by hex digits, e.g. '\\x1b'".to_string());
+                        }
+                        i += 2 + digits;
+                    }
+                    '0'..='7' => {
+                        let digits = chars[i + 1..].iter().take(3).take_while(|d| matches!(**d, '0'..='7')).count();
+                        i += 1 + digits;
+                    }
+                    _ if SIMPLE_ESCAPES.contains(next) => i += 2,
+                    _ => return Err(format!("unknown escape '\\{next}'")),
+                }
+            }
+            c if c.is_control() => {
+                return Err(format!(
+                    "contains a literal control character (U+{:04X}); write it as ^X or an escape like \\e",
+                    c as u32
+                ));
+            }
+            _ => i += 1,
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that a widget name can be written unquoted in a `bindkey` line
+pub fn is_valid_widget_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '+'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_key_sequences() {
+        for sequence in ["^R", "^[[A", "^?", "\\e[1;5C", "\\C-x\\C-e", "\\M-\\C-h", "\\033", "\\x1b", "jk", " ", "^x^e"] {
+            assert!(validate_key_sequence(sequence).is_ok(), "{sequence} should be valid");
+        }
+    }
+
+    #[test]
+    fn test_malformed_key_sequences() {
+        let cases = [
+            ("", "empty"),
+            ("^", "at the end"),
+            ("^1", "not a control character"),
+            ("\\", "trailing"),
+            ("\\C-", "must be followed by a key"),
+            ("\\Cx", "must be followed by a key"),
+            ("\\xZZ", "hex digits"),
+            ("\\q", "unknown escape"),
+            ("a\u{1b}b", "literal control character"),
+        ];
+        for (sequence, expected) in cases {
+            let err = validate_key_sequence(sequence).unwrap_err();
+            assert!(err.contains(expected), "{sequence:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_widget_names() {
+        assert!(is_valid_widget_name("fzf-history-widget"));
+        assert!(is_valid_widget_name("history-substring-search-up"));
+        assert!(is_valid_widget_name(".accept-line"));
+        assert!(!is_valid_widget_name(""));
+        assert!(!is_valid_widget_name("-s"));
+        assert!(!is_valid_widget_name("my widget"));
+        assert!(!is_valid_widget_name("widget; rm -rf ~"));
+    }
+}
@@ -3,4 +3,6 @@
 //! Handles generation of .zshrc and .zshenv files from profile manifests.
 
 pub mod generator;
+pub mod keys;
+pub mod options;
 pub mod zdotdir;
@@ -0,0 +1,157 @@
+//! Known zsh option names
+//!
+//! Used to validate the `[options]` section of profile.toml before any
+//! `setopt`/`unsetopt` lines are generated. Matching follows zsh's own rules:
+//! option names are case insensitive, underscores are ignored, and a leading
+//! `no` inverts the option (`NO_BEEP` is the same as unsetting `BEEP`).
+
+/// All option names understood by zsh 5.x (see `man zshoptions`), including
+/// the documented aliases such as `HIST_APPEND` and `DOT_GLOB`
+pub const ZSH_OPTIONS: &[&str] = &[
+    // Changing Directories
+    "auto_cd", "auto_pushd", "cdable_vars", "cd_silent", "chase_dots", "chase_links",
+    "posix_cd", "pushd_ignore_dups", "pushd_minus", "pushd_silent", "pushd_to_home",
+    // Completion
+    "always_last_prompt", "always_to_end", "auto_list", "auto_menu", "auto_name_dirs",
+    "auto_param_keys", "auto_param_slash", "auto_remove_slash", "bash_auto_list",
+    "complete_aliases", "complete_in_word", "glob_complete", "hash_list_all",
+    "list_ambiguous", "list_beep", "list_packed", "list_rows_first", "list_types",
+    "menu_complete", "rec_exact",
+    // Expansion and Globbing
+    "bad_pattern", "bare_glob_qual", "brace_ccl", "case_glob", "case_match", "case_paths",
+    "csh_null_glob", "equals", "extended_glob", "force_float", "glob", "glob_assign",
+    "glob_dots", "glob_star_short", "glob_subst", "hist_subst_pattern", "ignore_braces",
+    "ignore_close_braces", "ksh_glob", "magic_equal_subst", "mark_dirs", "multibyte",
+    "nomatch", "null_glob", "numeric_glob_sort", "rc_expand_param", "rematch_pcre",
+    "sh_glob", "unset", "warn_create_global", "warn_nested_var",
+    // History
+    "append_history", "bang_hist", "extended_history", "hist_allow_clobber", "hist_beep",
+    "hist_expire_dups_first", "hist_fcntl_lock", "hist_find_no_dups",
+    "hist_ignore_all_dups", "hist_ignore_dups", "hist_ignore_space", "hist_lex_words",
+    "hist_no_functions", "hist_no_store", "hist_reduce_blanks", "hist_save_by_copy",
+    "hist_save_no_dups", "hist_verify", "inc_append_history", "inc_append_history_time",
+    "share_history",
+    // Initialisation
+    "all_export", "global_export", "global_rcs", "rcs",
+    // Input/Output
+    "aliases", "clobber", "clobber_empty", "correct", "correct_all", "dvorak",
+    "flow_control", "ignore_eof", "interactive_comments", "hash_cmds", "hash_dirs",
+    "hash_executables_only", "mail_warning", "path_dirs", "path_script",
+    "print_eight_bit", "print_exit_value", "rc_quotes", "rm_star_silent", "rm_star_wait",
+    "short_loops", "short_repeat", "sun_keyboard_hack",
+    // Job Control
+    "auto_continue", "auto_resume", "bg_nice", "check_jobs", "check_running_jobs", "hup",
+    "long_list_jobs", "monitor", "notify", "posix_jobs",
+    // Prompting
+    "prompt_bang", "prompt_cr", "prompt_sp", "prompt_percent", "prompt_subst",
+    "transient_rprompt",
+    // Scripts and Functions
+    "alias_func_def", "c_bases", "c_precedences", "debug_before_cmd", "err_exit",
+    "err_return", "eval_lineno", "exec", "function_argzero", "local_loops",
+    "local_options", "local_patterns", "local_traps", "multi_func_def", "multios",
+    "octal_zeroes", "pipe_fail", "source_trace", "typeset_silent", "typeset_to_unset",
+    "verbose", "xtrace",
+    // Shell Emulation
+    "append_create", "bash_rematch", "bsd_echo", "continue_on_error",
+    "csh_junkie_history", "csh_junkie_loops", "csh_junkie_quotes", "csh_nullcmd",
+    "ksh_arrays", "ksh_autoload", "ksh_option_print", "ksh_typeset",
+    "ksh_zero_subscript", "posix_aliases", "posix_argzero", "posix_builtins",
+    "posix_identifiers", "posix_strings", "posix_traps", "sh_file_expansion",
+    "sh_nullcmd", "sh_option_letters", "sh_word_split", "traps_async",
+    // Shell State
+    "interactive", "login", "privileged", "restricted", "shin_stdin", "single_command",
+    // Zle
+    "beep", "combining_chars", "emacs", "overstrike", "single_line_zle", "vi", "zle",
+    // Option aliases
+    "brace_expand", "dot_glob", "hash_all", "hist_append", "hist_expand", "log",
+    "mail_warn", "one_cmd", "physical", "prompt_vars", "stdin", "track_all",
+];
+
+/// Normalize an option name the way zsh does: lowercase, underscores removed
+pub fn normalize_option_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Resolve an option name to its normalized base name and polarity
+///
+/// Returns `Some((base, true))` for a known option and `Some((base, false))`
+/// for a `no`-prefixed form of a known option, e.g. `NO_BEEP` → `("beep", false)`.
+/// Names that are options in their own right (like `NOTIFY`) are never treated
+/// as negations. Returns `None` for unknown names.
+pub fn resolve_option(name: &str) -> Option<(String, bool)> {
+    let normalized = normalize_option_name(name);
+    if normalized.is_empty() {
+        return None;
+    }
+
+    let is_known = |candidate: &str| {
+        ZSH_OPTIONS
+            .iter()
+            .any(|opt| normalize_option_name(opt) == candidate)
+    };
+
+    if is_known(&normalized) {
+        return Some((normalized, true));
+    }
+
+    if let Some(base) = normalized.strip_prefix("no") {
+        if !base.is_empty() && is_known(base) {
+            return Some((base.to_string(), false));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_known_option(name: &str) -> bool {
+        resolve_option(name).is_some()
+    }
+
+    #[test]
+    fn test_normalize_option_name() {
+        assert_eq!(normalize_option_name("HIST_IGNORE_DUPS"), "histignoredups");
+        assert_eq!(normalize_option_name("autocd"), "autocd");
+        assert_eq!(normalize_option_name("Auto_Cd"), "autocd");
+    }
+
+    #[test]
+    fn test_known_options_any_spelling() {
+        assert!(is_known_option("AUTO_CD"));
+        assert!(is_known_option("autocd"));
+        assert!(is_known_option("Hist_Find_No_Dups"));
+        assert!(is_known_option("extendedglob"));
+    }
+
+    #[test]
+    fn test_no_prefix_inverts_option() {
+        assert_eq!(resolve_option("NO_BEEP"), Some(("beep".to_string(), false)));
+        assert_eq!(resolve_option("nobeep"), Some(("beep".to_string(), false)));
+        // NOTIFY and NOMATCH are real options, not negations
+        assert_eq!(resolve_option("NOTIFY"), Some(("notify".to_string(), true)));
+        assert_eq!(resolve_option("NOMATCH"), Some(("nomatch".to_string(), true)));
+    }
+
+    #[test]
+    fn test_unknown_options_rejected() {
+        assert!(!is_known_option("AUTO_CDD"));
+        assert!(!is_known_option(""));
+        assert!(!is_known_option("___"));
+        assert!(!is_known_option("no"));
+        assert!(!is_known_option("set -o vi"));
+    }
+
+    #[test]
+    fn test_option_names_are_unique() {
+        let mut seen = std::collections::HashSet::new();
+        for opt in ZSH_OPTIONS {
+            assert!(seen.insert(normalize_option_name(opt)), "duplicate option: {opt}");
+        }
+    }
+}
@@ -203,8 +203,40 @@ fn remove_zprof_section(content: &str) -> String {
     result
 }
 
+/// ZDOTDIR set by the zprof-managed section of ~/.zshenv
+///
+/// Returns None if ~/.zshenv doesn't exist or has no managed section with a
+/// ZDOTDIR export.
+pub fn managed_zdotdir() -> Result<Option<PathBuf>> {
+    let zshenv_path = get_zshenv_path()?;
+    if !zshenv_path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&zshenv_path)
+        .with_context(|| format!("Failed to read .zshenv at {}", zshenv_path.display()))?;
+    Ok(parse_managed_zdotdir(&content))
+}
+
+/// The ZDOTDIR export inside the zprof-managed section of `content`
+fn parse_managed_zdotdir(content: &str) -> Option<PathBuf> {
+    let mut in_zprof_section = false;
+    for line in content.lines() {
+        if line.contains("Managed by zprof - DO NOT EDIT") {
+            in_zprof_section = true;
+        } else if in_zprof_section && line.contains("==========") {
+            return None;
+        } else if in_zprof_section {
+            if let Some(path) = line.strip_prefix("export ZDOTDIR=\"").and_then(|rest| rest.strip_suffix('"')) {
+                return Some(PathBuf::from(path));
+            }
+        }
+    }
+    None
+}
+
 /// Get the path to ~/.zshenv
-fn get_zshenv_path() -> Result<PathBuf> {
+pub fn get_zshenv_path() -> Result<PathBuf> {
     let home = dirs::home_dir()
         .context("Failed to get home directory. Ensure HOME environment variable is set.")?;
     Ok(home.join(".zshenv"))
@@ -256,6 +288,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_managed_zdotdir() {
+        let content = "# ========== Managed by zprof - DO NOT EDIT THIS SECTION ==========\n\
+                       export ZDOTDIR=\"/home/user/.zsh-profiles/profiles/work\"\n\
+                       # ===================================================================\n\
+                       export ZDOTDIR=\"/elsewhere\"\n";
+        assert_eq!(
+            parse_managed_zdotdir(content),
+            Some(PathBuf::from("/home/user/.zsh-profiles/profiles/work"))
+        );
+        assert_eq!(parse_managed_zdotdir("export ZDOTDIR=\"/elsewhere\"\n"), None);
+        assert_eq!(parse_managed_zdotdir(&remove_zprof_section(content)), None);
+    }
+
     #[test]
     fn test_has_existing_zdotdir_empty() {
         // Can't easily test without real home directory
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to set up test environment with temporary home directory
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("profiles"))?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to create a profile with just a profile.toml
+fn write_profile(home_dir: &Path, name: &str) -> Result<()> {
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join(name);
+    fs::create_dir_all(&profile_dir)?;
+    fs::write(
+        profile_dir.join("profile.toml"),
+        format!("[profile]\nname = \"{name}\"\nframework = \"zap\"\nprompt_mode = \"framework_theme\"\nframework_theme = \"\"\n"),
+    )?;
+    Ok(())
+}
+
+/// Helper to run zprof command with custom HOME
+fn run_zprof_with_home(home_dir: &Path, args: &[&str]) -> Result<std::process::Output> {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.env_remove("ZPROF_PROFILE");
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+#[test]
+fn test_auto_resolve_uses_marker_then_rules() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "personal")?;
+    write_profile(&home_dir, "work")?;
+    write_profile(&home_dir, "client")?;
+    fs::write(
+        home_dir.join(".zsh-profiles").join("config.toml"),
+        "active_profile = \"personal\"\n\n[auto]\nmode = \"switch\"\n\n[[auto.rules]]\nprofile = \"client\"\nglob = \"~/clients/*\"\n\n[[auto.rules]]\nprofile = \"work\"\npath = \"~/work\"\n",
+    )?;
+    let monorepo = home_dir.join("work").join("monorepo");
+    let client_api = home_dir.join("clients").join("acme").join("api");
+    fs::create_dir_all(monorepo.join("services"))?;
+    fs::create_dir_all(&client_api)?;
+    fs::write(monorepo.join(".zprof-profile"), "client\n")?;
+
+    let resolve = |dir: &Path| -> Result<String> {
+        let output = run_zprof_with_home(&home_dir, &["auto", "resolve", &dir.display().to_string()])?;
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        Ok(String::from_utf8(output.stdout)?)
+    };
+
+    assert_eq!(
+        resolve(&client_api)?,
+        format!("client\t{}\tswitch\n", home_dir.join("clients").join("acme").display())
+    );
+    assert_eq!(resolve(&home_dir.join("work"))?, format!("work\t{}\tswitch\n", home_dir.join("work").display()));
+    assert_eq!(resolve(&monorepo.join("services"))?, format!("client\t{}\tswitch\n", monorepo.display()));
+    assert_eq!(resolve(&home_dir)?, "");
+
+    // Markers naming a missing profile are ignored by the shell hook
+    fs::write(monorepo.join(".zprof-profile"), "gone\n")?;
+    assert_eq!(resolve(&monorepo)?, "");
+
+    // Names that escape the profiles directory never reach the hook, even
+    // when something profile-like exists there
+    let evil = home_dir.join("repo").join(".evil");
+    fs::create_dir_all(&evil)?;
+    fs::write(evil.join("profile.toml"), "[profile]\nname = \"evil\"\n")?;
+    fs::write(client_api.join(".zprof-profile"), "../../repo/.evil\n")?;
+    let output = run_zprof_with_home(&home_dir, &["auto", "resolve", &client_api.display().to_string()])?;
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    fs::remove_file(client_api.join(".zprof-profile"))?;
+
+    let output = run_zprof_with_home(&home_dir, &["auto", "status", &monorepo.display().to_string()])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(stdout.contains(".zprof-profile → 'gone'"), "{stdout}");
+    assert!(stdout.contains("Rule 1 (glob = \"~/clients/*\") → 'client': no match"), "{stdout}");
+    assert!(stdout.contains("Rule 2 (path = \"~/work\") → 'work' (matches, but an earlier match wins)"), "{stdout}");
+    assert!(stdout.contains("Profile 'gone' doesn't exist"), "{stdout}");
+    assert!(stdout.contains("Current profile: personal"), "{stdout}");
+    assert!(stdout.contains("Mode: switch"), "{stdout}");
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use anyhow::Result;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to set up test environment with temporary home directory
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("profiles"))?;
+    fs::create_dir_all(home_dir.join("bin"))?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to write a profile.toml and .zshrc for a profile
+fn write_profile(home_dir: &Path, name: &str) -> Result<PathBuf> {
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join(name);
+    fs::create_dir_all(&profile_dir)?;
+    fs::write(
+        profile_dir.join("profile.toml"),
+        format!(
+            "[profile]\nname = \"{name}\"\nframework = \"oh-my-zsh\"\nprompt_mode = \"framework_theme\"\nframework_theme = \"\"\n\n[plugins]\nenabled = [\"git\"]\n"
+        ),
+    )?;
+    fs::write(profile_dir.join(".zshrc"), "# zprof profile\n")?;
+    Ok(profile_dir)
+}
+
+/// Helper to put a stand-in `zsh` script first on PATH
+fn fake_zsh(home_dir: &Path, script: &str) -> Result<()> {
+    let path = home_dir.join("bin").join("zsh");
+    fs::write(&path, format!("#!/bin/sh\n{script}"))?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+/// Helper to run zprof command with custom HOME and the fake zsh on PATH
+fn run_zprof_with_home(home_dir: &Path, args: &[&str]) -> Result<std::process::Output> {
+    let path = format!("{}:{}", home_dir.join("bin").display(), std::env::var("PATH").unwrap_or_default());
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.env("PATH", path);
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+#[test]
+fn test_bench_stores_results_and_flags_regressions() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "work")?;
+    fake_zsh(&home_dir, "sleep 0.02\n")?;
+
+    let cache = home_dir.join(".zsh-profiles/cache/bench/work.json");
+    fs::create_dir_all(cache.parent().unwrap())?;
+    fs::write(
+        &cache,
+        r#"{"profile":"work","runs":3,"mean_ms":1.0,"p50_ms":1.0,"p95_ms":1.0,"measured_at":"2026-01-01T00:00:00Z"}"#,
+    )?;
+
+    let output = run_zprof_with_home(&home_dir, &["bench", "work", "--runs", "3"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("MEAN") && stdout.contains("P95"), "{stdout}");
+    assert!(stdout.contains("slower than 2026-01-01 00:00 (1.0 ms)"), "{stdout}");
+    assert!(stdout.contains("zprof bench work --breakdown"), "{stdout}");
+
+    assert!(stdout.contains("zprof bench work --accept"), "{stdout}");
+
+    // The regression doesn't replace the baseline until it is accepted
+    let stored: serde_json::Value = serde_json::from_str(&fs::read_to_string(&cache)?)?;
+    assert_eq!(stored["mean_ms"], 1.0);
+    let output = run_zprof_with_home(&home_dir, &["bench", "work", "--runs", "3"])?;
+    assert!(String::from_utf8_lossy(&output.stdout).contains("slower than 2026-01-01 00:00"));
+
+    let output = run_zprof_with_home(&home_dir, &["bench", "work", "--runs", "3", "--accept"])?;
+    assert!(output.status.success());
+    let stored: serde_json::Value = serde_json::from_str(&fs::read_to_string(&cache)?)?;
+    assert_eq!(stored["runs"], 3);
+    assert!(stored["mean_ms"].as_f64().unwrap() >= 20.0);
+
+    let output = run_zprof_with_home(&home_dir, &["regenerate", "work"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Run 'zprof bench work' to compare startup time"), "{stdout}");
+
+    // A profile without generated files can't be timed
+    fs::remove_file(home_dir.join(".zsh-profiles/profiles/work/.zshrc"))?;
+    let output = run_zprof_with_home(&home_dir, &["bench", "work"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("zprof regenerate work"));
+
+    Ok(())
+}
+
+#[test]
+fn test_bench_breakdown_attributes_functions_to_plugins() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let profile_dir = write_profile(&home_dir, "work")?;
+    let plugin_file = profile_dir.join(".oh-my-zsh/plugins/git/git.plugin.zsh");
+    let lib_file = profile_dir.join(".oh-my-zsh/lib/git.zsh");
+
+    // Canned zprof report for the profiling run; timing runs just exit
+    fake_zsh(
+        &home_dir,
+        &format!(
+            r#"case "$3" in
+zprof*)
+  echo 'num  calls                time                       self            name'
+  echo '-----------------------------------------------------------------------------------'
+  echo ' 1)    1          10.00    10.00   50.00%      9.00     9.00   45.00%  compinit'
+  echo ' 2)    2           6.00     3.00   30.00%      6.00     3.00   30.00%  git_prompt_info'
+  echo ' 3)    1           2.00     2.00   10.00%      2.00     2.00   10.00%  _omz_git_prompt_status'
+  echo ' 4)    1           3.00     3.00   15.00%      3.00     3.00   15.00%  git_current_branch'
+  echo ''
+  echo '@@zprof-sources'
+  printf 'git_prompt_info\t{plugin}\n_omz_git_prompt_status\t{plugin}\ngit_current_branch\t{lib}\n'
+  ;;
+esac
+"#,
+            plugin = plugin_file.display(),
+            lib = lib_file.display()
+        ),
+    )?;
+
+    let output = run_zprof_with_home(&home_dir, &["bench", "work", "--runs", "1", "--breakdown"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("Time spent in functions for 'work':"), "{stdout}");
+
+    let rows: Vec<String> = stdout
+        .lines()
+        .skip_while(|line| !line.starts_with("Time spent"))
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    assert_eq!(rows, vec!["compinit 9.0 ms", "git 8.0 ms", "oh-my-zsh 3.0 ms"]);
+
+    let stored = fs::read_to_string(home_dir.join(".zsh-profiles/cache/bench/work.json"))?;
+    assert!(stored.contains("\"breakdown\""), "{stored}");
+    assert!(!home_dir.join(".zsh-profiles/cache/bench/work.zdotdir").exists());
+
+    Ok(())
+}
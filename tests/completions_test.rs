@@ -0,0 +1,66 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to set up test environment with temporary home directory
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("profiles"))?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to create a profile with just a profile.toml
+fn write_profile(home_dir: &Path, name: &str, framework: &str, plugins: &[&str]) -> Result<()> {
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join(name);
+    fs::create_dir_all(&profile_dir)?;
+    fs::write(
+        profile_dir.join("profile.toml"),
+        format!(
+            "[profile]\nname = \"{name}\"\nframework = \"{framework}\"\nprompt_mode = \"framework_theme\"\nframework_theme = \"\"\n\n[plugins]\nenabled = {plugins:?}\n"
+        ),
+    )?;
+    Ok(())
+}
+
+/// Helper to ask zprof for zsh completions of the last word in `words`
+fn complete(home_dir: &Path, words: &[&str]) -> Result<Vec<String>> {
+    let output = Command::new(env!("CARGO_BIN_EXE_zprof"))
+        .env("HOME", home_dir)
+        .env("COMPLETE", "zsh")
+        .env("_CLAP_COMPLETE_INDEX", (words.len() - 1).to_string())
+        .env("_CLAP_IFS", "\n")
+        .arg("--")
+        .args(words)
+        .output()?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    Ok(String::from_utf8(output.stdout)?.lines().map(String::from).collect())
+}
+
+#[test]
+fn test_completions_script_and_dynamic_values() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "work", "zap", &["zsh-vi-mode"])?;
+    write_profile(&home_dir, "personal", "oh-my-zsh", &["git"])?;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_zprof")).args(["completions", "zsh"]).output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("#compdef zprof"));
+
+    let profiles = complete(&home_dir, &["zprof", "use", ""])?;
+    assert_eq!(profiles[..2], ["personal:oh-my-zsh", "work:zap"], "{profiles:?}");
+    assert!(complete(&home_dir, &["zprof", "create", "new", "--preset", ""])?
+        .iter()
+        .any(|candidate| candidate.starts_with("minimal:")));
+
+    // Plugins are limited to what the profile's framework can load
+    let plugins = complete(&home_dir, &["zprof", "plugin", "add", "work", ""])?;
+    assert!(plugins.iter().any(|candidate| candidate.starts_with("zsh-autosuggestions:")), "{plugins:?}");
+    assert!(!plugins.iter().any(|candidate| candidate.starts_with("docker:")), "{plugins:?}");
+    let plugins = complete(&home_dir, &["zprof", "plugin", "remove", "personal", ""])?;
+    assert!(plugins.contains(&"git".to_string()), "{plugins:?}");
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to set up test environment with temporary home directory
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("profiles"))?;
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("shared"))?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to write a profile.toml and .zshrc for a profile
+fn write_profile(home_dir: &Path, name: &str, manifest: &str) -> Result<PathBuf> {
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join(name);
+    fs::create_dir_all(&profile_dir)?;
+    fs::write(profile_dir.join("profile.toml"), manifest)?;
+    fs::write(profile_dir.join(".zshrc"), "# zprof profile\n")?;
+    Ok(profile_dir)
+}
+
+/// Helper to run zprof command with custom HOME
+fn run_zprof_with_home(home_dir: &PathBuf, args: &[&str]) -> Result<std::process::Output> {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+const BASE: &str = r#"schema_version = 2
+
+# The shared setup
+[profile]
+name = "base"
+framework = "oh-my-zsh"
+prompt_mode = "framework_theme"
+framework_theme = "robbyrussell"
+created = "2025-01-01T00:00:00Z"
+modified = "2025-01-01T00:00:00Z"
+
+[plugins]
+enabled = ["git"]
+"#;
+
+const CHILD: &str = r#"schema_version = 2
+
+[profile]
+name = "child"
+extends = "base"
+
+[env]
+EDITOR = "vim"
+"#;
+
+#[test]
+fn test_copy_creates_renamed_profile() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "base", BASE)?;
+
+    let output = run_zprof_with_home(&home_dir, &["copy", "base", "variant"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let variant_dir = home_dir.join(".zsh-profiles").join("profiles").join("variant");
+    let manifest = fs::read_to_string(variant_dir.join("profile.toml"))?;
+    assert!(manifest.contains("# The shared setup\n[profile]\nname = \"variant\"\n"), "{manifest}");
+    assert!(!manifest.contains("2025-01-01"), "timestamps are reset: {manifest}");
+    assert!(fs::read_to_string(variant_dir.join(".zshrc"))?.contains("# Profile: variant"));
+
+    // The source is untouched
+    let base = home_dir.join(".zsh-profiles").join("profiles").join("base");
+    assert_eq!(fs::read_to_string(base.join("profile.toml"))?, BASE);
+
+    // Copying onto an existing profile is refused
+    let output = run_zprof_with_home(&home_dir, &["copy", "base", "variant"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_updates_children_and_active_profile() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "base", BASE)?;
+    write_profile(&home_dir, "child", CHILD)?;
+
+    let output = run_zprof_with_home(&home_dir, &["use", "base"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = run_zprof_with_home(&home_dir, &["rename", "base", "shared"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Updated extends in: child"), "{stdout}");
+
+    let profiles = home_dir.join(".zsh-profiles").join("profiles");
+    assert!(!profiles.join("base").exists());
+    let manifest = fs::read_to_string(profiles.join("shared").join("profile.toml"))?;
+    assert!(manifest.contains("name = \"shared\""), "{manifest}");
+    let child = fs::read_to_string(profiles.join("child").join("profile.toml"))?;
+    assert!(child.contains("extends = \"shared\""), "{child}");
+
+    let config = fs::read_to_string(home_dir.join(".zsh-profiles").join("config.toml"))?;
+    assert!(config.contains("active_profile = \"shared\""), "{config}");
+    let zshenv = fs::read_to_string(home_dir.join(".zshenv"))?;
+    assert!(zshenv.contains(&format!("export ZDOTDIR=\"{}\"", profiles.join("shared").display())), "{zshenv}");
+    assert!(!zshenv.contains("profiles/base"), "{zshenv}");
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_rolls_back_when_a_child_fails_to_regenerate() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "base", BASE)?;
+    write_profile(&home_dir, "child", CHILD)?;
+    let output = run_zprof_with_home(&home_dir, &["use", "base"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // A descendant that doesn't validate fails the rename after the move
+    let broken = CHILD.replace("name = \"child\"", "name = \"broken\"").replace("EDITOR", "\"BAD-KEY\"");
+    write_profile(&home_dir, "broken", &broken)?;
+    let config = fs::read_to_string(home_dir.join(".zsh-profiles").join("config.toml"))?;
+    let zshenv = fs::read_to_string(home_dir.join(".zshenv"))?;
+
+    let output = run_zprof_with_home(&home_dir, &["rename", "base", "shared"])?;
+    assert!(!output.status.success());
+
+    let profiles = home_dir.join(".zsh-profiles").join("profiles");
+    assert!(!profiles.join("shared").exists());
+    assert_eq!(fs::read_to_string(profiles.join("base").join("profile.toml"))?, BASE);
+    assert_eq!(fs::read_to_string(profiles.join("child").join("profile.toml"))?, CHILD);
+    assert_eq!(fs::read_to_string(profiles.join("broken").join("profile.toml"))?, broken);
+    assert_eq!(fs::read_to_string(home_dir.join(".zsh-profiles").join("config.toml"))?, config);
+    assert_eq!(fs::read_to_string(home_dir.join(".zshenv"))?, zshenv);
+    assert!(fs::read_to_string(profiles.join("base").join(".zshrc"))?.contains("# Profile: base"));
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_rejects_invalid_name() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "base", BASE)?;
+
+    let output = run_zprof_with_home(&home_dir, &["rename", "base", "../escape"])?;
+    assert!(!output.status.success());
+    assert!(home_dir.join(".zsh-profiles").join("profiles").join("base").exists());
+
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to set up test environment with temporary home directory
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("profiles"))?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to write a profile.toml for a profile
+fn write_profile(home_dir: &Path, name: &str, manifest: &str) -> Result<PathBuf> {
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join(name);
+    fs::create_dir_all(&profile_dir)?;
+    let path = profile_dir.join("profile.toml");
+    fs::write(&path, manifest)?;
+    Ok(path)
+}
+
+/// Helper to run zprof command with custom HOME
+fn run_zprof_with_home(home_dir: &PathBuf, args: &[&str]) -> Result<std::process::Output> {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.current_dir(home_dir);
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+const WORK: &str = r#"schema_version = 2
+
+[profile]
+name = "work"
+framework = "oh-my-zsh"
+prompt_mode = "framework_theme"
+framework_theme = "robbyrussell"
+
+[plugins]
+enabled = ["git", "docker"]
+
+[env]
+EDITOR = "vim"
+"#;
+
+const PERSONAL: &str = r#"schema_version = 2
+
+[profile]
+name = "personal"
+framework = "oh-my-zsh"
+prompt_mode = "prompt_engine"
+prompt_engine = "starship"
+
+[plugins]
+enabled = ["git", "fzf"]
+
+[env]
+EDITOR = "nvim"
+PAGER = "less"
+"#;
+
+#[test]
+fn test_diff_shows_semantic_changes() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "work", WORK)?;
+    write_profile(&home_dir, "personal", PERSONAL)?;
+
+    let output = run_zprof_with_home(&home_dir, &["diff", "work", "personal"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("Comparing work → personal"), "{stdout}");
+    assert!(
+        stdout.contains("prompt: framework theme 'robbyrussell' → prompt engine 'starship'"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("framework: "), "{stdout}");
+    assert!(stdout.contains("Plugins:\n    + fzf\n    - docker\n"), "{stdout}");
+    assert!(stdout.contains("~ EDITOR: vim → nvim"), "{stdout}");
+    assert!(stdout.contains("+ PAGER=less"), "{stdout}");
+    assert!(!stdout.contains("@@"), "generated files are only diffed with --generated");
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_generated_files() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "work", WORK)?;
+    write_profile(&home_dir, "personal", PERSONAL)?;
+
+    let output = run_zprof_with_home(&home_dir, &["diff", "work", "personal", "--generated"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("--- a/.zshrc\n+++ b/.zshrc\n"), "{stdout}");
+    assert!(stdout.contains("\n+export PAGER="), "{stdout}");
+    assert!(!stdout.contains("# Generated: "), "{stdout}");
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_against_archive() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "work", WORK)?;
+
+    let archive = home_dir.join("work.zprof");
+    let output = run_zprof_with_home(&home_dir, &["export", "work", "-o", archive.to_str().unwrap()])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = run_zprof_with_home(&home_dir, &["diff", "work", archive.to_str().unwrap()])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✓ No differences"), "{stdout}");
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_against_own_export_with_when_overlay() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(
+        &home_dir,
+        "solo",
+        &format!("{WORK}\n[when.env.\"ZPROF_DIFF_OVERLAY\".plugins]\nenabled = [\"fzf\"]\n\n[when.env.\"ZPROF_DIFF_OVERLAY\".env]\nPAGER = \"less\"\n"),
+    )?;
+
+    let archive = home_dir.join("solo.zprof");
+    let output = run_zprof_with_home(&home_dir, &["export", "solo", "-o", archive.to_str().unwrap()])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // The overlay matches on this side and on the archive's alike
+    let output = Command::new(env!("CARGO_BIN_EXE_zprof"))
+        .env("HOME", &home_dir)
+        .env("ZPROF_DIFF_OVERLAY", "1")
+        .current_dir(&home_dir)
+        .args(["diff", "solo", archive.to_str().unwrap(), "--generated"])
+        .output()?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✓ No differences"), "{stdout}");
+    assert!(stdout.contains("✓ Generated files are identical"), "{stdout}");
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_child_against_own_export() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "work", WORK)?;
+    write_profile(&home_dir, "child", "[profile]\nname = \"child\"\nextends = \"work\"\n\n[env]\nPAGER = \"less\"\n")?;
+
+    let archive = home_dir.join("child.zprof");
+    let output = run_zprof_with_home(&home_dir, &["export", "child", "-o", archive.to_str().unwrap()])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = run_zprof_with_home(&home_dir, &["diff", "child", archive.to_str().unwrap(), "--generated"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✓ No differences"), "{stdout}");
+    assert!(stdout.contains("✓ Generated files are identical"), "{stdout}");
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_unknown_profile_fails() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "work", WORK)?;
+
+    let output = run_zprof_with_home(&home_dir, &["diff", "work", "missing"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to load profile 'missing'"));
+
+    Ok(())
+}
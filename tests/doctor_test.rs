@@ -0,0 +1,101 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to set up test environment with temporary home directory
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("profiles"))?;
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("shared"))?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to write a profile.toml and .zshrc for a profile
+fn write_profile(home_dir: &Path, name: &str, framework: &str) -> Result<PathBuf> {
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join(name);
+    fs::create_dir_all(&profile_dir)?;
+    fs::write(
+        profile_dir.join("profile.toml"),
+        format!(
+            "[profile]\nname = \"{name}\"\nframework = \"{framework}\"\nprompt_mode = \"framework_theme\"\nframework_theme = \"\"\n\n[plugins]\nenabled = [\"git\"]\n"
+        ),
+    )?;
+    fs::write(profile_dir.join(".zshrc"), "# zprof profile\n")?;
+    Ok(profile_dir)
+}
+
+/// Helper to run zprof command with custom HOME
+fn run_zprof_with_home(home_dir: &PathBuf, args: &[&str]) -> Result<std::process::Output> {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+#[test]
+fn test_doctor_reports_and_fixes_problems() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let profile_dir = write_profile(&home_dir, "work", "oh-my-zsh")?;
+    fs::create_dir_all(profile_dir.join(".oh-my-zsh").join(".git"))?;
+    let empty_plugin = profile_dir.join(".oh-my-zsh/custom/plugins/git");
+    fs::create_dir_all(&empty_plugin)?;
+    fs::write(home_dir.join(".zsh-profiles").join("config.toml"), "active_profile = \"work\"\n")?;
+
+    let output = run_zprof_with_home(&home_dir, &["doctor"])?;
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✓ config.toml: active profile is 'work'"), "{stdout}");
+    assert!(stdout.contains("✗ ~/.zshenv: no zprof-managed ZDOTDIR export"), "{stdout}");
+    assert!(stdout.contains("✓ profile 'work': framework: oh-my-zsh is installed"), "{stdout}");
+    assert!(stdout.contains("stop these plugins from loading: git"), "{stdout}");
+    assert!(stdout.contains("⚠ shared history"), "{stdout}");
+    assert!(stdout.contains("3 can be fixed automatically with 'zprof doctor --fix'"), "{stdout}");
+
+    let output = run_zprof_with_home(&home_dir, &["doctor", "--fix"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}\n{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("✓ rewrite ~/.zshenv to activate 'work'"), "{stdout}");
+    assert!(stdout.contains("✓ ~/.zshenv: activates 'work'"), "{stdout}");
+
+    let zshenv = fs::read_to_string(home_dir.join(".zshenv"))?;
+    assert!(zshenv.contains(&format!("export ZDOTDIR=\"{}\"", profile_dir.display())), "{zshenv}");
+    assert!(!empty_plugin.exists());
+    assert!(home_dir.join(".zsh-profiles/shared/.zsh_history").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_doctor_json_reports_invalid_manifest_and_placeholder_framework() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let profile_dir = write_profile(&home_dir, "zim", "zimfw")?;
+    // What the placeholder installer used to leave behind
+    fs::create_dir_all(profile_dir.join(".zim").join("modules"))?;
+    let broken = home_dir.join(".zsh-profiles/profiles/broken");
+    fs::create_dir_all(&broken)?;
+    fs::write(broken.join("profile.toml"), "[profile]\nname = \"broken\"\nframework = \"oh-my-zhs\"\n")?;
+
+    let output = run_zprof_with_home(&home_dir, &["doctor", "--json"])?;
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let checks = report["checks"].as_array().unwrap();
+    let check = |name: &str| {
+        checks
+            .iter()
+            .find(|c| c["name"] == name)
+            .unwrap_or_else(|| panic!("no check named {name}: {report}"))
+    };
+
+    assert_eq!(check("profile 'broken'")["status"], "fail");
+    assert_eq!(check("profile 'zim'")["status"], "pass");
+    let framework = check("profile 'zim': framework");
+    assert_eq!(framework["status"], "fail");
+    assert_eq!(framework["repair"]["action"], "install_framework");
+    assert_eq!(check("config.toml")["status"], "warn");
+    assert!(report["failures"].as_u64().unwrap() >= 2);
+
+    Ok(())
+}
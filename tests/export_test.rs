@@ -4,8 +4,8 @@ use std::fs;
 use std::path::PathBuf;
 use tempfile::TempDir;
 
-use zprof::archive::export;
-use zprof::core::manifest::{Manifest, PluginsSection, ProfileSection};
+use zprof::archive::{export, import};
+use zprof::core::manifest::{self, Manifest, PluginsSection, ProfileSection};
 
 /// Helper to create a test profile directory with manifest
 fn create_test_profile(base_dir: &std::path::Path, name: &str) -> Result<PathBuf> {
@@ -25,11 +25,22 @@ fn create_test_profile(base_dir: &std::path::Path, name: &str) -> Result<PathBuf
             },
             created: chrono::Utc::now(),
             modified: chrono::Utc::now(),
+            extends: None,
         },
         plugins: PluginsSection {
             enabled: vec!["git".to_string(), "docker".to_string()],
+            entry: Vec::new(),
         },
         env: std::collections::HashMap::new(),
+        aliases: Default::default(),
+        options: Default::default(),
+        path: Default::default(),
+        schema_version: manifest::CURRENT_SCHEMA_VERSION,
+        hooks: Default::default(),
+        keys: Default::default(),
+        completion: Default::default(),
+        when: Default::default(),
+        snippets: Vec::new(),
     };
 
     let toml = manifest.to_toml_string()?;
@@ -83,6 +94,165 @@ fn test_export_creates_archive() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[serial]
+fn test_archive_bundles_snippet_files() -> Result<()> {
+    use flate2::read::GzDecoder;
+    use tar::Archive;
+
+    let temp_dir = TempDir::new()?;
+    let profile_name = "test-snippets";
+    let original_home = std::env::var("HOME").ok();
+
+    std::env::set_var("HOME", temp_dir.path());
+    let profile_dir = create_test_profile(temp_dir.path(), profile_name)?;
+
+    // Snippet files may live in a subdirectory, which export otherwise skips
+    let mut manifest = manifest::parse_manifest(&fs::read_to_string(profile_dir.join("profile.toml"))?)?;
+    manifest.snippets.push(manifest::Snippet {
+        code: None,
+        file: Some("snippets/work.zsh".to_string()),
+        phase: manifest::SnippetPhase::PostPlugins,
+    });
+    fs::write(profile_dir.join("profile.toml"), manifest.to_toml_string()?)?;
+    fs::create_dir_all(profile_dir.join("snippets"))?;
+    fs::write(profile_dir.join("snippets").join("work.zsh"), "alias k=kubectl\n")?;
+
+    let output_path = temp_dir.path().join(format!("{profile_name}.zprof"));
+    let archive_path = export::export_profile(profile_name, Some(output_path))?;
+
+    let mut archive = Archive::new(GzDecoder::new(fs::File::open(&archive_path)?));
+    let found_files: Vec<String> = archive
+        .entries()?
+        .map(|entry| Ok(entry?.path()?.to_string_lossy().to_string()))
+        .collect::<Result<_>>()?;
+    assert!(found_files.contains(&"snippets/work.zsh".to_string()), "{found_files:?}");
+
+    // A missing snippet file fails the export instead of producing a broken archive
+    fs::remove_file(profile_dir.join("snippets").join("work.zsh"))?;
+    let err = export::export_profile(profile_name, Some(temp_dir.path().join("again.zprof"))).unwrap_err();
+    assert!(err.to_string().contains("snippets/work.zsh"), "{err}");
+
+    if let Some(home) = original_home {
+        std::env::set_var("HOME", home);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_export_leaves_out_overlay_secret_files() -> Result<()> {
+    use flate2::read::GzDecoder;
+    use tar::Archive;
+
+    let temp_dir = TempDir::new()?;
+    let profile_name = "test-overlay-secret";
+    let original_home = std::env::var("HOME").ok();
+
+    std::env::set_var("HOME", temp_dir.path());
+    let profile_dir = create_test_profile(temp_dir.path(), profile_name)?;
+
+    // The overlay isn't merged into [env] under resolve = "shell", but its
+    // secret file must stay out of the archive all the same
+    let mut toml = fs::read_to_string(profile_dir.join("profile.toml"))?;
+    toml.push_str("\n[when]\nresolve = \"shell\"\n\n[when.env.\"CI\".env]\nTOKEN = { file = \"token\" }\n");
+    fs::write(profile_dir.join("profile.toml"), toml)?;
+    fs::write(profile_dir.join("token"), "s3cret\n")?;
+
+    let output_path = temp_dir.path().join(format!("{profile_name}.zprof"));
+    let archive_path = export::export_profile(profile_name, Some(output_path))?;
+
+    let mut archive = Archive::new(GzDecoder::new(fs::File::open(&archive_path)?));
+    let found_files: Vec<String> = archive
+        .entries()?
+        .map(|entry| Ok(entry?.path()?.to_string_lossy().to_string()))
+        .collect::<Result<_>>()?;
+    assert!(found_files.contains(&"custom.sh".to_string()), "{found_files:?}");
+    assert!(!found_files.contains(&"token".to_string()), "{found_files:?}");
+
+    if let Some(home) = original_home {
+        std::env::set_var("HOME", home);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_export_child_profile_is_standalone() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let original_home = std::env::var("HOME").ok();
+
+    std::env::set_var("HOME", temp_dir.path());
+    create_test_profile(temp_dir.path(), "base")?;
+    let child_dir = temp_dir.path().join(".zsh-profiles").join("profiles").join("child");
+    fs::create_dir_all(&child_dir)?;
+    fs::write(
+        child_dir.join("profile.toml"),
+        "[profile]\nname = \"child\"\nextends = \"base\"\n\n[plugins]\nenabled = [\"fzf\"]\n",
+    )?;
+
+    // The archive can't rely on 'base' existing where it is imported
+    let archive_path = export::export_profile("child", Some(temp_dir.path().join("child.zprof")))?;
+    let archived = import::load_manifest_from_archive(&archive_path)?;
+    assert_eq!(archived.profile.name, "child");
+    assert_eq!(archived.profile.extends, None);
+    assert_eq!(archived.profile.framework, "oh-my-zsh");
+    assert_eq!(archived.plugins.enabled, ["git", "docker", "fzf"]);
+
+    if let Some(home) = original_home {
+        std::env::set_var("HOME", home);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_export_child_profile_bundles_inherited_files() -> Result<()> {
+    use flate2::read::GzDecoder;
+    use tar::Archive;
+
+    let temp_dir = TempDir::new()?;
+    let original_home = std::env::var("HOME").ok();
+
+    std::env::set_var("HOME", temp_dir.path());
+    let base_dir = create_test_profile(temp_dir.path(), "base")?;
+    let mut toml = fs::read_to_string(base_dir.join("profile.toml"))?;
+    toml.push_str("\n[hooks]\non_activate = \"hooks/up.sh\"\n\n[[snippets]]\nfile = \"snippets/base.zsh\"\nphase = \"post-plugins\"\n");
+    fs::write(base_dir.join("profile.toml"), toml)?;
+    fs::create_dir_all(base_dir.join("hooks"))?;
+    fs::write(base_dir.join("hooks").join("up.sh"), "echo up\n")?;
+    fs::create_dir_all(base_dir.join("snippets"))?;
+    fs::write(base_dir.join("snippets").join("base.zsh"), "alias b=true\n")?;
+
+    let child_dir = temp_dir.path().join(".zsh-profiles").join("profiles").join("child");
+    fs::create_dir_all(&child_dir)?;
+    fs::write(child_dir.join("profile.toml"), "[profile]\nname = \"child\"\nextends = \"base\"\n")?;
+
+    let archive_path = export::export_profile("child", Some(temp_dir.path().join("child.zprof")))?;
+
+    let mut archive = Archive::new(GzDecoder::new(fs::File::open(&archive_path)?));
+    let found_files: Vec<String> = archive
+        .entries()?
+        .map(|entry| Ok(entry?.path()?.to_string_lossy().to_string()))
+        .collect::<Result<_>>()?;
+    assert!(found_files.contains(&"inherited/base/hooks/up.sh".to_string()), "{found_files:?}");
+    assert!(found_files.contains(&"inherited/base/snippets/base.zsh".to_string()), "{found_files:?}");
+
+    // The archived manifest refers to the bundled copies, not to 'base'
+    let archived = import::load_manifest_from_archive(&archive_path)?;
+    assert_eq!(archived.hooks.on_activate.as_deref(), Some("inherited/base/hooks/up.sh"));
+    assert_eq!(archived.snippets[0].file.as_deref(), Some("inherited/base/snippets/base.zsh"));
+
+    if let Some(home) = original_home {
+        std::env::set_var("HOME", home);
+    }
+
+    Ok(())
+}
+
 #[test]
 #[serial]
 fn test_archive_contains_required_files() -> Result<()> {
@@ -0,0 +1,275 @@
+use anyhow::Result;
+use serial_test::serial;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+use zprof::core::manifest;
+
+/// Helper to set up test environment with temporary home directory
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("profiles"))?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to write a profile.toml for a profile
+fn write_profile(home_dir: &Path, name: &str, manifest: &str) -> Result<()> {
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join(name);
+    fs::create_dir_all(&profile_dir)?;
+    fs::write(profile_dir.join("profile.toml"), manifest)?;
+    Ok(())
+}
+
+/// Helper to run zprof command with custom HOME
+fn run_zprof_with_home(home_dir: &PathBuf, args: &[&str]) -> Result<std::process::Output> {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+const BASE: &str = r#"
+[profile]
+name = "base"
+framework = "oh-my-zsh"
+prompt_mode = "framework_theme"
+framework_theme = "robbyrussell"
+
+[plugins]
+enabled = ["git", "docker"]
+
+[env]
+EDITOR = "vim"
+PAGER = "less"
+
+[aliases]
+ll = "ls -la"
+
+[options]
+set = ["AUTO_CD", "HIST_IGNORE_DUPS"]
+"#;
+
+const K8S: &str = r#"
+[profile]
+name = "k8s"
+extends = "base"
+prompt_mode = "prompt_engine"
+prompt_engine = "starship"
+
+[plugins]
+enabled = ["docker", "kubectl"]
+
+[env]
+EDITOR = "nvim"
+KUBECONFIG = "~/.kube/work"
+
+[aliases]
+k = "kubectl"
+
+[options]
+unset = ["AUTO_CD"]
+"#;
+
+#[test]
+#[serial]
+fn test_load_and_validate_resolves_extends() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    std::env::set_var("HOME", &home_dir);
+    write_profile(&home_dir, "base", BASE)?;
+    write_profile(&home_dir, "k8s", K8S)?;
+
+    let resolved = manifest::load_and_validate("k8s")?;
+
+    assert_eq!(resolved.profile.name, "k8s");
+    assert_eq!(resolved.profile.extends.as_deref(), Some("base"));
+    // Framework inherited, prompt mode replaced as a whole
+    assert_eq!(resolved.profile.framework, "oh-my-zsh");
+    assert_eq!(
+        resolved.profile.prompt_mode,
+        manifest::PromptMode::PromptEngine {
+            engine: "starship".to_string()
+        }
+    );
+    // Plugins append with de-dup, parent order first
+    assert_eq!(resolved.plugins.enabled, vec!["git", "docker", "kubectl"]);
+    // Env and aliases override key by key
+    assert_eq!(resolved.env.get("EDITOR").and_then(manifest::EnvValue::as_literal), Some("nvim"));
+    assert_eq!(resolved.env.get("PAGER").and_then(manifest::EnvValue::as_literal), Some("less"));
+    assert_eq!(resolved.env.len(), 3);
+    assert_eq!(resolved.aliases.plain.len(), 2);
+    // Child's unset replaces the parent's set for the same option
+    assert_eq!(resolved.options.set, vec!["HIST_IGNORE_DUPS"]);
+    assert_eq!(resolved.options.unset, vec!["AUTO_CD"]);
+
+    // The unresolved view only has what the child defines
+    let own = manifest::load_unresolved("k8s")?;
+    assert_eq!(own.plugins.enabled, vec!["docker", "kubectl"]);
+    assert!(own.profile.framework.is_empty());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_extends_multi_level_and_descendants() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    std::env::set_var("HOME", &home_dir);
+    write_profile(&home_dir, "base", BASE)?;
+    write_profile(&home_dir, "k8s", K8S)?;
+    write_profile(
+        &home_dir,
+        "oncall",
+        r#"
+[profile]
+name = "oncall"
+extends = "k8s"
+
+[plugins]
+enabled = ["aws"]
+"#,
+    )?;
+    write_profile(
+        &home_dir,
+        "other",
+        r#"
+[profile]
+name = "other"
+framework = "zap"
+"#,
+    )?;
+
+    let resolved = manifest::load_and_validate("oncall")?;
+    assert_eq!(resolved.plugins.enabled, vec!["git", "docker", "kubectl", "aws"]);
+    assert_eq!(resolved.profile.framework, "oh-my-zsh");
+    assert_eq!(resolved.profile.extends.as_deref(), Some("k8s"));
+
+    assert_eq!(manifest::find_descendants("base")?, vec!["k8s", "oncall"]);
+    assert_eq!(manifest::find_descendants("k8s")?, vec!["oncall"]);
+    assert!(manifest::find_descendants("other")?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_inherited_secret_file_is_read_from_parent() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    std::env::set_var("HOME", &home_dir);
+    write_profile(
+        &home_dir,
+        "base",
+        "[profile]\nname = \"base\"\nframework = \"zap\"\n\n[env]\nTOKEN = { file = \"token\" }\n\n[when.env.\"CI\".env]\nCI_TOKEN = { file = \"./ci-token\" }\n",
+    )?;
+    write_profile(&home_dir, "kid", "[profile]\nname = \"kid\"\nextends = \"base\"\n\n[env]\nOWN = { file = \"own\" }\n")?;
+
+    let resolved = manifest::resolve_manifest(&home_dir.join(".zsh-profiles/profiles/kid/profile.toml"))?;
+    let file = |value: Option<&manifest::EnvValue>| match value {
+        Some(manifest::EnvValue::Secret(manifest::SecretSource::File(path))) => path.clone(),
+        other => panic!("expected a file secret, got {other:?}"),
+    };
+    assert_eq!(file(resolved.env.get("TOKEN")), "~/.zsh-profiles/profiles/base/token");
+    assert_eq!(file(resolved.when.env["CI"].env.get("CI_TOKEN")), "~/.zsh-profiles/profiles/base/ci-token");
+    // The child's own secret stays relative to its directory
+    assert_eq!(file(resolved.env.get("OWN")), "own");
+
+    let zshenv = zprof::shell::generator::generate_zshenv_from_manifest(&resolved)?;
+    assert!(!zshenv.contains("$ZDOTDIR/token"), "{zshenv}");
+    assert!(zshenv.contains("$ZDOTDIR/own"), "{zshenv}");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_extends_cycle_detected() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    std::env::set_var("HOME", &home_dir);
+    write_profile(
+        &home_dir,
+        "a",
+        "[profile]\nname = \"a\"\nframework = \"zap\"\nextends = \"b\"\n",
+    )?;
+    write_profile(&home_dir, "b", "[profile]\nname = \"b\"\nextends = \"a\"\n")?;
+
+    let err = manifest::load_and_validate("a").unwrap_err();
+    let message = format!("{err:#}");
+    assert!(message.contains("cycle"), "unexpected error: {message}");
+    assert!(message.contains("a → b → a"), "unexpected error: {message}");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_extends_cycle_detected_when_name_differs_from_directory() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    std::env::set_var("HOME", &home_dir);
+    // 'a' was copied by hand and still carries its old name
+    write_profile(
+        &home_dir,
+        "a",
+        "[profile]\nname = \"old-a\"\nframework = \"zap\"\nextends = \"b\"\n",
+    )?;
+    write_profile(&home_dir, "b", "[profile]\nname = \"b\"\nextends = \"a\"\n")?;
+
+    let err = manifest::load_and_validate("a").unwrap_err();
+    let message = format!("{err:#}");
+    assert!(message.contains("a → b → a"), "unexpected error: {message}");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_extends_missing_parent() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    std::env::set_var("HOME", &home_dir);
+    write_profile(&home_dir, "k8s", K8S)?;
+
+    let err = manifest::load_and_validate("k8s").unwrap_err();
+    assert!(format!("{err:#}").contains("extends 'base', which does not exist"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_show_resolved_flag() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "base", BASE)?;
+    write_profile(&home_dir, "k8s", K8S)?;
+
+    let output = run_zprof_with_home(&home_dir, &["show", "k8s"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Extends: base"));
+    assert!(stdout.contains("Framework: oh-my-zsh"));
+    assert!(stdout.contains("Plugins (2):"));
+
+    let output = run_zprof_with_home(&home_dir, &["show", "k8s", "--resolved"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Plugins (3):"));
+    assert!(stdout.contains("PAGER=less"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_list_shows_inherited_framework() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "base", BASE)?;
+    write_profile(&home_dir, "k8s", K8S)?;
+
+    let output = run_zprof_with_home(&home_dir, &["list"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("Warning"), "{stdout}");
+    assert!(stdout.contains("k8s"));
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to set up test environment with two profiles, "work" active
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    let profiles_dir = home_dir.join(".zsh-profiles").join("profiles");
+
+    for (name, framework, extra) in [
+        ("base", "oh-my-zsh", "[plugins]\nenabled = [\"git\"]\n"),
+        ("work", "", "[plugins]\nenabled = [\"docker\"]\n\n[env]\nEDITOR = \"vim\"\n"),
+    ] {
+        let inherit = match framework {
+            "" => "extends = \"base\"\n".to_string(),
+            framework => format!("framework = \"{framework}\"\n"),
+        };
+        fs::create_dir_all(profiles_dir.join(name))?;
+        fs::write(
+            profiles_dir.join(name).join("profile.toml"),
+            format!(
+                "schema_version = 2\n\n[profile]\nname = \"{name}\"\n{inherit}prompt_mode = \"framework_theme\"\nframework_theme = \"robbyrussell\"\ncreated = \"2025-10-31T14:30:00Z\"\nmodified = \"2025-11-01T09:00:00Z\"\n\n{extra}"
+            ),
+        )?;
+    }
+    fs::write(home_dir.join(".zsh-profiles").join("config.toml"), "active_profile = \"work\"\n")?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to run zprof command with custom HOME
+fn run_zprof_with_home(home_dir: &Path, args: &[&str]) -> Result<std::process::Output> {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.env_remove("ZPROF_PROFILE");
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+fn json(home_dir: &Path, args: &[&str]) -> Result<serde_json::Value> {
+    let output = run_zprof_with_home(home_dir, args)?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+#[test]
+fn test_format_json_for_list_show_and_current() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+
+    let list = json(&home_dir, &["list", "--format", "json"])?;
+    assert_eq!(list["format_version"], 1);
+    assert_eq!(list["profiles"][0]["name"], "base");
+    assert_eq!(list["profiles"][0]["active"], false);
+    assert_eq!(list["profiles"][1]["name"], "work");
+    assert_eq!(list["profiles"][1]["active"], true);
+    assert_eq!(list["profiles"][1]["framework"], "oh-my-zsh");
+    assert_eq!(list["profiles"][1]["framework_theme"], "robbyrussell");
+
+    // Like plain `show`, inherited plugins only appear with --resolved
+    let show = json(&home_dir, &["--format", "json", "show", "work"])?;
+    assert_eq!(show["profile"]["extends"], "base");
+    assert_eq!(show["profile"]["framework"], "oh-my-zsh");
+    assert_eq!(show["profile"]["plugins"], serde_json::json!(["docker"]));
+    assert_eq!(show["profile"]["env_vars"]["EDITOR"], "vim");
+    assert_eq!(show["profile"]["prompt_mode"]["type"], "framework_theme");
+    let show = json(&home_dir, &["show", "work", "--resolved", "--format", "json"])?;
+    assert_eq!(show["profile"]["plugins"], serde_json::json!(["git", "docker"]));
+
+    let current = json(&home_dir, &["current", "--format", "json"])?;
+    assert_eq!(current["active_profile"], "work");
+    assert_eq!(current["session_profile"], serde_json::Value::Null);
+    assert_eq!(current["profile"]["name"], "work");
+    assert_eq!(current["profile"]["created_at"], "2025-10-31T14:30:00+00:00");
+
+    Ok(())
+}
+
+#[test]
+fn test_format_toml_and_unsupported_commands() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+
+    let output = run_zprof_with_home(&home_dir, &["version", "--format", "toml"])?;
+    let version: toml::Table = toml::from_str(&String::from_utf8(output.stdout)?)?;
+    assert_eq!(version["format_version"].as_integer(), Some(1));
+    assert_eq!(version["version"].as_str(), Some(env!("CARGO_PKG_VERSION")));
+
+    let output = run_zprof_with_home(&home_dir, &["available", "plugins", "--framework", "zap", "--format", "toml"])?;
+    let plugins: toml::Table = toml::from_str(&String::from_utf8(output.stdout)?)?;
+    let plugins = plugins["plugins"].as_array().unwrap();
+    assert!(plugins.iter().all(|plugin| plugin["framework"].as_str() == Some("zap")));
+    assert!(plugins.iter().any(|plugin| plugin["name"].as_str() == Some("zsh-autosuggestions")));
+
+    // Plain output is unchanged without --format
+    let output = run_zprof_with_home(&home_dir, &["current"])?;
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Current profile: work"));
+
+    let output = run_zprof_with_home(&home_dir, &["use", "base", "--format", "json"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--format only applies to"));
+    assert_eq!(
+        fs::read_to_string(home_dir.join(".zsh-profiles").join("config.toml"))?,
+        "active_profile = \"work\"\n"
+    );
+
+    Ok(())
+}
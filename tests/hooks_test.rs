@@ -0,0 +1,107 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to set up test environment with temporary home directory
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("profiles"))?;
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("shared"))?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to write a complete profile with the given [hooks] section
+fn write_profile(home_dir: &Path, name: &str, hooks: &str) -> Result<PathBuf> {
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join(name);
+    fs::create_dir_all(&profile_dir)?;
+    fs::write(
+        profile_dir.join("profile.toml"),
+        format!(
+            "schema_version = 2\n\n[profile]\nname = \"{name}\"\nframework = \"zap\"\n\n[hooks]\n{hooks}"
+        ),
+    )?;
+    fs::write(profile_dir.join(".zshrc"), format!("# {name} profile .zshrc\n"))?;
+    Ok(profile_dir)
+}
+
+fn set_active(home_dir: &Path, name: &str) -> Result<()> {
+    fs::write(
+        home_dir.join(".zsh-profiles").join("config.toml"),
+        format!("active_profile = \"{name}\"\n"),
+    )?;
+    Ok(())
+}
+
+/// Helper to run zprof command with custom HOME
+fn run_zprof_with_home(home_dir: &PathBuf, args: &[&str]) -> Result<std::process::Output> {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.env("SHELL", "/bin/sh");
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+#[test]
+fn test_use_runs_deactivate_then_activate_hooks() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let log = home_dir.join("hooks.log");
+    let hook = |event: &str| format!("\"echo {event}:$ZPROF_PROFILE >> '{}'\"", log.display());
+
+    write_profile(&home_dir, "personal", &format!("on_deactivate = {}\n", hook("deactivate")))?;
+    write_profile(&home_dir, "work", &format!("on_activate = {}\n", hook("activate")))?;
+    set_active(&home_dir, "personal")?;
+
+    let output = run_zprof_with_home(&home_dir, &["use", "work"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read_to_string(&log)?, "deactivate:personal\nactivate:work\n");
+
+    // Re-activating the active profile runs no hooks
+    let output = run_zprof_with_home(&home_dir, &["use", "work"])?;
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(&log)?, "deactivate:personal\nactivate:work\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_use_reports_failing_hook_but_switches() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "personal", "")?;
+    write_profile(
+        &home_dir,
+        "work",
+        "on_activate = \"echo 'context not found' >&2; exit 2\"\n",
+    )?;
+    set_active(&home_dir, "personal")?;
+
+    let output = run_zprof_with_home(&home_dir, &["use", "work"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("on_activate hook for 'work' failed"), "{stderr}");
+    assert!(stderr.contains("exited with status 2"), "{stderr}");
+    assert!(stderr.contains("context not found"), "{stderr}");
+
+    let config = fs::read_to_string(home_dir.join(".zsh-profiles").join("config.toml"))?;
+    assert!(config.contains("active_profile = \"work\""), "{config}");
+
+    Ok(())
+}
+
+#[test]
+fn test_use_stops_hook_after_timeout() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "personal", "")?;
+    write_profile(&home_dir, "work", "on_activate = \"sleep 10\"\ntimeout = 1\n")?;
+    set_active(&home_dir, "personal")?;
+
+    let output = run_zprof_with_home(&home_dir, &["use", "work"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("timed out after 1s"), "{stderr}");
+
+    Ok(())
+}
@@ -318,3 +318,34 @@ fn test_import_unsupported_framework() -> Result<()> {
 //
 // These are better suited for manual integration testing with actual archives.
 // The tests above cover the critical error paths and validation logic.
+
+/// Integration test: [when] overlays matching this machine reach the generated files
+#[test]
+fn test_import_applies_when_overlays() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path();
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join("work");
+    std::fs::create_dir_all(&profile_dir)?;
+    std::fs::write(
+        profile_dir.join("profile.toml"),
+        "[profile]\nname = \"work\"\nframework = \"oh-my-zsh\"\nprompt_mode = \"framework_theme\"\nframework_theme = \"robbyrussell\"\n\n[when.env.\"ZPROF_IMPORT_OVERLAY\".env]\nPAGER = \"less\"\n",
+    )?;
+
+    let zprof = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_zprof"))
+            .env("HOME", home_dir)
+            .env("ZPROF_IMPORT_OVERLAY", "1")
+            .args(args)
+            .output()
+    };
+    let archive = home_dir.join("work.zprof");
+    let output = zprof(&["export", "work", "-o", archive.to_str().unwrap()])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = zprof(&["import", archive.to_str().unwrap(), "--name", "imported"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let zshenv = std::fs::read_to_string(home_dir.join(".zsh-profiles").join("profiles").join("imported").join(".zshenv"))?;
+    assert!(zshenv.contains("export PAGER=\"less\""), "{zshenv}");
+
+    Ok(())
+}
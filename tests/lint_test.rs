@@ -0,0 +1,145 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to set up test environment with temporary home directory
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("profiles"))?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to write a profile.toml for a profile
+fn write_profile(home_dir: &Path, name: &str, manifest: &str) -> Result<PathBuf> {
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join(name);
+    fs::create_dir_all(&profile_dir)?;
+    let path = profile_dir.join("profile.toml");
+    fs::write(&path, manifest)?;
+    Ok(path)
+}
+
+/// Helper to run zprof command with custom HOME
+fn run_zprof_with_home(home_dir: &PathBuf, args: &[&str]) -> Result<std::process::Output> {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+const CLEAN: &str = r#"schema_version = 2
+
+[profile]
+name = "clean"
+framework = "oh-my-zsh"
+prompt_mode = "framework_theme"
+framework_theme = "robbyrussell"
+
+[plugins]
+enabled = ["git"]
+"#;
+
+const SLOPPY: &str = r#"schema_version = 2
+
+[profile]
+name = "sloppy"
+framework = "zinit"
+prompt_mode = "prompt_engine"
+prompt_engine = "p10k"
+
+[plugins]
+enabled = ["zsh-autosugestions", "git", "git", "my-own-plugin"]  # load order matters
+"#;
+
+#[test]
+fn test_lint_reports_codes_and_locations() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "clean", CLEAN)?;
+    write_profile(&home_dir, "sloppy", SLOPPY)?;
+
+    let output = run_zprof_with_home(&home_dir, &["lint", "--all"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "errors should fail the command:\n{stdout}");
+
+    assert!(stdout.contains("✓ clean: no problems found"), "{stdout}");
+    assert!(stdout.contains("warning[L021]: prompt_engine 'p10k' is a short name for 'powerlevel10k'"), "{stdout}");
+    assert!(stdout.contains("warning[L001]: plugin 'zsh-autosugestions' is not in the plugin registry"), "{stdout}");
+    assert!(stdout.contains("warning[L004]: plugin 'git' is enabled more than once"), "{stdout}");
+    assert!(stdout.contains("error[L002]: zinit has no repository to install plugin 'my-own-plugin' from"), "{stdout}");
+    assert!(stdout.contains("profile.toml:10:"), "{stdout}");
+    assert!(stdout.contains("3 can be fixed automatically with 'zprof lint --fix'"), "{stdout}");
+
+    Ok(())
+}
+
+#[test]
+fn test_lint_fix_applies_safe_corrections() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let path = write_profile(&home_dir, "sloppy", SLOPPY)?;
+
+    let output = run_zprof_with_home(&home_dir, &["lint", "sloppy", "--fix"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("applied 3 fix(es)"), "{stdout}");
+
+    let content = fs::read_to_string(&path)?;
+    assert!(content.contains("]  # load order matters\n"), "{content}");
+    let fixed: toml::Table = toml::from_str(&content)?;
+    assert_eq!(fixed["profile"]["prompt_engine"].as_str(), Some("powerlevel10k"));
+    let enabled: Vec<&str> = fixed["plugins"]["enabled"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|v| v.as_str())
+        .collect();
+    assert_eq!(enabled, vec!["zsh-autosuggestions", "git", "my-own-plugin"]);
+
+    // The unfixable error is still reported
+    assert!(!output.status.success());
+    assert!(stdout.contains("error[L002]"), "{stdout}");
+    assert!(!stdout.contains("warning[L0"), "{stdout}");
+
+    let backups = fs::read_dir(home_dir.join(".zsh-profiles").join("cache").join("backups"))?
+        .map(|entry| entry.map(|e| e.file_name().to_string_lossy().to_string()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    assert_eq!(backups.len(), 1);
+    assert!(backups[0].starts_with("sloppy.profile.toml.pre-lint."));
+
+    Ok(())
+}
+
+#[test]
+fn test_lint_points_inherited_problems_at_parent() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(
+        &home_dir,
+        "base",
+        "[profile]\nname = \"base\"\nframework = \"oh-my-zsh\"\n\n[plugins]\nenabled = [\"git\", \"zsh-autosugestions\"]\n",
+    )?;
+    write_profile(
+        &home_dir,
+        "child",
+        "[profile]\nname = \"child\"\nextends = \"base\"\n\n[plugins]\nenabled = [\"docker\", \"fzf\"]\n",
+    )?;
+
+    let output = run_zprof_with_home(&home_dir, &["lint", "child"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("warning[L001]: plugin 'zsh-autosugestions' is not in the plugin registry"), "{stdout}");
+    assert!(stdout.contains("= note: inherited from profile 'base'; fix it there"), "{stdout}");
+    // Nothing in the child's own file is underlined
+    assert!(!stdout.contains('^'), "{stdout}");
+    assert!(stdout.contains("→ Run 'zprof lint base --fix' to correct it in 'base'"), "{stdout}");
+    assert!(!stdout.contains("can be fixed automatically"), "{stdout}");
+
+    // --fix only edits the child's own file, so it leaves the problem to the parent
+    let child = fs::read_to_string(home_dir.join(".zsh-profiles").join("profiles").join("child").join("profile.toml"))?;
+    let output = run_zprof_with_home(&home_dir, &["lint", "child", "--fix"])?;
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("applied"));
+    assert_eq!(
+        fs::read_to_string(home_dir.join(".zsh-profiles").join("profiles").join("child").join("profile.toml"))?,
+        child
+    );
+
+    Ok(())
+}
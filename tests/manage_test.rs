@@ -0,0 +1,214 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to set up test environment with temporary home directory
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("profiles"))?;
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("shared"))?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to write a profile.toml and .zshrc for a profile
+fn write_profile(home_dir: &Path, name: &str, manifest: &str) -> Result<PathBuf> {
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join(name);
+    fs::create_dir_all(&profile_dir)?;
+    fs::write(profile_dir.join("profile.toml"), manifest)?;
+    fs::write(profile_dir.join(".zshrc"), "# zprof profile\n")?;
+    Ok(profile_dir)
+}
+
+/// Helper to run zprof command with custom HOME
+fn run_zprof_with_home(home_dir: &PathBuf, args: &[&str]) -> Result<std::process::Output> {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+const WORK: &str = r#"schema_version = 2
+
+[profile]
+name = "work"
+framework = "oh-my-zsh"
+prompt_mode = "framework_theme"
+framework_theme = "robbyrussell"
+
+[plugins]
+# git first
+enabled = ["git"]
+
+[env]
+EDITOR = "vim"
+"#;
+
+const CHILD: &str = r#"schema_version = 2
+
+[profile]
+name = "child"
+extends = "work"
+"#;
+
+fn manifest(home_dir: &Path, name: &str) -> Result<String> {
+    Ok(fs::read_to_string(
+        home_dir.join(".zsh-profiles").join("profiles").join(name).join("profile.toml"),
+    )?)
+}
+
+#[test]
+fn test_plugin_add_remove_and_list() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let profile_dir = write_profile(&home_dir, "work", WORK)?;
+
+    let output = run_zprof_with_home(&home_dir, &["plugin", "add", "work", "docker", "fzf"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✓ Enabled plugin 'docker' in 'work'"), "{stdout}");
+    assert!(stdout.contains("Backup:"), "{stdout}");
+
+    let content = manifest(&home_dir, "work")?;
+    assert!(content.contains("# git first\nenabled = [\"git\", \"docker\", \"fzf\"]"), "{content}");
+    let zshrc = fs::read_to_string(profile_dir.join(".zshrc"))?;
+    assert!(zshrc.contains("docker"), "{zshrc}");
+
+    let output = run_zprof_with_home(&home_dir, &["plugin", "list", "work"])?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("  git\n  docker\n  fzf\n"), "{stdout}");
+
+    let output = run_zprof_with_home(&home_dir, &["plugin", "remove", "work", "docker"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let content = manifest(&home_dir, "work")?;
+    assert!(content.contains("enabled = [\"git\", \"fzf\"]"), "{content}");
+
+    let output = run_zprof_with_home(&home_dir, &["plugin", "remove", "work", "docker"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not enabled in 'work'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_plugin_add_rejects_misspelled_plugin() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "work", WORK)?;
+
+    let output = run_zprof_with_home(&home_dir, &["plugin", "add", "work", "zsh-autosugestions"])?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Did you mean 'zsh-autosuggestions'?"), "{stderr}");
+    assert_eq!(manifest(&home_dir, "work")?, WORK);
+
+    Ok(())
+}
+
+#[test]
+fn test_plugin_remove_inherited_plugin_points_at_parent() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "work", WORK)?;
+    write_profile(&home_dir, "child", CHILD)?;
+
+    let output = run_zprof_with_home(&home_dir, &["plugin", "remove", "child", "git"])?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("inherited from 'work'"), "{stderr}");
+
+    Ok(())
+}
+
+#[test]
+fn test_theme_and_engine_set() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let profile_dir = write_profile(&home_dir, "work", WORK)?;
+    // Already installed, so nothing is downloaded
+    fs::create_dir_all(home_dir.join(".zprof").join("engines").join("pure"))?;
+
+    let output = run_zprof_with_home(&home_dir, &["engine", "set", "work", "pure"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Prompt: framework theme 'robbyrussell' → prompt engine 'pure'"),
+        "{stdout}"
+    );
+    let content = manifest(&home_dir, "work")?;
+    assert!(content.contains("prompt_mode = \"prompt_engine\""), "{content}");
+    assert!(content.contains("prompt_engine = \"pure\""), "{content}");
+    assert!(!content.contains("framework_theme"), "{content}");
+    assert!(fs::read_to_string(profile_dir.join(".zshrc"))?.contains("prompt pure"));
+
+    let output = run_zprof_with_home(&home_dir, &["theme", "set", "work", "agnoster"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let content = manifest(&home_dir, "work")?;
+    assert!(content.contains("framework_theme = \"agnoster\""), "{content}");
+    assert!(!content.contains("prompt_engine"), "{content}");
+
+    let output = run_zprof_with_home(&home_dir, &["engine", "set", "work", "starshp"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Did you mean 'starship'?"));
+
+    Ok(())
+}
+
+#[test]
+fn test_theme_set_requires_an_installed_theme() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let profile_dir = write_profile(&home_dir, "work", WORK)?;
+    let themes_dir = profile_dir.join(".oh-my-zsh").join("themes");
+    fs::create_dir_all(&themes_dir)?;
+    fs::write(themes_dir.join("agnoster.zsh-theme"), "# agnoster\n")?;
+
+    let output = run_zprof_with_home(&home_dir, &["theme", "set", "work", "agnoster"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // A theme oh-my-zsh can't find fails before profile.toml is touched
+    let before = manifest(&home_dir, "work")?;
+    let output = run_zprof_with_home(&home_dir, &["theme", "set", "work", "my-own-prompt"])?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Theme 'my-own-prompt' is not installed in 'work'"), "{stderr}");
+    assert!(stderr.contains("my-own-prompt.zsh-theme"), "{stderr}");
+    assert_eq!(manifest(&home_dir, "work")?, before);
+
+    // Themes that are prompt engines go through the engine installer, like 'engine set'
+    fs::create_dir_all(home_dir.join(".zprof").join("engines").join("pure"))?;
+    let output = run_zprof_with_home(&home_dir, &["theme", "set", "work", "pure"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(manifest(&home_dir, "work")?.contains("framework_theme = \"pure\""));
+
+    Ok(())
+}
+
+#[test]
+fn test_env_set_and_unset() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let profile_dir = write_profile(&home_dir, "work", WORK)?;
+    write_profile(&home_dir, "child", CHILD)?;
+
+    let output = run_zprof_with_home(&home_dir, &["env", "set", "work", "EDITOR=nvim", "PAGER=less -R"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let content = manifest(&home_dir, "work")?;
+    assert!(content.contains("EDITOR = \"nvim\""), "{content}");
+    assert!(content.contains("PAGER = \"less -R\""), "{content}");
+    let zshenv = fs::read_to_string(profile_dir.join(".zshenv"))?;
+    assert!(zshenv.contains("EDITOR"), "{zshenv}");
+
+    let output = run_zprof_with_home(&home_dir, &["env", "unset", "work", "PAGER"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(!manifest(&home_dir, "work")?.contains("PAGER"));
+
+    let output = run_zprof_with_home(&home_dir, &["env", "unset", "child", "EDITOR"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("inherited from 'work'"));
+
+    // Invalid keys are rejected and the file is left alone
+    let before = manifest(&home_dir, "work")?;
+    let output = run_zprof_with_home(&home_dir, &["env", "set", "work", "BAD-KEY=1"])?;
+    assert!(!output.status.success());
+    assert_eq!(manifest(&home_dir, "work")?, before);
+
+    Ok(())
+}
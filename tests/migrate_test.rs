@@ -0,0 +1,159 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to set up test environment with temporary home directory
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("profiles"))?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to write a profile.toml for a profile
+fn write_profile(home_dir: &Path, name: &str, manifest: &str) -> Result<PathBuf> {
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join(name);
+    fs::create_dir_all(&profile_dir)?;
+    let path = profile_dir.join("profile.toml");
+    fs::write(&path, manifest)?;
+    Ok(path)
+}
+
+/// Helper to run zprof command with custom HOME
+fn run_zprof_with_home(home_dir: &PathBuf, args: &[&str]) -> Result<std::process::Output> {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+const LEGACY: &str = r#"# Shell for the old projects
+[profile]
+name = "legacy"
+framework = "oh-my-zsh"
+theme = "robbyrussell"
+created = "2025-01-01T00:00:00Z"
+modified = "2025-01-01T00:00:00Z"
+
+[plugins]
+enabled = ["git"]  # the essentials
+"#;
+
+const CURRENT: &str = r#"schema_version = 2
+
+[profile]
+name = "current"
+framework = "zap"
+prompt_mode = "framework_theme"
+framework_theme = ""
+"#;
+
+fn backups(home_dir: &Path) -> Result<Vec<String>> {
+    let dir = home_dir.join(".zsh-profiles").join("cache").join("backups");
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        names.push(entry?.file_name().to_string_lossy().to_string());
+    }
+    Ok(names)
+}
+
+#[test]
+fn test_migrate_dry_run_leaves_file_untouched() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let path = write_profile(&home_dir, "legacy", LEGACY)?;
+
+    let output = run_zprof_with_home(&home_dir, &["migrate", "legacy", "--dry-run"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("would migrate schema version 1 → 2"), "{stdout}");
+    assert!(stdout.contains("profile.theme"), "{stdout}");
+
+    assert_eq!(fs::read_to_string(&path)?, LEGACY);
+    assert!(backups(&home_dir)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_migrate_rewrites_manifest_and_backs_up() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let path = write_profile(&home_dir, "legacy", LEGACY)?;
+
+    let output = run_zprof_with_home(&home_dir, &["migrate", "legacy"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let migrated = fs::read_to_string(&path)?;
+    assert!(migrated.starts_with("schema_version = 2"), "{migrated}");
+    assert!(migrated.contains("framework_theme = \"robbyrussell\""), "{migrated}");
+    assert!(!migrated.contains("\ntheme ="), "{migrated}");
+    // Hand-written comments survive the rewrite
+    assert!(migrated.contains("\n# Shell for the old projects\n[profile]\n"), "{migrated}");
+    assert!(migrated.contains("enabled = [\"git\"]  # the essentials"), "{migrated}");
+
+    let backups = backups(&home_dir)?;
+    assert_eq!(backups.len(), 1);
+    assert!(backups[0].starts_with("legacy.profile.toml.pre-migrate."));
+
+    // Loading no longer warns about the schema
+    let output = run_zprof_with_home(&home_dir, &["show", "legacy"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("zprof migrate"));
+
+    Ok(())
+}
+
+#[test]
+fn test_load_warns_about_outdated_schema() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "legacy", LEGACY)?;
+
+    let output = run_zprof_with_home(&home_dir, &["show", "legacy"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("schema version 1"), "{stderr}");
+    assert!(stderr.contains("zprof migrate legacy"), "{stderr}");
+
+    // Only commands about that one profile mention it
+    for args in [&["list"][..], &["show", "legacy", "--format", "json"], &["lint", "--all"]] {
+        let output = run_zprof_with_home(&home_dir, args)?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!stderr.contains("schema version"), "{args:?}: {stderr}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_migrate_all() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let legacy_path = write_profile(&home_dir, "legacy", LEGACY)?;
+    let current_path = write_profile(&home_dir, "current", CURRENT)?;
+
+    let output = run_zprof_with_home(&home_dir, &["migrate", "--all"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("current: already on schema version 2"), "{stdout}");
+    assert!(stdout.contains("legacy: migrated schema version 1 → 2"), "{stdout}");
+
+    // Up-to-date profiles are left byte for byte as they were
+    assert_eq!(fs::read_to_string(&current_path)?, CURRENT);
+    assert!(fs::read_to_string(&legacy_path)?.starts_with("schema_version = 2"));
+    assert_eq!(backups(&home_dir)?.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_migrate_requires_profile_or_all() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+
+    let output = run_zprof_with_home(&home_dir, &["migrate"])?;
+    assert!(!output.status.success());
+
+    Ok(())
+}
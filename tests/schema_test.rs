@@ -0,0 +1,45 @@
+use anyhow::Result;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to run zprof command with custom HOME
+fn run_zprof_with_home(home_dir: &PathBuf, args: &[&str]) -> Result<std::process::Output> {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+#[test]
+fn test_schema_command_prints_json_schema() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+
+    let output = run_zprof_with_home(&home_dir, &["schema"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(schema["$schema"], "https://json-schema.org/draft/2020-12/schema");
+    assert!(schema["properties"]["profile"].is_object());
+    assert!(schema["$defs"]["ProfileSection"]["properties"]["framework"]["enum"].is_array());
+
+    Ok(())
+}
+
+#[test]
+fn test_schema_write_matches_printed_schema() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles"))?;
+
+    let output = run_zprof_with_home(&home_dir, &["schema", "--write"])?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let written = fs::read_to_string(home_dir.join(".zsh-profiles").join("profile.schema.json"))?;
+    let printed = run_zprof_with_home(&home_dir, &["schema"])?.stdout;
+    assert_eq!(written, String::from_utf8(printed)?);
+
+    Ok(())
+}
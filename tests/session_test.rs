@@ -0,0 +1,112 @@
+use anyhow::Result;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Helper to set up test environment with temporary home directory and a
+/// stand-in `zsh` that reports how it was started
+fn setup_test_env() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let home_dir = temp_dir.path().to_path_buf();
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("profiles"))?;
+    fs::create_dir_all(home_dir.join(".zsh-profiles").join("shared"))?;
+
+    let zsh = home_dir.join("bin").join("zsh");
+    fs::create_dir_all(zsh.parent().unwrap())?;
+    fs::write(
+        &zsh,
+        "#!/bin/sh\necho \"ZDOTDIR=$ZDOTDIR\"\necho \"ZPROF_PROFILE=$ZPROF_PROFILE\"\nfor arg in \"$@\"; do echo \"arg=$arg\"; done\nexit 3\n",
+    )?;
+    fs::set_permissions(&zsh, fs::Permissions::from_mode(0o755))?;
+    Ok((temp_dir, home_dir))
+}
+
+/// Helper to write a profile.toml and .zshrc for a profile
+fn write_profile(home_dir: &Path, name: &str) -> Result<PathBuf> {
+    let profile_dir = home_dir.join(".zsh-profiles").join("profiles").join(name);
+    fs::create_dir_all(&profile_dir)?;
+    fs::write(
+        profile_dir.join("profile.toml"),
+        format!(
+            "[profile]\nname = \"{name}\"\nframework = \"oh-my-zsh\"\nprompt_mode = \"framework_theme\"\nframework_theme = \"robbyrussell\"\n\n[plugins]\nenabled = [\"git\"]\n"
+        ),
+    )?;
+    fs::write(profile_dir.join(".zshrc"), "# zprof profile\n")?;
+    Ok(profile_dir)
+}
+
+/// Helper to run zprof command with custom HOME and the stand-in zsh on PATH
+fn run_zprof_with_home(home_dir: &Path, args: &[&str], session: Option<&str>) -> Result<std::process::Output> {
+    let path = format!("{}:{}", home_dir.join("bin").display(), std::env::var("PATH").unwrap_or_default());
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_zprof"));
+    cmd.env("HOME", home_dir);
+    cmd.env("PATH", path);
+    match session {
+        Some(profile) => cmd.env("ZPROF_PROFILE", profile),
+        None => cmd.env_remove("ZPROF_PROFILE"),
+    };
+    cmd.args(args);
+    Ok(cmd.output()?)
+}
+
+#[test]
+fn test_shell_and_run_start_zsh_without_activating() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    let profile_dir = write_profile(&home_dir, "work")?;
+    let config_path = home_dir.join(".zsh-profiles").join("config.toml");
+    fs::write(&config_path, "active_profile = \"personal\"\n")?;
+
+    let output = run_zprof_with_home(&home_dir, &["shell", "work"], None)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(3), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains(&format!("ZDOTDIR={}", profile_dir.display())), "{stdout}");
+    assert!(stdout.contains("ZPROF_PROFILE=work\narg=-i\n"), "{stdout}");
+
+    let output = run_zprof_with_home(&home_dir, &["run", "work", "--", "echo", "it's here"], None)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stdout.contains("arg=-i\narg=-c\narg=echo 'it'\\''s here'\n"), "{stdout}");
+
+    // Neither command changes what new terminals get
+    assert_eq!(fs::read_to_string(&config_path)?, "active_profile = \"personal\"\n");
+    assert!(!home_dir.join(".zshenv").exists());
+
+    let output = run_zprof_with_home(&home_dir, &["run", "missing", "--", "true"], None)?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Profile 'missing' not found"));
+
+    // Paths that leave the profiles directory are refused before zsh starts
+    let outside = home_dir.join("repo").join(".evil");
+    fs::create_dir_all(&outside)?;
+    fs::copy(profile_dir.join("profile.toml"), outside.join("profile.toml"))?;
+    fs::copy(profile_dir.join(".zshrc"), outside.join(".zshrc"))?;
+    for name in ["../../repo/.evil", &outside.display().to_string()] {
+        let output = run_zprof_with_home(&home_dir, &["shell", name], None)?;
+        assert_ne!(output.status.code(), Some(3), "{name}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid profile name"), "{name}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_current_reports_session_profile() -> Result<()> {
+    let (_temp, home_dir) = setup_test_env()?;
+    write_profile(&home_dir, "work")?;
+    write_profile(&home_dir, "personal")?;
+    fs::write(home_dir.join(".zsh-profiles").join("config.toml"), "active_profile = \"personal\"\n")?;
+
+    let output = run_zprof_with_home(&home_dir, &["current"], Some("work"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("Current profile: work (this session only)"), "{stdout}");
+    assert!(stdout.contains("New terminals use 'personal'"), "{stdout}");
+
+    let output = run_zprof_with_home(&home_dir, &["current"], None)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Current profile: personal\n"), "{stdout}");
+
+    Ok(())
+}
@@ -111,6 +111,7 @@ fn test_config_serialization() -> Result<()> {
     let config = Config {
         active_profile: Some("my-profile".to_string()),
         default_framework: Some("oh-my-zsh".to_string()),
+        ..Default::default()
     };
 
     // Write to file